@@ -1,10 +1,13 @@
 extern crate mws;
 use chrono::offset::TimeZone;
 use chrono::{DateTime, NaiveDate, Utc};
+use encoding_rs::DecoderResult;
+use std::io::{self, Read, Write};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
 use mws::client::Client;
+use mws::reports::{ReportProcessingStatus, ReportType};
 
 mod env;
 
@@ -23,7 +26,7 @@ struct Opt {
 enum Command {
   ReportRequest {
     #[structopt(long = "report_type")]
-    report_type: String,
+    report_type: ReportType,
     #[structopt(long = "start_date", parse(try_from_str))]
     start_date: Option<NaiveDate>,
     #[structopt(long = "end_date", parse(try_from_str))]
@@ -39,11 +42,31 @@ enum Command {
   },
   ReportListRequestByTypes {
     #[structopt(long = "type")]
-    types: Vec<String>,
+    types: Vec<ReportType>,
   },
   ReportScheduleList {
     #[structopt(long = "type")]
-    types: Vec<String>,
+    types: Vec<ReportType>,
+  },
+  ReportCount {
+    #[structopt(long = "type")]
+    types: Vec<ReportType>,
+    #[structopt(long = "acknowledged")]
+    acknowledged: Option<bool>,
+  },
+  ReportRequestCount {
+    #[structopt(long = "type")]
+    types: Vec<ReportType>,
+    #[structopt(long = "status")]
+    statuses: Vec<ReportProcessingStatus>,
+  },
+  ReportRequestCancel {
+    #[structopt(long = "id")]
+    ids: Vec<String>,
+    #[structopt(long = "type")]
+    types: Vec<ReportType>,
+    #[structopt(long = "status")]
+    statuses: Vec<ReportProcessingStatus>,
   },
   ReportGet {
     #[structopt(long = "id")]
@@ -51,6 +74,7 @@ enum Command {
     #[structopt(long = "out", parse(from_os_str))]
     out: PathBuf,
   },
+  /// Converts a Shift_JIS file to UTF-8, fails on malformed input
   EncodingConvJp {
     #[structopt(long = "in", parse(from_os_str))]
     input: PathBuf,
@@ -184,6 +208,56 @@ fn main() {
 
       println!("{:#?}", res)
     }
+    Command::ReportCount {
+      types,
+      acknowledged,
+    } => {
+      use mws::reports::*;
+      let res = GetReportCount(
+        &client,
+        GetReportCountParameters {
+          ReportTypeList: non_empty(types),
+          Acknowledged: acknowledged,
+          ..Default::default()
+        },
+      )
+      .unwrap();
+
+      println!("{:#?}", res)
+    }
+    Command::ReportRequestCount { types, statuses } => {
+      use mws::reports::*;
+      let res = GetReportRequestCount(
+        &client,
+        GetReportRequestCountParameters {
+          ReportTypeList: non_empty(types),
+          ReportProcessingStatusList: non_empty(statuses),
+          ..Default::default()
+        },
+      )
+      .unwrap();
+
+      println!("{:#?}", res)
+    }
+    Command::ReportRequestCancel {
+      ids,
+      types,
+      statuses,
+    } => {
+      use mws::reports::*;
+      let res = CancelReportRequests(
+        &client,
+        CancelReportRequestsParameters {
+          ReportRequestIdList: non_empty(ids),
+          ReportTypeList: non_empty(types),
+          ReportProcessingStatusList: non_empty(statuses),
+          ..Default::default()
+        },
+      )
+      .unwrap();
+
+      println!("{:#?}", res)
+    }
     Command::ListSubscriptions { marketplace_id } => {
       use mws::subscriptions::*;
       let res = ListSubscriptions(
@@ -227,14 +301,12 @@ fn main() {
       GetReport(&client, id, &mut out).unwrap();
     }
     Command::EncodingConvJp { input, out } => {
-      use encoding_rs::*;
-      let bytes = std::fs::read(input).unwrap();
-      let (cow, encoding_used, had_errors) = SHIFT_JIS.decode(&bytes);
-      if had_errors {
-        panic!("decode error.")
+      let input = std::fs::File::open(input).unwrap();
+      let mut out = std::fs::File::create(out).unwrap();
+      if let Err(err) = convert_shift_jis(input, &mut out) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
       }
-      println!("encoding_used: {:?}", encoding_used);
-      std::fs::write(out, cow.as_ref()).unwrap();
     }
     Command::ProductGetLowestPricedOffersForSKU {
       marketplace_id,
@@ -327,10 +399,54 @@ fn get_client(env: &Env) -> Client {
     mws_auth_token: env.auth_token.clone(),
     aws_access_key_id: env.access_key_id.clone(),
     secret_key: env.secret_key.clone(),
+    ..Default::default()
   };
   Client::new(opts).unwrap()
 }
 
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+  if values.is_empty() {
+    None
+  } else {
+    Some(values)
+  }
+}
+
 fn get_utc_datetime(date: NaiveDate) -> DateTime<Utc> {
   Utc.from_utc_date(&date).and_hms(0, 0, 0)
 }
+
+/// Converts `input` from Shift_JIS to UTF-8. Unlike `tdff::decoding_reader`, malformed input is
+/// an error instead of being replaced with U+FFFD. A UTF-8 or UTF-16 byte order mark takes
+/// precedence over Shift_JIS.
+fn convert_shift_jis<R: Read, W: Write>(mut input: R, out: &mut W) -> io::Result<()> {
+  let mut decoder = encoding_rs::SHIFT_JIS.new_decoder();
+  let mut buf = [0; 8192];
+  let mut decoded = [0; 8192];
+  let mut offset = 0;
+  loop {
+    let size = input.read(&mut buf)?;
+    let last = size == 0;
+    let mut src = &buf[..size];
+    loop {
+      let (result, read, written) =
+        decoder.decode_to_utf8_without_replacement(src, &mut decoded, last);
+      out.write_all(&decoded[..written])?;
+      src = &src[read..];
+      offset += read;
+      match result {
+        DecoderResult::InputEmpty => break,
+        DecoderResult::OutputFull => {}
+        DecoderResult::Malformed(..) => {
+          return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed Shift_JIS input before byte {}", offset),
+          ))
+        }
+      }
+    }
+    if last {
+      return Ok(());
+    }
+  }
+}
@@ -375,11 +375,11 @@ pub fn derive_from_diff_row(input: TokenStream) -> TokenStream {
           if v.contains(',') {
             let keys: Vec<_> = v.split(',').map(|s| s.trim()).collect();
             quote! {
-              #(#keys)|* => record.#ident = mws::tdff::FromTdffField::parse_tdff_field(k, &v)?,
+              #(#keys)|* => record.#ident = mws::tdff::parse_field(k, v)?,
             }
           } else {
             quote! {
-              #v => record.#ident = mws::tdff::FromTdffField::parse_tdff_field(k, &v)?,
+              #v => record.#ident = mws::tdff::parse_field(k, v)?,
             }
           }
         }
@@ -388,11 +388,11 @@ pub fn derive_from_diff_row(input: TokenStream) -> TokenStream {
           let ident_underscore_str = ident_str.replace("-", "_");
           if ident_str == ident_underscore_str {
             quote! {
-              #ident_str => record.#ident = mws::tdff::FromTdffField::parse_tdff_field(k, v)?,
+              #ident_str => record.#ident = mws::tdff::parse_field(k, v)?,
             }
           } else {
             quote! {
-              #ident_str => record.#ident = mws::tdff::FromTdffField::parse_tdff_field(k, v)?,
+              #ident_str => record.#ident = mws::tdff::parse_field(k, v)?,
             }
           }
         }
@@ -404,7 +404,6 @@ pub fn derive_from_diff_row(input: TokenStream) -> TokenStream {
     impl mws::tdff::FromTdffRow for #name
     {
       fn from_tdff_row(pairs: &::mws::tdff::TdffRow) -> mws::result::MwsResult<Self> {
-        use mws::tdff::FromTdffField;
         let mut record = #name::default();
         for (k, v) in pairs {
           let k = k as &str;
@@ -0,0 +1,213 @@
+//! Tests of `AsyncClient`, run with `cargo test -p mws-tests --features async`
+
+#![cfg(feature = "async")]
+
+extern crate mws;
+
+mod common;
+
+use common::{TestResponse, TestServer};
+use mws::client::{AsyncClient, ClientOptions, RetryPolicy};
+use mws::result::MwsError;
+use std::time::Duration;
+
+fn get_client(server: &TestServer) -> AsyncClient {
+  get_client_with_options(server, Default::default())
+}
+
+fn get_client_with_options(server: &TestServer, options: ClientOptions) -> AsyncClient {
+  AsyncClient::new(ClientOptions {
+    endpoint: server.url.clone(),
+    seller_id: "SELLER".to_owned(),
+    aws_access_key_id: "KEY".to_owned(),
+    secret_key: "SECRET".to_owned(),
+    ..options
+  })
+  .unwrap()
+}
+
+#[tokio::test]
+async fn request_xml() {
+  use mws::reports::nonblocking::GetReportList;
+
+  let server = TestServer::start(vec![TestResponse::new(
+    200,
+    &include_bytes!("./fixtures/responses/GetReportList.xml")[..],
+  )]);
+  let client = get_client(&server);
+
+  let res = GetReportList(&client, Default::default()).await.unwrap();
+  assert_eq!(res.HasNext, true);
+  assert_eq!(res.ReportInfo.len(), 1);
+  assert_eq!(res.ReportInfo[0].ReportId, "898899473");
+
+  let requests = server.requests();
+  assert_eq!(requests.len(), 1);
+  assert_eq!(requests[0].method, "POST");
+  assert!(requests[0].path.starts_with("/?"));
+  assert_eq!(
+    requests[0].param("Action"),
+    Some("GetReportList".to_owned())
+  );
+  assert_eq!(requests[0].param("SellerId"), Some("SELLER".to_owned()));
+}
+
+#[tokio::test]
+async fn error_response() {
+  use mws::reports::nonblocking::GetReportList;
+
+  let server = TestServer::start(vec![TestResponse::new(
+    503,
+    &include_bytes!("./fixtures/responses/ErrorResponse.xml")[..],
+  )]);
+  let client = get_client(&server);
+
+  match GetReportList(&client, Default::default()).await {
+    Err(MwsError::ErrorResponse(resp)) => {
+      assert_eq!(resp.status.as_u16(), 503);
+      assert!(resp.info.is_some());
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[tokio::test]
+async fn get_report_content_md5() {
+  use mws::reports::nonblocking::GetReport;
+
+  let report = &include_bytes!("./fixtures/report.tdff")[..];
+  let server = TestServer::start(vec![
+    TestResponse::new(200, report).header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    TestResponse::new(200, &report[..report.len() / 2])
+      .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+  ]);
+  let client = get_client(&server);
+
+  let mut out = vec![];
+  let (size, content_md5) = GetReport(&client, "898899473".to_owned(), &mut out)
+    .await
+    .unwrap();
+  assert_eq!(&out[..], report);
+  assert_eq!(size, report.len() as u64);
+  assert_eq!(content_md5, "agLXebc4tF+Ux/Pa1cmomg==");
+
+  let mut out = vec![];
+  match GetReport(&client, "898899473".to_owned(), &mut out).await {
+    Err(MwsError::ContentMD5Mismatch { expected, size, .. }) => {
+      assert_eq!(expected, "agLXebc4tF+Ux/Pa1cmomg==");
+      assert_eq!(size, (report.len() / 2) as u64);
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+  assert_eq!(
+    server.requests()[1].param("ReportId"),
+    Some("898899473".to_owned())
+  );
+}
+
+#[tokio::test]
+async fn retry() {
+  use mws::reports::nonblocking::GetReportList;
+
+  let error = &include_bytes!("./fixtures/responses/ErrorResponse.xml")[..];
+  let server = TestServer::start(vec![
+    TestResponse::new(503, error),
+    TestResponse::new(503, error),
+    TestResponse::new(
+      200,
+      &include_bytes!("./fixtures/responses/GetReportList.xml")[..],
+    ),
+  ]);
+  let client = get_client_with_options(
+    &server,
+    ClientOptions {
+      retry_policy: RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+        jitter: false,
+      },
+      ..Default::default()
+    },
+  );
+
+  let res = GetReportList(&client, Default::default()).await.unwrap();
+  assert_eq!(res.ReportInfo[0].ReportId, "898899473");
+  assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn throttle() {
+  use mws::reports::nonblocking::GetReportList;
+  use mws::throttle::{Quota, Throttler};
+  use std::time::Instant;
+
+  let server = TestServer::start(
+    (0..3)
+      .map(|_| {
+        TestResponse::new(
+          200,
+          &include_bytes!("./fixtures/responses/GetReportList.xml")[..],
+        )
+      })
+      .collect(),
+  );
+  let throttler = Throttler::new();
+  throttler.set_quota(
+    "/",
+    "GetReportList",
+    Quota::new(1, 1, Duration::from_millis(50)),
+  );
+  let client = get_client_with_options(
+    &server,
+    ClientOptions {
+      throttler: Some(throttler),
+      ..Default::default()
+    },
+  );
+
+  let started = Instant::now();
+  for _ in 0..3 {
+    GetReportList(&client, Default::default()).await.unwrap();
+  }
+  // 1 request is available immediately, the other 2 are restored after 50ms each
+  assert!(started.elapsed() >= Duration::from_millis(100));
+  assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn clock_skew() {
+  use chrono::{DateTime, Duration, Utc};
+  use mws::reports::nonblocking::GetReportList;
+
+  fn timestamp(request: &common::ReceivedRequest) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&request.param("Timestamp").unwrap())
+      .unwrap()
+      .with_timezone(&Utc)
+  }
+
+  let server_time = (Utc::now() + Duration::hours(1)).to_rfc2822();
+  let server = TestServer::start(vec![
+    TestResponse::new(
+      403,
+      &include_bytes!("./fixtures/responses/RequestExpired.xml")[..],
+    )
+    .header("Date", &server_time),
+    TestResponse::new(
+      200,
+      &include_bytes!("./fixtures/responses/GetReportList.xml")[..],
+    ),
+  ]);
+  let client = get_client(&server);
+  assert_eq!(client.clock_offset(), Duration::zero());
+
+  let res = GetReportList(&client, Default::default()).await.unwrap();
+  assert_eq!(res.ReportInfo[0].ReportId, "898899473");
+  let offset = client.clock_offset();
+  assert!((offset - Duration::hours(1)).num_seconds().abs() <= 2);
+
+  let requests = server.requests();
+  assert_eq!(requests.len(), 2);
+  let skew = timestamp(&requests[1]) - timestamp(&requests[0]);
+  assert!((skew - Duration::hours(1)).num_seconds().abs() <= 2);
+}
@@ -0,0 +1,180 @@
+//! Local HTTP server shared by the integration tests
+
+// every test crate uses a part of the helpers
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A canned response served by `TestServer`
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl TestResponse {
+  pub fn new<B: Into<Vec<u8>>>(status: u16, body: B) -> Self {
+    TestResponse {
+      status,
+      headers: vec![],
+      body: body.into(),
+    }
+  }
+
+  pub fn header(mut self, name: &str, value: &str) -> Self {
+    self.headers.push((name.to_owned(), value.to_owned()));
+    self
+  }
+}
+
+/// A request received by `TestServer`
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+  pub method: String,
+  /// The path, with the query string
+  pub path: String,
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+  pub fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(k, _)| k.eq_ignore_ascii_case(name))
+      .map(|(_, v)| v.as_ref())
+  }
+
+  /// A parameter of the query string
+  pub fn param(&self, key: &str) -> Option<String> {
+    let query = self.path.splitn(2, '?').nth(1).unwrap_or_default();
+    find_pair(query, key)
+  }
+
+  pub fn form(&self) -> Vec<(String, String)> {
+    parse_pairs(&String::from_utf8(self.body.clone()).unwrap())
+  }
+
+  pub fn form_value(&self, key: &str) -> Option<String> {
+    find_pair(&String::from_utf8(self.body.clone()).unwrap(), key)
+  }
+}
+
+fn parse_pairs(encoded: &str) -> Vec<(String, String)> {
+  encoded
+    .split('&')
+    .filter(|pair| !pair.is_empty())
+    .map(|pair| {
+      let mut parts = pair.splitn(2, '=');
+      (
+        decode(parts.next().unwrap()),
+        decode(parts.next().unwrap_or_default()),
+      )
+    })
+    .collect()
+}
+
+fn find_pair(encoded: &str, key: &str) -> Option<String> {
+  parse_pairs(encoded)
+    .into_iter()
+    .find(|(k, _)| k == key)
+    .map(|(_, v)| v)
+}
+
+// Decodes an `application/x-www-form-urlencoded` component.
+fn decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut decoded = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'+' => decoded.push(b' '),
+      b'%' if i + 2 < bytes.len() => {
+        decoded.push(u8::from_str_radix(&value[i + 1..i + 3], 16).unwrap());
+        i += 2;
+      }
+      b => decoded.push(b),
+    }
+    i += 1;
+  }
+  String::from_utf8(decoded).unwrap()
+}
+
+/// Local HTTP server which serves canned responses in order, one connection per response
+pub struct TestServer {
+  pub url: String,
+  requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl TestServer {
+  pub fn start(responses: Vec<TestResponse>) -> TestServer {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let received = requests.clone();
+    thread::spawn(move || {
+      for response in responses {
+        let (stream, _) = match listener.accept() {
+          Ok(v) => v,
+          Err(_) => return,
+        };
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_owned();
+        let path = parts.next().unwrap_or_default().to_owned();
+        let mut headers = vec![];
+        loop {
+          let mut line = String::new();
+          reader.read_line(&mut line).unwrap();
+          let line = line.trim_end();
+          if line.is_empty() {
+            break;
+          }
+          let mut parts = line.splitn(2, ':');
+          headers.push((
+            parts.next().unwrap().trim().to_owned(),
+            parts.next().unwrap_or_default().trim().to_owned(),
+          ));
+        }
+        let len = headers
+          .iter()
+          .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+          .map(|(_, v)| v.parse().unwrap())
+          .unwrap_or(0);
+        let mut request_body = vec![0; len];
+        reader.read_exact(&mut request_body).unwrap();
+        received.lock().unwrap().push(ReceivedRequest {
+          method,
+          path,
+          headers,
+          body: request_body,
+        });
+
+        let mut stream = reader.into_inner();
+        let mut head = format!(
+          "HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n",
+          response.status,
+          response.body.len()
+        );
+        for (name, value) in &response.headers {
+          head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(&response.body).unwrap();
+      }
+    });
+    TestServer { url, requests }
+  }
+
+  pub fn requests(&self) -> Vec<ReceivedRequest> {
+    self.requests.lock().unwrap().clone()
+  }
+}
@@ -0,0 +1,1077 @@
+extern crate mws;
+
+use mws::client::{Client, ClientOptions, Method, RetryPolicy, StatusCode};
+use mws::result::{MwsError, MwsErrorCode, MwsResult};
+use mws::transport::{MockResponse, MockTransport};
+
+fn get_client(transport: &MockTransport) -> Client {
+  get_client_with_retry(transport, Default::default())
+}
+
+fn get_client_with_retry(transport: &MockTransport, retry_policy: RetryPolicy) -> Client {
+  Client::with_transport(
+    ClientOptions {
+      endpoint: "https://mws.amazonservices.com".to_owned(),
+      seller_id: "SELLER".to_owned(),
+      mws_auth_token: Some("TOKEN".to_owned()),
+      aws_access_key_id: "KEY".to_owned(),
+      secret_key: "SECRET".to_owned(),
+      retry_policy,
+      ..Default::default()
+    },
+    transport.clone(),
+  )
+}
+
+#[test]
+fn orders() {
+  use mws::orders::*;
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "ListOrders",
+      include_str!("./fixtures/responses/ListOrders.xml"),
+    )
+    .add_xml(
+      "ListOrdersByNextToken",
+      include_str!("./fixtures/responses/ListOrdersByNextToken.xml"),
+    )
+    .add_xml(
+      "ListOrderItems",
+      include_str!("./fixtures/responses/ListOrderItems.xml"),
+    );
+  let client = get_client(&transport);
+
+  let res = ListOrders(
+    &client,
+    ListOrdersParameters {
+      MarketplaceId: vec!["ATVPDKIKX0DER".to_owned()],
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.Orders.len(), 1);
+  assert_eq!(res.Orders[0].AmazonOrderId, "902-3159896-1390916");
+  assert_eq!(res.Orders[0].OrderStatus, OrderStatus::Pending);
+
+  let next_token = res.NextToken.unwrap();
+  let res = ListOrdersByNextToken(&client, next_token.clone()).unwrap();
+  assert_eq!(res.NextToken, None);
+  assert_eq!(res.Orders[0].AmazonOrderId, "058-1233752-8214740");
+  assert_eq!(res.Orders[0].OrderTotal.as_ref().unwrap().Amount, "25.00");
+
+  let res = ListOrderItems(&client, "058-1233752-8214740".to_owned()).unwrap();
+  assert_eq!(res.AmazonOrderId, "058-1233752-8214740");
+  assert_eq!(res.OrderItems[0].SellerSKU, "CBA_OTF_1");
+  assert_eq!(res.OrderItems[0].QuantityOrdered, 1);
+
+  let requests = transport.requests();
+  assert_eq!(requests.len(), 3);
+  assert_eq!(
+    requests[0]
+      .url
+      .starts_with("https://mws.amazonservices.com/Orders/2013-09-01?"),
+    true
+  );
+  assert_eq!(
+    requests[0].param("MarketplaceId.Id.1"),
+    Some("ATVPDKIKX0DER")
+  );
+  assert_eq!(requests[0].param("SellerId"), Some("SELLER"));
+  assert_eq!(requests[0].param("MWSAuthToken"), Some("TOKEN"));
+  assert_eq!(requests[0].param("Signature").is_some(), true);
+  assert_eq!(requests[1].param("NextToken"), Some(next_token.as_ref()));
+  assert_eq!(
+    requests[2].param("AmazonOrderId"),
+    Some("058-1233752-8214740")
+  );
+}
+
+#[test]
+fn reports() {
+  use mws::reports::*;
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "GetReportList",
+      include_str!("./fixtures/responses/GetReportList.xml"),
+    )
+    .add_xml(
+      "GetReportRequestList",
+      include_str!("./fixtures/responses/GetReportRequestList.xml"),
+    )
+    .add_xml(
+      "RequestReport",
+      include_str!("./fixtures/responses/RequestReport.xml"),
+    )
+    .add_response(
+      "GetReport",
+      MockResponse::new(
+        StatusCode::OK,
+        &include_bytes!("./fixtures/report.tdff")[..],
+      )
+      .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    );
+  let client = get_client(&transport);
+
+  let res = GetReportList(&client, Default::default()).unwrap();
+  assert_eq!(res.HasNext, true);
+  assert_eq!(res.ReportInfo.len(), 1);
+  assert_eq!(res.ReportInfo[0].ReportId, "898899473");
+
+  let res = GetReportRequestList(&client, Default::default()).unwrap();
+  assert_eq!(res.ReportRequestInfo[0].ReportRequestId, "2291326454");
+  assert_eq!(
+    res.ReportRequestInfo[0].ReportProcessingStatus,
+    ReportProcessingStatus::_DONE_
+  );
+  assert_eq!(
+    res.ReportRequestInfo[0].GeneratedReportId,
+    Some("898899473".to_owned())
+  );
+
+  let res = RequestReport(
+    &client,
+    RequestReportParameters {
+      ReportType: ReportType::MerchantListings,
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(
+    res.ReportRequestInfo.ReportProcessingStatus,
+    ReportProcessingStatus::_SUBMITTED_
+  );
+
+  let mut out = vec![];
+  let (size, content_md5) = GetReport(&client, "898899473".to_owned(), &mut out).unwrap();
+  assert_eq!(&out[..], &include_bytes!("./fixtures/report.tdff")[..]);
+  assert_eq!(size, out.len() as u64);
+  assert_eq!(content_md5, "agLXebc4tF+Ux/Pa1cmomg==");
+
+  let requests = transport.requests();
+  assert_eq!(
+    requests[2].param("ReportType"),
+    Some("_GET_MERCHANT_LISTINGS_DATA_")
+  );
+  assert_eq!(requests[3].param("ReportId"), Some("898899473"));
+}
+
+#[test]
+fn report_counts() {
+  use mws::reports::*;
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "GetReportCount",
+      include_str!("./fixtures/responses/GetReportCount.xml"),
+    )
+    .add_xml(
+      "GetReportRequestCount",
+      include_str!("./fixtures/responses/GetReportRequestCount.xml"),
+    )
+    .add_xml(
+      "CancelReportRequests",
+      include_str!("./fixtures/responses/CancelReportRequests.xml"),
+    );
+  let client = get_client(&transport);
+
+  let res = GetReportCount(
+    &client,
+    GetReportCountParameters {
+      Acknowledged: Some(false),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.Count, 166);
+
+  let res = GetReportRequestCount(
+    &client,
+    GetReportRequestCountParameters {
+      ReportProcessingStatusList: Some(vec![
+        ReportProcessingStatus::_SUBMITTED_,
+        ReportProcessingStatus::_IN_PROGRESS_,
+      ]),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.Count, 1276);
+
+  let res = CancelReportRequests(
+    &client,
+    CancelReportRequestsParameters {
+      ReportRequestIdList: Some(vec!["2291326454".to_owned()]),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.Count, 1);
+  assert_eq!(res.ReportRequestInfo.len(), 1);
+  assert_eq!(res.ReportRequestInfo[0].ReportRequestId, "2291326454");
+  assert_eq!(
+    res.ReportRequestInfo[0].ReportProcessingStatus,
+    ReportProcessingStatus::_CANCELLED_
+  );
+
+  let requests = transport.requests();
+  assert_eq!(requests[0].param("Acknowledged"), Some("false"));
+  assert_eq!(
+    requests[1].param("ReportProcessingStatusList.Status.1"),
+    Some("_SUBMITTED_")
+  );
+  assert_eq!(
+    requests[1].param("ReportProcessingStatusList.Status.2"),
+    Some("_IN_PROGRESS_")
+  );
+  assert_eq!(
+    requests[2].param("ReportRequestIdList.Id.1"),
+    Some("2291326454")
+  );
+}
+
+#[test]
+fn report_schedule() {
+  use mws::reports::*;
+
+  let transport = MockTransport::new();
+  transport.add_xml(
+    "ManageReportSchedule",
+    include_str!("./fixtures/responses/ManageReportSchedule.xml"),
+  );
+  let client = get_client(&transport);
+
+  let res = ManageReportSchedule(
+    &client,
+    ManageReportScheduleParameters {
+      ReportType: ReportType::XmlOrders,
+      Schedule: Some(Schedule::_30_DAYS_),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.Count, 1);
+  assert_eq!(res.ReportSchedule.len(), 1);
+  assert_eq!(res.ReportSchedule[0].ReportType, "_GET_ORDERS_DATA_");
+  assert_eq!(res.ReportSchedule[0].Schedule, Schedule::_30_DAYS_);
+  assert_eq!(
+    res.ReportSchedule[0].next_available_date(None),
+    res.ReportSchedule[0].ScheduleDate
+  );
+
+  match ManageReportSchedule(
+    &client,
+    ManageReportScheduleParameters {
+      ReportType: ReportType::FlatFileSettlementV2,
+      Schedule: Some(Schedule::_1_DAY_),
+      ..Default::default()
+    },
+  ) {
+    Err(MwsError::Msg(_)) => {}
+    other => panic!("unexpected result: {:?}", other),
+  }
+
+  let requests = transport.requests();
+  assert_eq!(requests.len(), 1);
+  assert_eq!(requests[0].param("ReportType"), Some("_GET_ORDERS_DATA_"));
+  assert_eq!(requests[0].param("Schedule"), Some("_30_DAYS_"));
+}
+
+#[test]
+fn feeds() {
+  use mws::feeds::*;
+  use std::io::Cursor;
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "SubmitFeed",
+      include_str!("./fixtures/responses/SubmitFeed.xml"),
+    )
+    .add_xml(
+      "GetFeedSubmissionList",
+      include_str!("./fixtures/responses/GetFeedSubmissionList.xml"),
+    )
+    .add_xml(
+      "GetFeedSubmissionResult",
+      include_str!("./fixtures/responses/GetFeedSubmissionResult.xml"),
+    );
+  let client = get_client(&transport);
+
+  let content = "<?xml version=\"1.0\"?><AmazonEnvelope/>";
+  let res = SubmitFeed(
+    &client,
+    SubmitFeedParameters {
+      FeedType: "_POST_PRODUCT_DATA_".to_owned(),
+      ..Default::default()
+    },
+    Cursor::new(content),
+    "MD5".to_owned(),
+    "text/xml".to_owned(),
+  )
+  .unwrap();
+  assert_eq!(res.FeedSubmissionInfo.FeedSubmissionId, "2291326430");
+  assert_eq!(res.FeedSubmissionInfo.FeedProcessingStatus, "_SUBMITTED_");
+
+  let res = GetFeedSubmissionList(&client, Default::default()).unwrap();
+  assert_eq!(res.FeedSubmissionInfo.len(), 1);
+  assert_eq!(res.FeedSubmissionInfo[0].FeedProcessingStatus, "_DONE_");
+
+  let mut out = vec![];
+  GetFeedSubmissionResult(&client, "2291326430".to_owned(), &mut out).unwrap();
+  assert_eq!(
+    &out[..],
+    include_str!("./fixtures/responses/GetFeedSubmissionResult.xml").as_bytes()
+  );
+
+  let requests = transport.requests();
+  assert_eq!(requests[0].param("FeedType"), Some("_POST_PRODUCT_DATA_"));
+  assert_eq!(requests[0].body, content.as_bytes());
+  assert_eq!(requests[0].param("ContentMD5Value"), Some("MD5"));
+  assert_eq!(requests[2].param("FeedSubmissionId"), Some("2291326430"));
+}
+
+#[test]
+fn fulfillment_inventory() {
+  use mws::fulfillment_inventory::*;
+
+  let transport = MockTransport::new();
+  transport.add_xml(
+    "ListInventorySupply",
+    include_str!("./fixtures/responses/ListInventorySupply.xml"),
+  );
+  let client = get_client(&transport);
+
+  let res = ListInventorySupply(
+    &client,
+    ListInventorySupplyParameters {
+      SellerSkus: Some(vec!["SampleSKU1".to_owned()]),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.MarketplaceId, "ATVPDKIKX0DER");
+  assert_eq!(res.InventorySupplyList[0].SellerSKU, "SampleSKU1");
+  assert_eq!(res.InventorySupplyList[0].InStockSupplyQuantity, 15);
+  assert_eq!(
+    res.InventorySupplyList[0]
+      .EarliestAvailability
+      .TimepointType,
+    TimepointType::Immediately
+  );
+
+  let requests = transport.requests();
+  assert_eq!(
+    requests[0]
+      .url
+      .starts_with("https://mws.amazonservices.com/FulfillmentInventory/2010-10-01?"),
+    true
+  );
+}
+
+#[test]
+fn error_response() {
+  use mws::orders::*;
+
+  let transport = MockTransport::new();
+  transport.add_response(
+    "ListOrderItems",
+    MockResponse::new(
+      StatusCode::SERVICE_UNAVAILABLE,
+      include_str!("./fixtures/responses/ErrorResponse.xml"),
+    ),
+  );
+  let client = get_client(&transport);
+
+  match ListOrderItems(&client, "058-1233752-8214740".to_owned()) {
+    Err(MwsError::ErrorResponse(res)) => {
+      assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+      let info = res.info.unwrap();
+      assert_eq!(info.errors[0].code, MwsErrorCode::RequestThrottled);
+      assert_eq!(info.request_id, "a0d2c9a1-0b3c-4a21-9f0e-6c1d2b3c4d5e");
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+
+  // no response queued
+  match ListOrderItems(&client, "058-1233752-8214740".to_owned()) {
+    Err(MwsError::Msg(_)) => {}
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn retry() {
+  use mws::feeds::*;
+  use mws::orders::*;
+  use std::time::Duration;
+
+  let transport = MockTransport::new();
+  let throttled = MockResponse::new(
+    StatusCode::SERVICE_UNAVAILABLE,
+    include_str!("./fixtures/responses/ErrorResponse.xml"),
+  );
+  transport
+    .add_response("ListOrderItems", throttled.clone())
+    .add_response("ListOrderItems", throttled.clone())
+    .add_xml(
+      "ListOrderItems",
+      include_str!("./fixtures/responses/ListOrderItems.xml"),
+    )
+    .add_response("SubmitFeed", throttled.clone())
+    .add_xml(
+      "SubmitFeed",
+      include_str!("./fixtures/responses/SubmitFeed.xml"),
+    );
+  let client = get_client_with_retry(
+    &transport,
+    RetryPolicy {
+      max_attempts: 3,
+      base_delay: Duration::from_millis(1),
+      max_delay: Duration::from_millis(10),
+      jitter: true,
+    },
+  );
+
+  let res = ListOrderItems(&client, "058-1233752-8214740".to_owned()).unwrap();
+  assert_eq!(res.AmazonOrderId, "058-1233752-8214740");
+
+  let content = b"<?xml version=\"1.0\"?><AmazonEnvelope/>".to_vec();
+  let res = client
+    .request_with_bytes(
+      Method::POST,
+      "/",
+      "2009-01-01",
+      "SubmitFeed",
+      SubmitFeedParameters {
+        FeedType: "_POST_PRODUCT_DATA_".to_owned(),
+        ..Default::default()
+      },
+      content.clone(),
+      "MD5".to_owned(),
+      "text/xml".to_owned(),
+    )
+    .unwrap();
+  assert_eq!(res.status(), StatusCode::OK);
+
+  let requests = transport.requests();
+  assert_eq!(requests.len(), 5);
+  assert_eq!(requests[2].action(), Some("ListOrderItems"));
+  assert_eq!(requests[3].body, content);
+  assert_eq!(requests[4].body, content);
+}
+
+#[test]
+fn content_md5() {
+  use mws::feeds::GetFeedSubmissionResult;
+  use mws::reports::GetReport;
+  use std::time::Duration;
+
+  let report = &include_bytes!("./fixtures/report.tdff")[..];
+  let truncated = MockResponse::new(StatusCode::OK, &report[..report.len() / 2])
+    .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg==");
+  let transport = MockTransport::new();
+  transport
+    .add_response("GetReport", truncated.clone())
+    .add_response("GetReport", truncated.clone())
+    .add_response(
+      "GetReport",
+      MockResponse::new(StatusCode::OK, report).header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    )
+    .add_response(
+      "GetFeedSubmissionResult",
+      MockResponse::xml("<AmazonEnvelope/>").header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    );
+  let client = get_client(&transport);
+
+  let mut out = vec![];
+  match GetReport(&client, "898899473".to_owned(), &mut out) {
+    Err(MwsError::ContentMD5Mismatch { expected, size, .. }) => {
+      assert_eq!(expected, "agLXebc4tF+Ux/Pa1cmomg==");
+      assert_eq!(size, (report.len() / 2) as u64);
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+
+  let retry_policy = RetryPolicy {
+    max_attempts: 2,
+    base_delay: Duration::from_millis(1),
+    max_delay: Duration::from_millis(10),
+    jitter: false,
+  };
+  let mut attempts = 0;
+  let (size, _) = retry_policy
+    .run(|attempt| {
+      attempts = attempt;
+      out.clear();
+      GetReport(&client, "898899473".to_owned(), &mut out)
+    })
+    .unwrap();
+  assert_eq!(attempts, 2);
+  assert_eq!(size, report.len() as u64);
+  assert_eq!(&out[..], report);
+
+  match GetFeedSubmissionResult(&client, "2291326430".to_owned(), &mut vec![]) {
+    Err(MwsError::ContentMD5Mismatch { .. }) => {}
+    other => panic!("unexpected result: {:?}", other),
+  }
+
+  let transport = MockTransport::new();
+  transport.add_response("GetReport", truncated);
+  let client = Client::with_transport(
+    ClientOptions {
+      skip_content_md5_check: true,
+      ..client.options().clone()
+    },
+    transport.clone(),
+  );
+  let mut out = vec![];
+  let (size, content_md5) = GetReport(&client, "898899473".to_owned(), &mut out).unwrap();
+  assert_eq!(size, (report.len() / 2) as u64);
+  assert_eq!(content_md5, "agLXebc4tF+Ux/Pa1cmomg==");
+}
+
+#[test]
+fn throttle() {
+  use mws::orders::*;
+  use mws::throttle::{Quota, Throttler};
+  use std::thread;
+  use std::time::{Duration, Instant};
+
+  let transport = MockTransport::new();
+  for _ in 0..4 {
+    transport.add_xml(
+      "ListOrderItems",
+      include_str!("./fixtures/responses/ListOrderItems.xml"),
+    );
+  }
+  let throttler = Throttler::new();
+  throttler.set_quota(
+    "/Orders/2013-09-01",
+    "ListOrderItems",
+    Quota::new(2, 1, Duration::from_millis(50)),
+  );
+  let client = Client::with_transport(
+    ClientOptions {
+      endpoint: "https://mws.amazonservices.com".to_owned(),
+      seller_id: "SELLER".to_owned(),
+      throttler: Some(throttler),
+      ..Default::default()
+    },
+    transport.clone(),
+  );
+
+  let started = Instant::now();
+  let workers: Vec<_> = (0..2)
+    .map(|_| {
+      let client = client.clone();
+      thread::spawn(move || {
+        for _ in 0..2 {
+          ListOrderItems(&client, "058-1233752-8214740".to_owned()).unwrap();
+        }
+      })
+    })
+    .collect();
+  for worker in workers {
+    worker.join().unwrap();
+  }
+  // 2 requests are available immediately, the other 2 are restored after 50ms each
+  assert!(started.elapsed() >= Duration::from_millis(100));
+  assert_eq!(transport.requests().len(), 4);
+}
+
+#[test]
+fn response_meta() {
+  use mws::orders::*;
+
+  let transport = MockTransport::new();
+  transport
+    .add_response(
+      "ListOrderItems",
+      MockResponse::xml(include_str!("./fixtures/responses/ListOrderItems.xml"))
+        .header("x-mws-request-id", "88faca76-b600-46d2-b53c-0c8c4533e43a")
+        .header("x-mws-quota-max", "200.0")
+        .header("x-mws-quota-remaining", "199.0")
+        .header("x-mws-timestamp", "2017-02-25T18:10:21.687Z"),
+    )
+    .add_response(
+      "ListOrderItems",
+      MockResponse::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        include_str!("./fixtures/responses/ErrorResponse.xml"),
+      )
+      .header("x-mws-quota-remaining", "0.0"),
+    );
+  let client = get_client(&transport);
+
+  let (res, meta) = client
+    .with_meta(|client| ListOrderItems(client, "058-1233752-8214740".to_owned()))
+    .unwrap();
+  assert_eq!(res.AmazonOrderId, "058-1233752-8214740");
+  assert_eq!(meta.request_id, "88faca76-b600-46d2-b53c-0c8c4533e43a");
+  assert_eq!(meta.quota_max, Some(200.0));
+  assert_eq!(meta.quota_remaining, Some(199.0));
+  assert_eq!(meta.timestamp.unwrap().timestamp(), 1488046221);
+
+  match ListOrderItems(&client, "058-1233752-8214740".to_owned()) {
+    Err(MwsError::ErrorResponse(res)) => {
+      // falls back to the RequestId in the body
+      assert_eq!(res.meta.request_id, "a0d2c9a1-0b3c-4a21-9f0e-6c1d2b3c4d5e");
+      assert_eq!(res.meta.quota_remaining, Some(0.0));
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn clock_skew() {
+  use chrono::{DateTime, Duration, Utc};
+  use mws::orders::*;
+
+  fn timestamp(request: &mws::transport::RecordedRequest) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(request.param("Timestamp").unwrap())
+      .unwrap()
+      .with_timezone(&Utc)
+  }
+
+  let server_time = (Utc::now() + Duration::hours(1)).to_rfc2822();
+  let transport = MockTransport::new();
+  transport
+    .add_response(
+      "ListOrderItems",
+      MockResponse::new(
+        StatusCode::FORBIDDEN,
+        include_str!("./fixtures/responses/RequestExpired.xml"),
+      )
+      .header("Date", &server_time),
+    )
+    .add_xml(
+      "ListOrderItems",
+      include_str!("./fixtures/responses/ListOrderItems.xml"),
+    );
+  let client = get_client(&transport);
+  assert_eq!(client.clock_offset(), Duration::zero());
+
+  let res = ListOrderItems(&client, "058-1233752-8214740".to_owned()).unwrap();
+  assert_eq!(res.AmazonOrderId, "058-1233752-8214740");
+  let offset = client.clock_offset();
+  assert!((offset - Duration::hours(1)).num_seconds().abs() <= 2);
+
+  let requests = transport.requests();
+  assert_eq!(requests.len(), 2);
+  let skew = timestamp(&requests[1]) - timestamp(&requests[0]);
+  assert!((skew - Duration::hours(1)).num_seconds().abs() <= 2);
+
+  // clones share the offset, which can be set to simulate skew
+  client.clone().set_clock_offset(Duration::minutes(-30));
+  assert_eq!(client.clock_offset(), Duration::minutes(-30));
+  transport.add_xml(
+    "ListOrderItems",
+    include_str!("./fixtures/responses/ListOrderItems.xml"),
+  );
+  ListOrderItems(&client, "058-1233752-8214740".to_owned()).unwrap();
+  let requests = transport.requests();
+  let skew = Utc::now() - timestamp(&requests[2]);
+  assert!((skew - Duration::minutes(30)).num_seconds().abs() <= 2);
+}
+
+#[test]
+fn paginator() {
+  use mws::orders::*;
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "ListOrders",
+      include_str!("./fixtures/responses/ListOrders.xml"),
+    )
+    .add_response(
+      "ListOrdersByNextToken",
+      MockResponse::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        include_str!("./fixtures/responses/ErrorResponse.xml"),
+      ),
+    )
+    .add_xml(
+      "ListOrdersByNextToken",
+      include_str!("./fixtures/responses/ListOrdersByNextToken.xml"),
+    );
+  let client = get_client(&transport);
+
+  let mut orders = iter_orders(&client, Default::default());
+  assert_eq!(
+    orders.next().unwrap().unwrap().AmazonOrderId,
+    "902-3159896-1390916"
+  );
+  match orders.next() {
+    Some(Err(MwsError::ErrorResponse(_))) => {}
+    other => panic!("unexpected result: {:?}", other),
+  }
+  assert!(orders.next().is_none());
+
+  // resume from the failed page
+  let next_token = orders.page_token().unwrap().to_owned();
+  assert_eq!(next_token, "2YgYW55IGNhcm5hbCBwbGVhc3VyZS4=");
+  assert_eq!(orders.next_token(), Some(next_token.as_ref()));
+  let ids: Vec<_> = iter_orders(&client, Default::default())
+    .resume_from(next_token.clone())
+    .map(|order| order.unwrap().AmazonOrderId)
+    .collect();
+  assert_eq!(ids, vec!["058-1233752-8214740"]);
+
+  let requests = transport.requests();
+  assert_eq!(requests.len(), 3);
+  assert_eq!(requests[2].action(), Some("ListOrdersByNextToken"));
+  assert_eq!(requests[2].param("NextToken"), Some(next_token.as_ref()));
+}
+
+#[test]
+fn paginator_page_token() {
+  use mws::paginator::{Page, Paginator};
+
+  fn page(items: &[u32], next_token: Option<&str>) -> MwsResult<Page<u32>> {
+    Ok(Page {
+      items: items.to_vec(),
+      next_token: next_token.map(ToOwned::to_owned),
+    })
+  }
+  let next = |_: &(), next_token: String| match next_token.as_ref() {
+    "2" => page(&[3, 4], Some("3")),
+    "3" => page(&[5], None),
+    _ => unreachable!(),
+  };
+
+  let mut records = Paginator::new(&(), |_| page(&[1, 2], Some("2")), next);
+  assert_eq!(records.next().unwrap().unwrap(), 1);
+  assert_eq!(records.page_token(), None);
+  assert_eq!(records.next().unwrap().unwrap(), 2);
+  assert_eq!(records.next().unwrap().unwrap(), 3);
+  assert_eq!(records.page_token(), Some("2"));
+  assert_eq!(records.next_token(), Some("3"));
+
+  // the page of record 3 is requested again, record 4 is not lost
+  let page_token = records.page_token().unwrap().to_owned();
+  let records: Vec<_> = Paginator::new(&(), |_| unreachable!(), next)
+    .resume_from(page_token)
+    .map(Result::unwrap)
+    .collect();
+  assert_eq!(records, vec![3, 4, 5]);
+}
+
+#[test]
+fn paginator_has_next() {
+  use mws::reports::*;
+
+  let transport = MockTransport::new();
+  transport.add_xml(
+    "GetReportRequestList",
+    include_str!("./fixtures/responses/GetReportRequestList.xml"),
+  );
+  let client = get_client(&transport);
+
+  let mut requests = iter_report_requests(&client, Default::default());
+  let page = requests.next_page().unwrap().unwrap();
+  assert_eq!(page.items.len(), 1);
+  assert_eq!(page.next_token, None);
+  assert!(requests.next().is_none());
+  assert_eq!(transport.requests().len(), 1);
+}
+
+fn report_request_list(status: &str, generated: bool) -> String {
+  let xml = include_str!("./fixtures/responses/GetReportRequestList.xml")
+    .replace("_DONE_", status);
+  if generated {
+    xml
+  } else {
+    xml.replace("<GeneratedReportId>898899473</GeneratedReportId>", "")
+  }
+}
+
+fn fetch_report_options() -> mws::reports::FetchReportOptions {
+  use std::time::Duration;
+
+  mws::reports::FetchReportOptions {
+    poll_interval: Duration::from_millis(1),
+    throttle_delay: Duration::from_millis(1),
+    ..Default::default()
+  }
+}
+
+#[test]
+fn fetch_report() {
+  use mws::reports::*;
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "RequestReport",
+      include_str!("./fixtures/responses/RequestReport.xml"),
+    )
+    .add_response(
+      "GetReportRequestList",
+      MockResponse::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        include_str!("./fixtures/responses/ErrorResponse.xml"),
+      ),
+    )
+    .add_xml(
+      "GetReportRequestList",
+      &report_request_list("_IN_PROGRESS_", false),
+    )
+    .add_xml("GetReportRequestList", &report_request_list("_DONE_", false))
+    .add_xml(
+      "GetReportList",
+      &include_str!("./fixtures/responses/GetReportList.xml")
+        .replace("2278662938", "2291326454"),
+    )
+    .add_response(
+      "GetReport",
+      MockResponse::new(
+        StatusCode::OK,
+        &include_bytes!("./fixtures/report.tdff")[..],
+      )
+      .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    )
+    .add_xml(
+      "UpdateReportAcknowledgements",
+      include_str!("./fixtures/responses/UpdateReportAcknowledgements.xml"),
+    );
+  let client = get_client(&transport);
+
+  let mut out = vec![];
+  let outcome = fetch_report(
+    &client,
+    RequestReportParameters {
+      ReportType: ReportType::MerchantListings,
+      ..Default::default()
+    },
+    &fetch_report_options(),
+    &mut out,
+  )
+  .unwrap();
+  assert_eq!(outcome.request().ReportRequestId, "2291326454");
+  match outcome {
+    FetchReportOutcome::Done {
+      report_id, output, ..
+    } => {
+      assert_eq!(report_id, "898899473");
+      assert_eq!(output, out.len() as u64);
+    }
+    other => panic!("unexpected outcome: {:?}", other),
+  }
+  assert_eq!(&out[..], &include_bytes!("./fixtures/report.tdff")[..]);
+
+  let requests = transport.requests();
+  let actions: Vec<_> = requests.iter().map(|r| r.action().unwrap()).collect();
+  assert_eq!(
+    actions,
+    vec![
+      "RequestReport",
+      "GetReportRequestList",
+      "GetReportRequestList",
+      "GetReportRequestList",
+      "GetReportList",
+      "GetReport",
+      "UpdateReportAcknowledgements",
+    ]
+  );
+  assert_eq!(
+    requests[1].param("ReportRequestIdList.Id.1"),
+    Some("2291326454")
+  );
+  assert_eq!(
+    requests[4].param("ReportRequestIdList.Id.1"),
+    Some("2291326454")
+  );
+  assert_eq!(requests[5].param("ReportId"), Some("898899473"));
+  assert_eq!(requests[6].param("ReportIdList.Id.1"), Some("898899473"));
+  assert_eq!(requests[6].param("Acknowledged"), Some("true"));
+}
+
+#[test]
+fn fetch_report_rows() {
+  use mws::reports::*;
+  use mws::result::MwsResult;
+  use mws::tdff::{FromTdffRow, TdffRow};
+
+  #[derive(Debug, Default)]
+  struct Listing {
+    sku: String,
+    quantity: String,
+  }
+
+  impl FromTdffRow for Listing {
+    fn from_tdff_row(row: &TdffRow) -> MwsResult<Self> {
+      let mut listing = Listing::default();
+      for (key, value) in row {
+        match *key {
+          "sku" => listing.sku = value.clone(),
+          "quantity" => listing.quantity = value.clone(),
+          _ => {}
+        }
+      }
+      Ok(listing)
+    }
+  }
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "RequestReport",
+      include_str!("./fixtures/responses/RequestReport.xml"),
+    )
+    .add_xml("GetReportRequestList", &report_request_list("_DONE_", true))
+    .add_response(
+      "GetReport",
+      MockResponse::new(
+        StatusCode::OK,
+        &include_bytes!("./fixtures/report.tdff")[..],
+      )
+      .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    );
+  let client = get_client(&transport);
+
+  let rows: Vec<Listing> = fetch_report_rows(
+    &client,
+    Default::default(),
+    &FetchReportOptions {
+      acknowledge: false,
+      ..fetch_report_options()
+    },
+  )
+  .unwrap()
+  .into_output()
+  .unwrap();
+  assert_eq!(rows[0].sku, "OQ-5B1U-8LAN");
+  assert_eq!(rows[0].quantity, "57");
+  assert_eq!(transport.requests().len(), 3);
+
+  // the rows of a truncated report are passed on, but it fails the check and is not acknowledged
+  let report = &include_bytes!("./fixtures/report.tdff")[..];
+  // the header and the first row
+  let end = report
+    .iter()
+    .enumerate()
+    .filter(|&(_, &b)| b == b'\n')
+    .nth(1)
+    .unwrap()
+    .0;
+  let truncated = &report[..end + 1];
+  transport
+    .add_xml(
+      "RequestReport",
+      include_str!("./fixtures/responses/RequestReport.xml"),
+    )
+    .add_xml("GetReportRequestList", &report_request_list("_DONE_", true))
+    .add_response(
+      "GetReport",
+      MockResponse::new(StatusCode::OK, truncated)
+        .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    );
+  let mut skus = vec![];
+  let res = fetch_report_rows_with(
+    &client,
+    Default::default(),
+    &fetch_report_options(),
+    |row: Listing| {
+      skus.push(row.sku);
+      Ok(())
+    },
+  );
+  match res {
+    Err(MwsError::ContentMD5Mismatch { size, .. }) => assert_eq!(size, truncated.len() as u64),
+    other => panic!("unexpected result: {:?}", other),
+  }
+  assert_eq!(skus.len(), 1);
+  assert_eq!(transport.requests().len(), 6);
+}
+
+#[test]
+fn fetch_report_outcomes() {
+  use mws::reports::*;
+  use std::time::Duration;
+
+  let transport = MockTransport::new();
+  for status in &["_DONE_NO_DATA_", "_CANCELLED_", "_IN_PROGRESS_"] {
+    transport
+      .add_xml(
+        "RequestReport",
+        include_str!("./fixtures/responses/RequestReport.xml"),
+      )
+      .add_xml("GetReportRequestList", &report_request_list(status, false));
+  }
+  for _ in 0..2 {
+    transport.add_xml(
+      "GetReportRequestList",
+      &report_request_list("_IN_PROGRESS_", false),
+    );
+  }
+  let client = get_client(&transport);
+  let options = fetch_report_options();
+
+  match fetch_report(&client, Default::default(), &options, &mut vec![]).unwrap() {
+    FetchReportOutcome::DoneNoData(request) => assert_eq!(request.ReportRequestId, "2291326454"),
+    other => panic!("unexpected outcome: {:?}", other),
+  }
+  match fetch_report(&client, Default::default(), &options, &mut vec![]).unwrap() {
+    FetchReportOutcome::Cancelled(request) => assert_eq!(request.ReportRequestId, "2291326454"),
+    other => panic!("unexpected outcome: {:?}", other),
+  }
+
+  // the request is polled at 0ms, 20ms and a last time at the 30ms deadline
+  let options = FetchReportOptions {
+    poll_interval: Duration::from_millis(20),
+    timeout: Some(Duration::from_millis(30)),
+    ..options
+  };
+  match fetch_report(&client, Default::default(), &options, &mut vec![]) {
+    Err(MwsError::Timeout(what)) => assert_eq!(what, "report request '2291326454'"),
+    other => panic!("unexpected result: {:?}", other),
+  }
+  assert_eq!(transport.requests().len(), 8);
+}
+
+#[test]
+fn fetch_report_timeout_shorter_than_poll_interval() {
+  use mws::reports::*;
+  use std::time::{Duration, Instant};
+
+  let transport = MockTransport::new();
+  transport
+    .add_xml(
+      "RequestReport",
+      include_str!("./fixtures/responses/RequestReport.xml"),
+    )
+    .add_xml("GetReportRequestList", &report_request_list("_DONE_", true))
+    .add_response(
+      "GetReport",
+      MockResponse::new(
+        StatusCode::OK,
+        &include_bytes!("./fixtures/report.tdff")[..],
+      )
+      .header("Content-MD5", "agLXebc4tF+Ux/Pa1cmomg=="),
+    );
+  let client = get_client(&transport);
+
+  let started = Instant::now();
+  let outcome = fetch_report(
+    &client,
+    Default::default(),
+    &FetchReportOptions {
+      timeout: Some(Duration::from_millis(10)),
+      acknowledge: false,
+      ..Default::default()
+    },
+    &mut vec![],
+  )
+  .unwrap();
+  match outcome {
+    FetchReportOutcome::Done { report_id, .. } => assert_eq!(report_id, "898899473"),
+    other => panic!("unexpected outcome: {:?}", other),
+  }
+  assert!(started.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,217 @@
+extern crate mws;
+
+use mws::orders::{CurrencyAmount, FulfillmentChannel, OrderStatus, ShippingAddress};
+use mws::reports::orders::*;
+
+fn usd(amount: &str) -> Option<CurrencyAmount> {
+  Some(CurrencyAmount {
+    CurrencyCode: "USD".to_owned(),
+    Amount: amount.to_owned(),
+  })
+}
+
+#[test]
+fn flat_file_all_orders() {
+  let orders =
+    ReportOrder::parse_flat_file(&include_bytes!("./fixtures/all_orders.tdff")[..]).unwrap();
+  let summary: Vec<_> = orders
+    .iter()
+    .map(|o| {
+      (
+        o.order.AmazonOrderId.as_ref(),
+        o.order.OrderStatus.clone(),
+        o.order.FulfillmentChannel.clone(),
+        o.items.len(),
+        o.order.NumberOfItemsShipped,
+        o.order.NumberOfItemsUnshipped,
+      )
+    })
+    .collect();
+  assert_eq!(
+    summary,
+    vec![
+      (
+        "112-3456789-0123456",
+        OrderStatus::Shipped,
+        FulfillmentChannel::AFN,
+        2,
+        3,
+        0
+      ),
+      (
+        "112-9876543-2109876",
+        OrderStatus::Pending,
+        FulfillmentChannel::MFN,
+        1,
+        0,
+        2
+      ),
+      (
+        "112-5555555-5555555",
+        OrderStatus::Canceled,
+        FulfillmentChannel::AFN,
+        1,
+        0,
+        0
+      ),
+    ]
+  );
+
+  let order = &orders[0].order;
+  assert_eq!(
+    order.PurchaseDate,
+    Some("2019-02-10T08:12:33Z".parse().unwrap())
+  );
+  assert_eq!(order.ShipServiceLevel, "Expedited");
+  assert_eq!(
+    order.ShippingAddress,
+    Some(ShippingAddress {
+      City: "SAN MATEO".to_owned(),
+      StateOrRegion: "CA".to_owned(),
+      PostalCode: "94401-1234".to_owned(),
+      CountryCode: "US".to_owned(),
+      ..Default::default()
+    })
+  );
+
+  let item = &orders[0].items[0];
+  assert_eq!(item.SellerSKU, "edifier-r1280t-fba");
+  assert_eq!(item.ASIN, "B016P9HJIA");
+  assert_eq!(item.QuantityOrdered, 1);
+  assert_eq!(item.QuantityShipped, 1);
+  assert_eq!(item.ItemPrice, usd("99.99"));
+  assert_eq!(item.ItemTax, usd("8.75"));
+  assert_eq!(item.ShippingPrice, usd("4.99"));
+  assert_eq!(item.ShippingTax, None);
+  assert_eq!(item.PromotionDiscount, usd("-5.00"));
+  assert_eq!(item.ShippingDiscount, usd("-4.99"));
+  assert_eq!(orders[0].items[1].SellerSKU, "edifier-r980t-fba");
+
+  let order = &orders[1].order;
+  assert_eq!(
+    order.PurchaseDate,
+    Some("2019-02-10T17:00:00Z".parse().unwrap())
+  );
+  assert_eq!(order.SellerOrderId, "666");
+  assert_eq!(order.ShippingAddress, None);
+  assert!(order.IsBusinessOrder);
+  assert_eq!(order.PurchaseOrderNumber, "PO-1234");
+  assert_eq!(orders[1].items[0].QuantityShipped, 0);
+}
+
+#[test]
+fn xml_all_orders() {
+  let reader = XmlOrderReader::new(&include_bytes!("./fixtures/all_orders.xml")[..]).unwrap();
+  let orders: Vec<XmlOrder> = reader.collect::<Result<_, _>>().unwrap();
+  assert_eq!(orders.len(), 2);
+  assert_eq!(orders[0].AmazonOrderID, "112-3456789-0123456");
+  assert_eq!(orders[0].OrderItem.len(), 2);
+  assert_eq!(orders[0].OrderItem[0].Promotion.len(), 3);
+  assert_eq!(
+    orders[0].OrderItem[0].price_component("Tax"),
+    Some(&XmlAmount {
+      currency: "USD".to_owned(),
+      Value: "8.75".to_owned(),
+    })
+  );
+
+  let orders: Vec<ReportOrder> = orders
+    .into_iter()
+    .map(XmlOrder::into_report_order)
+    .collect::<Result<_, _>>()
+    .unwrap();
+  let order = &orders[0].order;
+  assert_eq!(order.OrderStatus, OrderStatus::Shipped);
+  assert_eq!(order.FulfillmentChannel, FulfillmentChannel::AFN);
+  assert_eq!(order.NumberOfItemsShipped, 3);
+  assert_eq!(order.NumberOfItemsUnshipped, 0);
+  assert_eq!(
+    order.LastUpdateDate,
+    Some("2019-02-11T20:01:02Z".parse().unwrap())
+  );
+  assert_eq!(
+    order
+      .ShippingAddress
+      .as_ref()
+      .map(|a| a.StateOrRegion.as_ref()),
+    Some("CA")
+  );
+
+  let item = &orders[0].items[0];
+  assert_eq!(item.OrderItemId, "46510268396154");
+  assert_eq!(item.ItemPrice, usd("99.99"));
+  assert_eq!(item.ItemTax, usd("8.75"));
+  assert_eq!(item.ShippingPrice, usd("4.99"));
+  assert_eq!(item.PromotionDiscount, usd("-5.00"));
+  assert_eq!(item.ShippingDiscount, usd("-4.99"));
+  assert_eq!(orders[0].items[1].PromotionDiscount, None);
+
+  let order = &orders[1].order;
+  assert_eq!(order.FulfillmentChannel, FulfillmentChannel::MFN);
+  assert_eq!(order.NumberOfItemsUnshipped, 2);
+  assert!(order.IsBusinessOrder);
+  assert_eq!(order.PurchaseOrderNumber, "PO-1234");
+  assert_eq!(order.ShippingAddress, None);
+}
+
+#[test]
+fn flat_file_and_xml_orders_agree() {
+  let flat_file =
+    ReportOrder::parse_flat_file(&include_bytes!("./fixtures/all_orders.tdff")[..]).unwrap();
+  let xml: Vec<ReportOrder> = XmlOrderReader::new(&include_bytes!("./fixtures/all_orders.xml")[..])
+    .unwrap()
+    .map(|order| order.and_then(XmlOrder::into_report_order))
+    .collect::<Result<_, _>>()
+    .unwrap();
+
+  for (a, b) in flat_file.iter().zip(xml.iter()) {
+    assert_eq!(a.order.AmazonOrderId, b.order.AmazonOrderId);
+    assert_eq!(a.order.PurchaseDate, b.order.PurchaseDate);
+    assert_eq!(a.order.OrderStatus, b.order.OrderStatus);
+    assert_eq!(a.order.NumberOfItemsShipped, b.order.NumberOfItemsShipped);
+    assert_eq!(
+      a.order.NumberOfItemsUnshipped,
+      b.order.NumberOfItemsUnshipped
+    );
+    let skus =
+      |o: &ReportOrder| -> Vec<String> { o.items.iter().map(|i| i.SellerSKU.clone()).collect() };
+    assert_eq!(skus(a), skus(b));
+  }
+}
+
+#[test]
+fn xml_all_orders_truncated() {
+  let xml = include_str!("./fixtures/all_orders.xml");
+  let truncated = &xml[..xml.find("<AmazonOrderID>112-9876543").unwrap()];
+  let mut reader = XmlOrderReader::new(truncated.as_bytes()).unwrap();
+  assert!(reader.next().unwrap().is_ok());
+  assert!(reader.next().unwrap().is_err());
+  assert!(reader.next().is_none());
+}
+
+#[test]
+fn flat_file_invalid_date() {
+  use mws::result::MwsError;
+
+  let tdff = include_str!("./fixtures/all_orders.tdff").replace("2019-02-10T09:00:00-08:00", "n/a");
+  match ReportOrder::parse_flat_file(tdff.as_bytes()) {
+    Err(MwsError::Tdff(err)) => {
+      assert_eq!(err.header, "purchase-date");
+      assert_eq!(err.value, "n/a");
+      assert_eq!(err.position.map(|p| p.line()), Some(3));
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn xml_invalid_promotion_discount() {
+  let xml = include_str!("./fixtures/all_orders.xml").replacen(
+    "<ItemPromotionDiscount>-3.00</ItemPromotionDiscount>",
+    "<ItemPromotionDiscount>n/a</ItemPromotionDiscount>",
+    1,
+  );
+  let mut reader = XmlOrderReader::new(xml.as_bytes()).unwrap();
+  let order = reader.next().unwrap().unwrap();
+  assert!(order.into_report_order().is_err());
+}
@@ -0,0 +1,158 @@
+extern crate mws;
+
+use mws::reports::rows::*;
+use mws::tdff::TdffParser;
+
+fn parse<T: mws::tdff::FromTdffRow>(bytes: &[u8]) -> Vec<T> {
+  TdffParser::new(bytes).unwrap().parse_all().unwrap()
+}
+
+#[test]
+fn afn_inventory() {
+  let rows: Vec<AfnInventoryRow> = parse(include_bytes!("./fixtures/afn_inventory.tdff"));
+  assert_eq!(rows.len(), 3);
+  assert_eq!(
+    rows[0],
+    AfnInventoryRow {
+      seller_sku: "edifier-r1280t-fba".to_owned(),
+      fulfillment_channel_sku: "X001ABCDEF".to_owned(),
+      asin: "B016P9HJIA".to_owned(),
+      condition_type: "NewItem".to_owned(),
+      warehouse_condition_code: "SELLABLE".to_owned(),
+      quantity_available: 42,
+      country: "".to_owned(),
+    }
+  );
+  assert_eq!(rows[1].warehouse_condition_code, "UNSELLABLE");
+  assert_eq!(rows[2].quantity_available, 0);
+
+  let rows: Vec<AfnInventoryRow> =
+    parse(include_bytes!("./fixtures/afn_inventory_by_country.tdff"));
+  let tuples: Vec<_> = rows
+    .iter()
+    .map(|row| {
+      (
+        row.seller_sku.as_ref(),
+        row.fulfillment_channel_sku.as_ref(),
+        row.country.as_ref(),
+        row.quantity_available,
+      )
+    })
+    .collect();
+  assert_eq!(
+    tuples,
+    vec![
+      ("edifier-r1280t-fba", "X001ABCDEF", "DE", 7),
+      ("edifier-r1280t-fba", "X001ABCDEF", "FR", 3),
+    ]
+  );
+}
+
+#[test]
+fn fba_myi_inventory() {
+  let rows: Vec<FbaMyiInventoryRow> = parse(include_bytes!(
+    "./fixtures/fba_myi_unsuppressed_inventory.tdff"
+  ));
+  assert_eq!(rows.len(), 2);
+
+  let row = &rows[0];
+  assert_eq!(row.sku, "edifier-r1280t-fba");
+  assert_eq!(row.fnsku, "X001ABCDEF");
+  assert_eq!(
+    row.product_name,
+    "Edifier R1280T Powered Bookshelf Speakers"
+  );
+  assert_eq!(row.your_price, "99.99");
+  assert_eq!(row.mfn_listing_exists, YesNo(false));
+  assert_eq!(row.mfn_fulfillable_quantity, 0);
+  assert_eq!(row.afn_listing_exists, YesNo(true));
+  assert_eq!(row.afn_warehouse_quantity, 45);
+  assert_eq!(row.afn_fulfillable_quantity, 42);
+  assert_eq!(row.afn_unsellable_quantity, 1);
+  assert_eq!(row.afn_reserved_quantity, 2);
+  assert_eq!(row.afn_total_quantity, 55);
+  assert_eq!(row.per_unit_volume, "0.85");
+  assert_eq!(row.afn_inbound_shipped_quantity, 10);
+
+  let row = &rows[1];
+  assert_eq!(row.mfn_listing_exists, YesNo(true));
+  assert_eq!(row.mfn_fulfillable_quantity, 211);
+  assert_eq!(row.afn_listing_exists, YesNo(false));
+  assert_eq!(row.afn_inbound_working_quantity, 0);
+}
+
+#[test]
+fn merchant_listings() {
+  let rows: Vec<MerchantListingRow> =
+    parse(include_bytes!("./fixtures/merchant_listings_all.tdff"));
+  assert_eq!(rows.len(), 2);
+
+  let row = &rows[0];
+  assert_eq!(row.item_name, "Edifier R1280T Powered Bookshelf Speakers");
+  assert_eq!(row.listing_id, "0207ZB4F2O1");
+  assert_eq!(row.seller_sku, "edifier-r1280t-fba");
+  assert_eq!(row.price, "99.99");
+  assert_eq!(row.quantity, 0);
+  assert_eq!(row.open_date, "2019-02-20 08:00:00 PST");
+  assert_eq!(row.item_condition, "11");
+  assert_eq!(row.asin1, "B016P9HJIA");
+  assert_eq!(row.product_id, "B016P9HJIA");
+  assert_eq!(row.fulfillment_channel, "AMAZON_NA");
+  assert_eq!(row.merchant_shipping_group, "Migrated Template");
+  assert_eq!(row.status, "Active");
+
+  let row = &rows[1];
+  assert_eq!(row.quantity, 211);
+  assert_eq!(row.pending_quantity, 0);
+  assert_eq!(row.fulfillment_channel, "DEFAULT");
+  assert_eq!(row.status, "Inactive");
+}
+
+#[test]
+fn merchant_listings_jp() {
+  let rows: Vec<MerchantListingRow> =
+    parse(include_bytes!("./fixtures/merchant_listings_all_jp.tdff"));
+  assert_eq!(
+    rows,
+    vec![MerchantListingRow {
+      item_name: "Edifier R1280T スピーカー".to_owned(),
+      listing_id: "0207ZB4F2O1".to_owned(),
+      seller_sku: "edifier-r1280t".to_owned(),
+      price: "12800".to_owned(),
+      quantity: 5,
+      open_date: "2019/02/20 08:00:00 JST".to_owned(),
+      product_id_type: "4".to_owned(),
+      item_condition: "11".to_owned(),
+      product_id: "B016P9HJIA".to_owned(),
+      pending_quantity: 1,
+      fulfillment_channel: "DEFAULT".to_owned(),
+      merchant_shipping_group: "移行済みテンプレート".to_owned(),
+      status: "Active".to_owned(),
+      ..Default::default()
+    }]
+  );
+}
+
+#[test]
+fn open_listings() {
+  let rows: Vec<OpenListingRow> = parse(include_bytes!("./fixtures/report.tdff"));
+  assert_eq!(
+    rows[0],
+    OpenListingRow {
+      sku: "OQ-5B1U-8LAN".to_owned(),
+      asin: "B004EOOAXQ".to_owned(),
+      price: "624.99".to_owned(),
+      quantity: 57,
+      ..Default::default()
+    }
+  );
+  assert_eq!(rows[5].sku, "edifier-e10-fba");
+  assert_eq!(rows[5].quantity, 0);
+}
+
+#[test]
+fn yes_no() {
+  assert_eq!("Yes".parse::<YesNo>().unwrap(), YesNo(true));
+  assert_eq!("no".parse::<YesNo>().unwrap(), YesNo(false));
+  assert!("maybe".parse::<YesNo>().is_err());
+}
@@ -0,0 +1,142 @@
+extern crate chrono;
+extern crate mws;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use mws::constants::{MARKETPLACE_ID_DE, MARKETPLACE_ID_US};
+use mws::reports::settlement::*;
+
+#[test]
+fn settlement_report() {
+  let report = SettlementReport::parse(
+    &include_bytes!("./fixtures/settlement.tdff")[..],
+    SettlementLocale::for_marketplace(MARKETPLACE_ID_US),
+  )
+  .unwrap();
+
+  assert_eq!(
+    report.summary,
+    SettlementSummary {
+      settlement_id: "11925146381".to_owned(),
+      settlement_start_date: Utc.ymd(2019, 2, 8).and_hms(7, 57, 59),
+      settlement_end_date: Utc.ymd(2019, 2, 22).and_hms(7, 57, 59),
+      deposit_date: Some(Utc.ymd(2019, 2, 24).and_hms(7, 57, 59)),
+      total_amount: Decimal::new(890, 2),
+      currency: "USD".to_owned(),
+    }
+  );
+  assert_eq!(report.lines.len(), 12);
+  assert_eq!(
+    report.lines.iter().map(|line| line.amount).sum::<Decimal>(),
+    report.summary.total_amount
+  );
+
+  let line = &report.lines[0];
+  assert_eq!(line.transaction_type, "Order");
+  assert_eq!(line.order_id, Some("112-3456789-0123456".to_owned()));
+  assert_eq!(line.merchant_order_id, None);
+  assert_eq!(line.amount_type, "ItemPrice");
+  assert_eq!(line.amount_description, "Principal");
+  assert_eq!(line.amount, Decimal::new(12499, 2));
+  assert_eq!(line.posted_date, Some(NaiveDate::from_ymd(2019, 2, 10)));
+  assert_eq!(line.sku, Some("edifier-r1280t-fba".to_owned()));
+  assert_eq!(line.quantity_purchased, Some(1));
+
+  let line = &report.lines[8];
+  assert_eq!(line.transaction_type, "Refund");
+  assert_eq!(line.adjustment_id, Some("amzn1.sys.adj.0d3e1".to_owned()));
+  assert_eq!(line.quantity_purchased, None);
+
+  let orders = report.orders();
+  let orders: Vec<_> = orders
+    .iter()
+    .map(|order| (order.order_id, order.lines.len(), order.total))
+    .collect();
+  assert_eq!(
+    orders,
+    vec![
+      ("112-3456789-0123456", 8, Decimal::new(-1069, 2)),
+      ("112-9876543-2109876", 3, Decimal::new(5958, 2)),
+    ]
+  );
+
+  let fee_totals = report.fee_totals();
+  let fee_totals: Vec<_> = fee_totals
+    .iter()
+    .map(|fee| {
+      (
+        fee.amount_type,
+        fee.amount_description,
+        fee.count,
+        fee.total,
+      )
+    })
+    .collect();
+  assert_eq!(
+    fee_totals,
+    vec![
+      ("ItemPrice", "Principal", 3, Decimal::new(7598, 2)),
+      ("ItemPrice", "Tax", 1, Decimal::new(875, 2)),
+      (
+        "ItemFees",
+        "FBAPerUnitFulfillmentFee",
+        1,
+        Decimal::new(-319, 2)
+      ),
+      ("ItemFees", "Commission", 3, Decimal::new(-1515, 2)),
+      (
+        "ItemWithheldTax",
+        "MarketplaceFacilitatorTax-Principal",
+        1,
+        Decimal::new(-875, 2)
+      ),
+      ("Promotion", "Principal", 1, Decimal::new(-500, 2)),
+      ("ItemFees", "RefundCommission", 1, Decimal::new(-375, 2)),
+      (
+        "other-transaction",
+        "Subscription Fee",
+        1,
+        Decimal::new(-3999, 2)
+      ),
+    ]
+  );
+}
+
+#[test]
+fn settlement_report_european_locale() {
+  let report = SettlementReport::parse(
+    &include_bytes!("./fixtures/settlement_de.tdff")[..],
+    SettlementLocale::for_marketplace(MARKETPLACE_ID_DE),
+  )
+  .unwrap();
+
+  assert_eq!(
+    report.summary.settlement_start_date,
+    Utc.ymd(2019, 2, 8).and_hms(7, 57, 59)
+  );
+  assert_eq!(report.summary.total_amount, Decimal::new(103614, 2));
+  assert_eq!(report.summary.currency, "EUR");
+  assert_eq!(report.lines[0].amount, Decimal::new(109900, 2));
+  assert_eq!(report.lines[1].amount, Decimal::new(-6286, 2));
+  assert_eq!(
+    report.lines[1].posted_date,
+    Some(NaiveDate::from_ymd(2019, 2, 10))
+  );
+
+  // European dates are not valid in the default locale.
+  assert!(SettlementReport::parse(
+    &include_bytes!("./fixtures/settlement_de.tdff")[..],
+    SettlementLocale::Default,
+  )
+  .is_err());
+}
+
+#[test]
+fn settlement_report_without_summary() {
+  let rows = vec![SettlementRow {
+    settlement_id: "11925146381".to_owned(),
+    transaction_type: "Order".to_owned(),
+    amount: "1.00".to_owned(),
+    ..Default::default()
+  }];
+  assert!(SettlementReport::from_rows(rows, SettlementLocale::Default).is_err());
+}
@@ -0,0 +1,656 @@
+extern crate mws;
+
+mod common;
+
+use common::{TestResponse, TestServer};
+use mws::result::MwsError;
+use mws::spapi::lwa::*;
+use mws::spapi::{SpClient, SpClientOptions};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A JSON response with the headers of the SP-API
+fn json(status: u16, body: &str) -> TestResponse {
+  TestResponse::new(status, body)
+    .header("Content-Type", "application/json")
+    .header("x-amzn-RequestId", "7d9d1f34-9c2e-4f1b-8a4b-3c2d1e0f9a8b")
+    .header("x-amzn-RateLimit-Limit", "0.0167")
+}
+
+fn token_json(access_token: &str, expires_in: u64) -> TestResponse {
+  json(
+    200,
+    &format!(
+      r#"{{"access_token":"{}","refresh_token":"Atzr|REFRESH","token_type":"bearer","expires_in":{}}}"#,
+      access_token, expires_in
+    ),
+  )
+}
+
+fn get_lwa_options(stand_in: &TestServer) -> LwaOptions {
+  LwaOptions {
+    token_endpoint: format!("{}/auth/o2/token", stand_in.url),
+    client_id: "amzn1.application-oa2-client.CLIENT".to_owned(),
+    client_secret: "SECRET".to_owned(),
+    refresh_token: Some("Atzr|REFRESH".to_owned()),
+    refresh_margin: Some(Duration::from_secs(60)),
+  }
+}
+
+#[test]
+fn lwa_refresh_token() {
+  let stand_in = TestServer::start(vec![token_json("Atza|FIRST", 3600)]);
+  let provider = TokenProvider::new(get_lwa_options(&stand_in));
+
+  let workers: Vec<_> = (0..4)
+    .map(|_| {
+      let provider = provider.clone();
+      thread::spawn(move || provider.access_token().unwrap())
+    })
+    .collect();
+  for worker in workers {
+    assert_eq!(worker.join().unwrap(), "Atza|FIRST");
+  }
+  assert_eq!(provider.access_token().unwrap(), "Atza|FIRST");
+
+  // concurrent callers share one token request
+  let requests = stand_in.requests();
+  assert_eq!(requests.len(), 1);
+  assert_eq!(requests[0].method, "POST");
+  assert_eq!(requests[0].path, "/auth/o2/token");
+  assert_eq!(
+    requests[0].header("content-type"),
+    Some("application/x-www-form-urlencoded")
+  );
+  assert_eq!(
+    requests[0].form_value("grant_type"),
+    Some("refresh_token".to_owned())
+  );
+  assert_eq!(
+    requests[0].form_value("refresh_token"),
+    Some("Atzr|REFRESH".to_owned())
+  );
+  assert_eq!(
+    requests[0].form_value("client_secret"),
+    Some("SECRET".to_owned())
+  );
+}
+
+#[test]
+fn lwa_refresh_before_expiration() {
+  let stand_in = TestServer::start(vec![
+    token_json("Atza|FIRST", 30),
+    token_json("Atza|SECOND", 3600),
+  ]);
+  // the first token expires within the margin, so it is refreshed on the next call
+  let provider = TokenProvider::new(get_lwa_options(&stand_in));
+  assert_eq!(provider.access_token().unwrap(), "Atza|FIRST");
+  assert_eq!(provider.access_token().unwrap(), "Atza|SECOND");
+  assert_eq!(provider.access_token().unwrap(), "Atza|SECOND");
+  assert_eq!(stand_in.requests().len(), 2);
+}
+
+#[test]
+fn lwa_grantless() {
+  let stand_in = TestServer::start(vec![token_json("Atza|NOTIFICATIONS", 3600)]);
+  let provider = TokenProvider::new(LwaOptions {
+    refresh_token: None,
+    ..get_lwa_options(&stand_in)
+  });
+
+  assert_eq!(
+    provider
+      .grantless_access_token(SCOPE_NOTIFICATIONS)
+      .unwrap(),
+    "Atza|NOTIFICATIONS"
+  );
+  assert_eq!(
+    provider
+      .token(&Grant::ClientCredentials(SCOPE_NOTIFICATIONS.to_owned()))
+      .unwrap(),
+    "Atza|NOTIFICATIONS"
+  );
+  match provider.access_token() {
+    Err(MwsError::Msg(_)) => {}
+    other => panic!("unexpected result: {:?}", other),
+  }
+
+  let requests = stand_in.requests();
+  assert_eq!(requests.len(), 1);
+  assert_eq!(
+    requests[0].form_value("grant_type"),
+    Some("client_credentials".to_owned())
+  );
+  assert_eq!(
+    requests[0].form_value("scope"),
+    Some(SCOPE_NOTIFICATIONS.to_owned())
+  );
+  assert_eq!(requests[0].form_value("refresh_token"), None);
+}
+
+#[test]
+fn lwa_refresh_does_not_block_other_grants() {
+  use mws::client::StatusCode;
+  use mws::transport::{HeaderMap, Request, RequestBody, Response, Transport};
+  use std::io::Cursor;
+  use std::sync::mpsc::{channel, Receiver, Sender};
+
+  /// Holds `refresh_token` requests until `release` receives a message
+  #[derive(Debug)]
+  struct SlowTransport {
+    started: Mutex<Sender<()>>,
+    release: Mutex<Receiver<()>>,
+  }
+
+  impl Transport for SlowTransport {
+    fn send(&self, request: Request) -> mws::result::MwsResult<Response> {
+      let refresh = match request.body {
+        RequestBody::Form(ref form) => form
+          .iter()
+          .any(|(k, v)| k == "grant_type" && v == "refresh_token"),
+        _ => false,
+      };
+      let access_token = if refresh {
+        self.started.lock().unwrap().send(()).unwrap();
+        self.release.lock().unwrap().recv().unwrap();
+        "Atza|REFRESHED"
+      } else {
+        "Atza|NOTIFICATIONS"
+      };
+      Ok(Response::new(
+        StatusCode::OK,
+        HeaderMap::new(),
+        Cursor::new(token_json(access_token, 3600).body),
+      ))
+    }
+  }
+
+  let (started, refresh_started) = channel();
+  let (release_refresh, release) = channel();
+  let provider = TokenProvider::with_transport(
+    LwaOptions {
+      token_endpoint: "https://api.amazon.com/auth/o2/token".to_owned(),
+      client_id: "amzn1.application-oa2-client.CLIENT".to_owned(),
+      client_secret: "SECRET".to_owned(),
+      refresh_token: Some("Atzr|REFRESH".to_owned()),
+      refresh_margin: None,
+    },
+    SlowTransport {
+      started: Mutex::new(started),
+      release: Mutex::new(release),
+    },
+  );
+  assert_eq!(
+    provider
+      .grantless_access_token(SCOPE_NOTIFICATIONS)
+      .unwrap(),
+    "Atza|NOTIFICATIONS"
+  );
+
+  let refreshing = {
+    let provider = provider.clone();
+    thread::spawn(move || provider.access_token())
+  };
+  refresh_started.recv().unwrap();
+
+  // the cached grantless token is returned while the refresh is in progress
+  let (done, grantless) = channel();
+  {
+    let provider = provider.clone();
+    thread::spawn(move || {
+      done
+        .send(provider.grantless_access_token(SCOPE_NOTIFICATIONS))
+        .unwrap()
+    });
+  }
+  let token = grantless.recv_timeout(Duration::from_secs(5));
+  release_refresh.send(()).unwrap();
+  assert_eq!(token.unwrap().unwrap(), "Atza|NOTIFICATIONS");
+  assert_eq!(refreshing.join().unwrap().unwrap(), "Atza|REFRESHED");
+}
+
+#[test]
+fn lwa_error() {
+  let stand_in = TestServer::start(vec![
+    json(
+      400,
+      r#"{"error_description":"The request has an invalid grant parameter : refresh_token","error":"invalid_grant"}"#,
+    ),
+    token_json("Atza|FIRST", 3600),
+  ]);
+  let provider = TokenProvider::new(get_lwa_options(&stand_in));
+  match provider.access_token() {
+    Err(MwsError::LwaError {
+      status,
+      error,
+      description,
+    }) => {
+      assert_eq!(status.as_u16(), 400);
+      assert_eq!(error, "invalid_grant");
+      assert!(description.starts_with("The request has an invalid grant parameter"));
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+  assert_eq!(provider.access_token().unwrap(), "Atza|FIRST");
+  provider.invalidate(&Grant::RefreshToken);
+}
+
+fn get_sp_client(stand_in: &TestServer) -> SpClient {
+  SpClient::new(SpClientOptions {
+    endpoint: stand_in.url.clone(),
+    region: "us-east-1".to_owned(),
+    aws_access_key_id: "AKIDEXAMPLE".to_owned(),
+    secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_owned(),
+    lwa: get_lwa_options(stand_in),
+    ..Default::default()
+  })
+}
+
+fn fixture(body: &str) -> TestResponse {
+  json(200, body)
+}
+
+#[test]
+fn spapi_orders() {
+  use mws::orders::{FulfillmentChannel, OrderStatus};
+  use mws::spapi::orders::*;
+
+  let stand_in = TestServer::start(vec![
+    token_json("Atza|FIRST", 3600),
+    fixture(include_str!("./fixtures/spapi/getOrders.json")),
+    fixture(include_str!("./fixtures/spapi/getOrders.json")),
+    fixture(include_str!("./fixtures/spapi/getOrdersByNextToken.json")),
+    fixture(include_str!("./fixtures/spapi/getOrderItems.json")),
+    json(403, include_str!("./fixtures/spapi/Unauthorized.json")),
+    token_json("Atza|SECOND", 3600),
+    fixture(r#"{"payload":{"AmazonOrderId":"902-3159896-1390916","OrderStatus":"Pending"}}"#),
+  ]);
+  let client = get_sp_client(&stand_in);
+  let params = GetOrdersParameters {
+    MarketplaceIds: vec!["ATVPDKIKX0DER".to_owned()],
+    CreatedAfter: Some("2017-01-01T00:00:00Z".parse().unwrap()),
+    ..Default::default()
+  };
+
+  let (res, meta) = client
+    .with_meta(|client| getOrders(client, params.clone()))
+    .unwrap();
+  assert_eq!(meta.rate_limit, Some(0.0167));
+  assert_eq!(meta.request_id, "7d9d1f34-9c2e-4f1b-8a4b-3c2d1e0f9a8b");
+  assert_eq!(
+    res.NextToken,
+    Some("2YgYW55IGNhcm5hbCBwbGVhcw==".to_owned())
+  );
+  let order = &res.Orders[0];
+  assert_eq!(order.AmazonOrderId, "902-3159896-1390916");
+  assert_eq!(order.OrderStatus, OrderStatus::Pending);
+  assert_eq!(order.FulfillmentChannel, FulfillmentChannel::MFN);
+  assert_eq!(order.OrderTotal.as_ref().unwrap().Amount, "11.01");
+  assert_eq!(order.ShippingAddress.as_ref().unwrap().City, "SEATTLE");
+  assert_eq!(order.PurchaseDate.unwrap().timestamp(), 1484941775);
+
+  let orders: Vec<_> = iter_orders(&client, params)
+    .collect::<Result<_, _>>()
+    .unwrap();
+  let ids: Vec<&str> = orders.iter().map(|o| o.AmazonOrderId.as_ref()).collect();
+  assert_eq!(ids, vec!["902-3159896-1390916", "058-1233752-8214740"]);
+
+  let items: Vec<_> = iter_order_items(&client, "058-1233752-8214740".to_owned())
+    .collect::<Result<_, _>>()
+    .unwrap();
+  assert_eq!(items.len(), 1);
+  assert_eq!(items[0].SellerSKU, "CBA_OTF_1");
+  assert_eq!(items[0].ItemPrice.as_ref().unwrap().Amount, "25.99");
+
+  match getOrder(&client, "902-3159896-1390916") {
+    Err(MwsError::SpErrorResponse(res)) => {
+      assert_eq!(res.status.as_u16(), 403);
+      assert_eq!(res.errors[0].code, "Unauthorized");
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+  // the rejected access token is refreshed
+  let order = getOrder(&client, "902-3159896-1390916").unwrap();
+  assert_eq!(order.OrderStatus, OrderStatus::Pending);
+
+  let requests = stand_in.requests();
+  assert_eq!(requests.len(), 8);
+  assert_eq!(
+    requests[1].path,
+    "/orders/v0/orders?MarketplaceIds=ATVPDKIKX0DER&CreatedAfter=2017-01-01T00%3A00%3A00Z"
+  );
+  assert_eq!(requests[1].header("x-amz-access-token"), Some("Atza|FIRST"));
+  assert!(requests[1]
+    .header("authorization")
+    .unwrap()
+    .starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+  assert_eq!(
+    requests[3].path,
+    "/orders/v0/orders?MarketplaceIds=ATVPDKIKX0DER&NextToken=2YgYW55IGNhcm5hbCBwbGVhcw%3D%3D"
+  );
+  assert_eq!(
+    requests[4].path,
+    "/orders/v0/orders/058-1233752-8214740/orderItems"
+  );
+  assert_eq!(requests[6].path, "/auth/o2/token");
+  assert_eq!(
+    requests[7].header("x-amz-access-token"),
+    Some("Atza|SECOND")
+  );
+}
+
+#[test]
+fn spapi_tokens_not_in_debug_output() {
+  use mws::client::Method;
+  use mws::transport::{Request, ReqwestTransport, Response, Transport};
+
+  /// Records the `Debug` output of every request
+  #[derive(Debug, Clone, Default)]
+  struct DebugTransport {
+    inner: ReqwestTransport,
+    requests: Arc<Mutex<Vec<String>>>,
+  }
+
+  impl Transport for DebugTransport {
+    fn send(&self, request: Request) -> mws::result::MwsResult<Response> {
+      self.requests.lock().unwrap().push(format!("{:?}", request));
+      self.inner.send(request)
+    }
+  }
+
+  let stand_in = TestServer::start(vec![token_json("Atza|SECRET", 3600), fixture("{}")]);
+  let transport = DebugTransport::default();
+  let client = SpClient::with_transport(
+    SpClientOptions {
+      endpoint: stand_in.url.clone(),
+      region: "us-east-1".to_owned(),
+      aws_access_key_id: "AKIDEXAMPLE".to_owned(),
+      secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_owned(),
+      session_token: Some("SESSION-SECRET".to_owned()),
+      lwa: get_lwa_options(&stand_in),
+      ..Default::default()
+    },
+    transport.clone(),
+  );
+  client
+    .request(Method::GET, "/orders/v0/orders", &[], None)
+    .unwrap();
+
+  let requests = stand_in.requests();
+  assert_eq!(
+    requests[1].header("x-amz-access-token"),
+    Some("Atza|SECRET")
+  );
+  assert_eq!(
+    requests[1].header("x-amz-security-token"),
+    Some("SESSION-SECRET")
+  );
+  let request = transport.requests.lock().unwrap()[1].clone();
+  assert!(request.contains("x-amz-access-token"));
+  assert!(!request.contains("Atza|SECRET"));
+  assert!(!request.contains("SESSION-SECRET"));
+}
+
+#[test]
+fn spapi_restricted_data_token() {
+  use mws::spapi::orders::*;
+
+  const RDT: &str = "Atz.sprdt|IQEBLjAsAhRmHjNgHpi0U-Dme37rR6CuUpSR";
+  let rdt = || {
+    fixture(include_str!(
+      "./fixtures/spapi/createRestrictedDataToken.json"
+    ))
+  };
+  let stand_in = TestServer::start(vec![
+    token_json("Atza|FIRST", 3600),
+    rdt(),
+    fixture(include_str!("./fixtures/spapi/getOrderAddress.json")),
+    fixture(include_str!("./fixtures/spapi/getOrderAddress.json")),
+    rdt(),
+    json(403, include_str!("./fixtures/spapi/Unauthorized.json")),
+    rdt(),
+    fixture(include_str!("./fixtures/spapi/getOrderBuyerInfo.json")),
+  ]);
+  let client = get_sp_client(&stand_in);
+
+  let address = getOrderAddress(&client, "902-3159896-1390916").unwrap();
+  let address = address.ShippingAddress.unwrap();
+  assert_eq!(address.Name, "Michigan address");
+  assert_eq!(address.Phone, Some("+1 480-386-0930 ext. 73824".to_owned()));
+  // the token is cached per resource
+  getOrderAddress(&client, "902-3159896-1390916").unwrap();
+  assert_eq!(client.rdt_cache().len(), 1);
+
+  match getOrderBuyerInfo(&client, "902-3159896-1390916") {
+    Err(MwsError::SpErrorResponse(res)) => assert_eq!(res.status.as_u16(), 403),
+    other => panic!("unexpected result: {:?}", other),
+  }
+  // the rejected token is requested again
+  let buyer = getOrderBuyerInfo(&client, "902-3159896-1390916").unwrap();
+  assert_eq!(buyer.BuyerEmail, "user@example.com");
+  assert_eq!(buyer.BuyerName, "John Doe");
+  assert_eq!(client.rdt_cache().len(), 2);
+
+  let requests = stand_in.requests();
+  assert_eq!(requests.len(), 8);
+  assert_eq!(requests[1].method, "POST");
+  assert_eq!(requests[1].path, "/tokens/2021-03-01/restrictedDataToken");
+  assert_eq!(requests[1].header("x-amz-access-token"), Some("Atza|FIRST"));
+  assert_eq!(
+    String::from_utf8(requests[1].body.clone()).unwrap(),
+    r#"{"restrictedResources":[{"method":"GET","path":"/orders/v0/orders/902-3159896-1390916/address"}]}"#
+  );
+  for &i in &[2, 3] {
+    assert_eq!(
+      requests[i].path,
+      "/orders/v0/orders/902-3159896-1390916/address"
+    );
+    assert_eq!(requests[i].header("x-amz-access-token"), Some(RDT));
+  }
+  assert_eq!(requests[6].path, "/tokens/2021-03-01/restrictedDataToken");
+  assert_eq!(
+    requests[7].path,
+    "/orders/v0/orders/902-3159896-1390916/buyerInfo"
+  );
+  assert_eq!(requests[7].header("x-amz-access-token"), Some(RDT));
+
+  // tokens are never logged
+  assert!(!format!("{:?}", client).contains("Atz"));
+}
+
+#[test]
+fn spapi_reports() {
+  use mws::spapi::reports::*;
+
+  const DOCUMENT: &str = "item-name\tseller-sku\tprice\tquantity\nWidget\tCBA_OTF_1\t25.99\t3\n";
+  // report documents are downloaded from another host
+  let storage = TestServer::start(vec![fixture(DOCUMENT)]);
+  let stand_in = TestServer::start(vec![
+    token_json("Atza|FIRST", 3600),
+    fixture(include_str!("./fixtures/spapi/createReport.json")),
+    fixture(include_str!("./fixtures/spapi/getReport.json")),
+    fixture(
+      &include_str!("./fixtures/spapi/getReportDocument.json").replace("{url}", &storage.url),
+    ),
+    fixture(include_str!("./fixtures/spapi/getReports.json")),
+    fixture(include_str!("./fixtures/spapi/getReportsByNextToken.json")),
+    fixture(""),
+  ]);
+  let client = get_sp_client(&stand_in);
+
+  let res = createReport(
+    &client,
+    CreateReportSpecification {
+      report_type: "_GET_MERCHANT_LISTINGS_ALL_DATA_".to_owned(),
+      marketplace_ids: vec!["ATVPDKIKX0DER".to_owned()],
+      data_start_time: Some("2021-06-30T15:56:53Z".parse().unwrap()),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(res.report_id, "ID323");
+
+  let report = getReport(&client, &res.report_id).unwrap();
+  assert_eq!(report.processing_status, ProcessingStatus::DONE);
+  assert_eq!(
+    mws_report_type(&report.report_type),
+    "_GET_MERCHANT_LISTINGS_ALL_DATA_"
+  );
+  let document_id = report.report_document_id.unwrap();
+  assert_eq!(document_id, "0356cf79-b8b0-4226-b4b9-0ee058ea5760");
+
+  let document = getReportDocument(&client, &document_id).unwrap();
+  assert_eq!(document.compression_algorithm, None);
+  let mut out = vec![];
+  let size = download_report_document(&client, &document, &mut out).unwrap();
+  assert_eq!(size, DOCUMENT.len() as u64);
+  assert_eq!(String::from_utf8(out).unwrap(), DOCUMENT);
+
+  let reports: Vec<_> = iter_reports(
+    &client,
+    GetReportsParameters {
+      report_types: vec!["_GET_MERCHANT_LISTINGS_ALL_DATA_".to_owned()],
+      ..Default::default()
+    },
+  )
+  .collect::<Result<_, _>>()
+  .unwrap();
+  let ids: Vec<&str> = reports.iter().map(|r| r.report_id.as_ref()).collect();
+  assert_eq!(ids, vec!["ID323", "ID324", "ID325"]);
+  assert_eq!(reports[1].processing_status, ProcessingStatus::IN_QUEUE);
+
+  cancelReport(&client, "ID324").unwrap();
+
+  let requests = stand_in.requests();
+  assert_eq!(requests.len(), 7);
+  assert_eq!(requests[1].method, "POST");
+  assert_eq!(requests[1].path, "/reports/2021-06-30/reports");
+  assert_eq!(
+    String::from_utf8(requests[1].body.clone()).unwrap(),
+    r#"{"reportType":"GET_MERCHANT_LISTINGS_ALL_DATA","marketplaceIds":["ATVPDKIKX0DER"],"dataStartTime":"2021-06-30T15:56:53Z"}"#
+  );
+  assert_eq!(requests[2].path, "/reports/2021-06-30/reports/ID323");
+  assert_eq!(
+    requests[3].path,
+    "/reports/2021-06-30/documents/0356cf79-b8b0-4226-b4b9-0ee058ea5760"
+  );
+  assert_eq!(
+    requests[4].path,
+    "/reports/2021-06-30/reports?reportTypes=GET_MERCHANT_LISTINGS_ALL_DATA"
+  );
+  assert_eq!(
+    requests[5].path,
+    "/reports/2021-06-30/reports?nextToken=VGhlIG5leHQgdG9rZW4%3D"
+  );
+  assert_eq!(requests[6].method, "DELETE");
+  assert_eq!(requests[6].path, "/reports/2021-06-30/reports/ID324");
+
+  // the pre-signed URL is requested without credentials
+  let downloads = storage.requests();
+  assert_eq!(downloads.len(), 1);
+  assert_eq!(
+    downloads[0].path,
+    "/NA/amzn1.tortuga.3.edbcd0d8-3434-8222-1234-52ad8ade1a5d.T1MKE3LEDWOKNM"
+  );
+  assert_eq!(downloads[0].header("x-amz-access-token"), None);
+  assert_eq!(downloads[0].header("authorization"), None);
+}
+
+#[test]
+fn spapi_submit_feed() {
+  use mws::feeds::message::inventory::InventoryMessage;
+  use mws::feeds::{Envelope, FeedType, OperationType};
+  use mws::spapi::feeds::*;
+
+  const REPORT: &str = include_str!("./fixtures/spapi/ProcessingReport.xml");
+  // feed documents are uploaded to and downloaded from another host
+  let storage = TestServer::start(vec![fixture(""), fixture(REPORT)]);
+  let stand_in = TestServer::start(vec![
+    token_json("Atza|FIRST", 3600),
+    fixture(
+      &include_str!("./fixtures/spapi/createFeedDocument.json").replace("{url}", &storage.url),
+    ),
+    fixture(include_str!("./fixtures/spapi/createFeed.json")),
+    fixture(include_str!("./fixtures/spapi/getFeedInProgress.json")),
+    fixture(include_str!("./fixtures/spapi/getFeed.json")),
+    fixture(
+      &include_str!("./fixtures/spapi/getFeedDocument.json").replace("{url}", &storage.url),
+    ),
+  ]);
+  let client = get_sp_client(&stand_in);
+
+  let mut envelope = Envelope::<InventoryMessage>::new("A1EXAMPLE2MERCHANT".to_owned());
+  envelope.add_message(
+    InventoryMessage {
+      message_id: "1".to_owned(),
+      sku: "CBA_OTF_1".to_owned(),
+      quantity: 3,
+      fulfillment_latency: 2,
+    },
+    Some(OperationType::Update),
+  );
+  let content = FeedContent::xml(&envelope).unwrap();
+  let xml = content.data.clone();
+
+  let mut out = vec![];
+  let feed = submit_feed(
+    &client,
+    SubmitFeedParameters {
+      feed_type: FeedType::Inventory.as_ref().to_owned(),
+      marketplace_ids: vec!["ATVPDKIKX0DER".to_owned()],
+      poll_interval: Some(Duration::from_millis(10)),
+      ..Default::default()
+    },
+    content,
+    &mut out,
+  )
+  .unwrap();
+  assert_eq!(feed.feed_id, "23492394");
+  assert_eq!(feed.processing_status, ProcessingStatus::DONE);
+  assert_eq!(String::from_utf8(out).unwrap(), REPORT);
+
+  let requests = stand_in.requests();
+  assert_eq!(requests.len(), 6);
+  assert_eq!(requests[1].method, "POST");
+  assert_eq!(requests[1].path, "/feeds/2021-06-30/documents");
+  assert_eq!(
+    String::from_utf8(requests[1].body.clone()).unwrap(),
+    r#"{"contentType":"text/xml; charset=UTF-8"}"#
+  );
+  assert_eq!(requests[2].path, "/feeds/2021-06-30/feeds");
+  assert_eq!(
+    String::from_utf8(requests[2].body.clone()).unwrap(),
+    r#"{"feedType":"POST_INVENTORY_AVAILABILITY_DATA","marketplaceIds":["ATVPDKIKX0DER"],"inputFeedDocumentId":"3d4e42b5-1d6e-44e8-a89c-2abfca0625bb"}"#
+  );
+  for &i in &[3, 4] {
+    assert_eq!(requests[i].path, "/feeds/2021-06-30/feeds/23492394");
+  }
+  assert_eq!(
+    requests[5].path,
+    "/feeds/2021-06-30/documents/0356cf79-b8b0-4226-b4b9-0ee058ea5760"
+  );
+
+  // the feed is uploaded to the pre-signed URL without credentials
+  let transfers = storage.requests();
+  assert_eq!(transfers.len(), 2);
+  assert_eq!(transfers[0].method, "PUT");
+  assert_eq!(
+    transfers[0].path,
+    "/NA/amzn1.tortuga.3.edbcd0d8-3434-8222-1234-52ad8ade1a5d.T1MKE3LEDWOKNM"
+  );
+  assert_eq!(
+    transfers[0].header("content-type"),
+    Some("text/xml; charset=UTF-8")
+  );
+  assert_eq!(transfers[0].header("x-amz-access-token"), None);
+  assert_eq!(transfers[0].body, xml);
+  assert!(String::from_utf8(xml)
+    .unwrap()
+    .contains("<SKU>CBA_OTF_1</SKU>"));
+  assert_eq!(transfers[1].method, "GET");
+  assert_eq!(
+    transfers[1].path,
+    "/NA/amzn1.tortuga.3.920614b0-fc4c-4393-b0d9-fff175300000.T29XK4YL08B2VM"
+  );
+}
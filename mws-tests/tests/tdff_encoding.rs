@@ -0,0 +1,76 @@
+extern crate mws;
+
+use mws::constants::{get_flat_file_encoding, MARKETPLACE_ID_DE, MARKETPLACE_ID_JP};
+use mws::reports::rows::{MerchantListingRow, OpenListingRow};
+use mws::tdff::{decoding_reader, TdffParser};
+use std::io::Read;
+
+#[test]
+fn shift_jis() {
+  let bytes = &include_bytes!("./fixtures/merchant_listings_all_jp_sjis.tdff")[..];
+  assert!(TdffParser::new(bytes)
+    .and_then(|parser| parser.parse_all::<MerchantListingRow>())
+    .is_err());
+
+  let rows: Vec<MerchantListingRow> = TdffParser::for_marketplace(bytes, MARKETPLACE_ID_JP)
+    .unwrap()
+    .parse_all()
+    .unwrap();
+  let expected: Vec<MerchantListingRow> =
+    TdffParser::new(&include_bytes!("./fixtures/merchant_listings_all_jp.tdff")[..])
+      .unwrap()
+      .parse_all()
+      .unwrap();
+  assert_eq!(rows, expected);
+  assert_eq!(rows[0].merchant_shipping_group, "移行済みテンプレート");
+}
+
+#[test]
+fn windows_1252() {
+  let bytes = &include_bytes!("./fixtures/open_listings_de.tdff")[..];
+  let rows: Vec<OpenListingRow> = TdffParser::for_marketplace(bytes, MARKETPLACE_ID_DE)
+    .unwrap()
+    .parse_all()
+    .unwrap();
+  let skus: Vec<_> = rows.iter().map(|row| row.sku.as_str()).collect();
+  assert_eq!(skus, vec!["lautsprecher-grün", "boîte-à-musique"]);
+  assert_eq!(rows[0].price, "99,99");
+}
+
+#[test]
+fn byte_order_mark() {
+  // The BOM takes precedence over the encoding of the marketplace.
+  let bytes = &include_bytes!("./fixtures/open_listings_bom.tdff")[..];
+  let rows: Vec<OpenListingRow> = TdffParser::for_marketplace(bytes, MARKETPLACE_ID_DE)
+    .unwrap()
+    .parse_all()
+    .unwrap();
+  assert_eq!(rows[0].sku, "lautsprecher-grün");
+  assert_eq!(rows[0].quantity, 5);
+
+  let mut decoded = String::new();
+  decoding_reader(bytes, None)
+    .read_to_string(&mut decoded)
+    .unwrap();
+  assert!(decoded.starts_with("sku\tasin"));
+}
+
+#[test]
+fn streaming() {
+  // Decoding in small reads gives the same result as decoding the whole file.
+  let bytes = &include_bytes!("./fixtures/merchant_listings_all_jp_sjis.tdff")[..];
+  let mut reader = decoding_reader(bytes, Some(get_flat_file_encoding(MARKETPLACE_ID_JP)));
+  let mut decoded = vec![];
+  let mut buf = [0; 7];
+  loop {
+    let n = reader.read(&mut buf).unwrap();
+    if n == 0 {
+      break;
+    }
+    decoded.extend_from_slice(&buf[..n]);
+  }
+  assert_eq!(
+    String::from_utf8(decoded).unwrap(),
+    include_str!("./fixtures/merchant_listings_all_jp.tdff")
+  );
+}
@@ -0,0 +1,108 @@
+extern crate mws;
+
+use mws::reports::rows::OpenListingRow;
+use mws::result::MwsError;
+use mws::tdff::{FromTdffRow, TdffParser};
+
+const MALFORMED: &str = "sku\tasin\tprice\tquantity
+edifier-b3\tB01CCBHNVS\t279.99\t224
+edifier-bte10\tB00NTQIIAQ\t162.99\tmany
+edifier-e10\tB009XGH2HG\t124.99
+edifier-e10-fba\tB009XGH2HG\t99.99\t0
+";
+
+fn line(err: &MwsError) -> u64 {
+  let position = match *err {
+    MwsError::Tdff(ref err) => err.position.as_ref(),
+    MwsError::Csv(ref err) => err.position(),
+    ref other => panic!("unexpected error: {:?}", other),
+  };
+  position.expect("position").line()
+}
+
+#[test]
+fn rows() {
+  let mut parser = TdffParser::new(&include_bytes!("./fixtures/report.tdff")[..]).unwrap();
+  let mut rows = parser.rows::<OpenListingRow>();
+  let first = rows.next().unwrap().unwrap();
+  assert_eq!(first.sku, "OQ-5B1U-8LAN");
+  assert_eq!(first.quantity, 57);
+
+  let second = rows.next().unwrap().unwrap();
+  assert_eq!(second.sku, "edifier-760d");
+  assert_eq!(second.quantity, 19);
+
+  let all = TdffParser::new(&include_bytes!("./fixtures/report.tdff")[..])
+    .unwrap()
+    .parse_all::<OpenListingRow>()
+    .unwrap();
+  assert_eq!(rows.count() + 2, all.len());
+}
+
+#[test]
+fn rows_error_line() {
+  let mut parser = TdffParser::new(MALFORMED.as_bytes()).unwrap();
+  let results: Vec<_> = parser.rows::<OpenListingRow>().collect();
+  assert_eq!(results.len(), 4);
+  assert_eq!(results[0].as_ref().unwrap().sku, "edifier-b3");
+  assert_eq!(line(results[1].as_ref().unwrap_err()), 3);
+  assert_eq!(line(results[2].as_ref().unwrap_err()), 4);
+  assert_eq!(results[3].as_ref().unwrap().sku, "edifier-e10-fba");
+
+  let err = TdffParser::new(MALFORMED.as_bytes())
+    .unwrap()
+    .parse_all::<OpenListingRow>()
+    .unwrap_err();
+  assert_eq!(line(&err), 3);
+}
+
+#[test]
+fn field_error() {
+  let err = TdffParser::new(MALFORMED.as_bytes())
+    .unwrap()
+    .parse_all::<OpenListingRow>()
+    .unwrap_err();
+  match err {
+    MwsError::Tdff(ref err) => {
+      let position = err.position.as_ref().unwrap();
+      assert_eq!(position.record(), 2);
+      assert_eq!(position.line(), 3);
+      assert_eq!(position.byte(), 57);
+      assert_eq!(err.header, "quantity");
+      assert_eq!(err.value, "many");
+      assert_eq!(err.target_type, "i32");
+    }
+    ref other => panic!("unexpected error: {:?}", other),
+  }
+  assert_eq!(
+    err.to_string(),
+    "tdff error: field 'quantity' of record 2 (line 3, byte 57): \
+     cannot parse 'many' as i32: invalid digit found in string"
+  );
+
+  // Without a parser, the position is unknown.
+  let row = vec![
+    ("sku", "edifier-b3".to_owned()),
+    ("quantity", "1.5".to_owned()),
+  ];
+  match OpenListingRow::from_tdff_row(&row) {
+    Err(MwsError::Tdff(err)) => {
+      assert_eq!(err.position, None);
+      assert_eq!(err.value, "1.5");
+    }
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn rows_skip_malformed() {
+  let mut parser = TdffParser::new(MALFORMED.as_bytes()).unwrap();
+  let mut rows = parser.rows::<OpenListingRow>().skip_malformed();
+  let skus: Vec<_> = rows.by_ref().map(|row| row.unwrap().sku).collect();
+  assert_eq!(skus, vec!["edifier-b3", "edifier-e10-fba"]);
+
+  let lines: Vec<_> = rows.errors().iter().map(line).collect();
+  assert_eq!(lines, vec![3, 4]);
+  assert_eq!(rows.take_errors().len(), 2);
+  assert!(rows.errors().is_empty());
+}
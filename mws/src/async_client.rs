@@ -0,0 +1,372 @@
+//! Non-blocking counterpart of `client::Client`, built on the async `reqwest::Client`
+//!
+//! Response bodies are buffered before they are handed to `FromXmlStream` decoders,
+//! so every type that can be decoded by `Client` can be decoded by `AsyncClient`.
+//! Requests are throttled and retried according to `ClientOptions::throttler` and
+//! `ClientOptions::retry_policy`, waiting does not block the executor. As with `Client`, a
+//! request failing with `RequestExpired` corrects the clock offset and is sent again once.
+
+use crate::client::{
+  error_response, server_clock_offset, ClientOptions, Method, ResponseMeta, StatusCode,
+};
+use crate::result::{MwsError, MwsResult};
+use crate::sign::SignatureV2;
+use crate::types::ToIso8601;
+use crate::xmlhelper::decode::{FromXmlStream, Stream};
+use crate::SerializeMwsParams;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::HeaderValue;
+use reqwest::{RequestBuilder, Response};
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::delay_for;
+
+/// The stream type async XML decoders read from
+pub type AsyncXmlStream = Stream<Cursor<Vec<u8>>>;
+
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+  options: ClientOptions,
+  http_client: reqwest::Client,
+  last_meta: Option<Arc<Mutex<Option<ResponseMeta>>>>,
+  /// Milliseconds added to the local clock when signing requests, shared by clones.
+  clock_offset: Arc<AtomicI64>,
+}
+
+impl AsyncClient {
+  pub fn new(options: ClientOptions) -> MwsResult<AsyncClient> {
+    Ok(AsyncClient::with_http_client(
+      options,
+      reqwest::Client::new(),
+    ))
+  }
+
+  pub fn with_http_client(options: ClientOptions, http_client: reqwest::Client) -> AsyncClient {
+    AsyncClient {
+      options: options,
+      http_client: http_client,
+      last_meta: None,
+      clock_offset: Arc::new(AtomicI64::new(0)),
+    }
+  }
+
+  pub fn options(&self) -> &ClientOptions {
+    &self.options
+  }
+
+  /// The difference between the server clock and the local clock, see `Client::clock_offset`.
+  pub fn clock_offset(&self) -> Duration {
+    Duration::milliseconds(self.clock_offset.load(Ordering::Relaxed))
+  }
+
+  /// Sets the difference between the server clock and the local clock,
+  /// the `Timestamp` of every request is shifted by `offset`.
+  pub fn set_clock_offset(&self, offset: Duration) {
+    self
+      .clock_offset
+      .store(offset.num_milliseconds(), Ordering::Relaxed);
+  }
+
+  fn new_signature<P>(&self, parameters: P) -> SignatureV2
+  where
+    P: SerializeMwsParams,
+  {
+    let mut sign = self.options.new_signature(parameters);
+    sign.add("Timestamp", (Utc::now() + self.clock_offset()).to_iso8601());
+    sign
+  }
+
+  /// Async version of `Client::with_meta`, `f` receives a client which records response metadata.
+  pub async fn with_meta<F, Fut, T>(&self, f: F) -> MwsResult<(T, ResponseMeta)>
+  where
+    F: FnOnce(AsyncClient) -> Fut,
+    Fut: Future<Output = MwsResult<T>>,
+  {
+    let last_meta = Arc::new(Mutex::new(None));
+    let client = AsyncClient {
+      last_meta: Some(last_meta.clone()),
+      ..self.clone()
+    };
+    let value = f(client).await?;
+    let meta = last_meta
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .take()
+      .unwrap_or_default();
+    Ok((value, meta))
+  }
+
+  pub async fn request<P>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+  ) -> MwsResult<Response>
+  where
+    P: SerializeMwsParams,
+  {
+    let params = parameters.into_mws_params();
+    self
+      .send_with_retry(path, action, || {
+        let sign = self.new_signature(params.clone());
+        let url = sign
+          .generate_url(method.clone(), path, version, action)?
+          .to_string();
+        Ok(self.http_client.request(method.clone(), &url))
+      })
+      .await
+  }
+
+  /// Sends a request with a body.
+  ///
+  /// The body can only be sent once, so the request is never retried.
+  pub async fn request_with_body<P, B>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+    body: B,
+    content_md5: String,
+    content_type: String,
+  ) -> MwsResult<Response>
+  where
+    P: SerializeMwsParams,
+    B: Into<reqwest::Body>,
+  {
+    let mut sign = self.new_signature(parameters);
+    sign.add("ContentMD5Value", content_md5);
+    let url = sign
+      .generate_url(method.clone(), path, version, action)?
+      .to_string();
+    let content_type = HeaderValue::from_str(&content_type)
+      .map_err(|_| MwsError::Msg(format!("invalid content type: '{}'", content_type)))?;
+    let request = self
+      .http_client
+      .request(method, &url)
+      .header(reqwest::header::CONTENT_TYPE, content_type)
+      .body(body);
+    self.send(path, action, request).await
+  }
+
+  pub async fn request_with_form<P>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+  ) -> MwsResult<Response>
+  where
+    P: SerializeMwsParams,
+  {
+    use std::collections::HashMap;
+
+    let params = parameters.into_mws_params();
+    self
+      .send_with_retry(path, action, || {
+        let sign = self.new_signature(params.clone());
+        let url = sign.generate_url(method.clone(), path, version, action)?;
+        let post_url = url.get_url_without_query();
+
+        let mut form: HashMap<String, String> = url.pairs.into_iter().collect();
+        form.insert("Signature".to_string(), url.signature);
+
+        Ok(self.http_client.request(method.clone(), &post_url).form(&form))
+      })
+      .await
+  }
+
+  pub async fn request_xml<P, T>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+  ) -> MwsResult<T>
+  where
+    P: SerializeMwsParams,
+    T: FromXmlStream<AsyncXmlStream>,
+  {
+    let resp = self
+      .request(method, path, version, action, parameters)
+      .await?;
+    decode_xml(resp).await
+  }
+
+  pub async fn request_xml_with_form<P, T>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+  ) -> MwsResult<T>
+  where
+    P: SerializeMwsParams,
+    T: FromXmlStream<AsyncXmlStream>,
+  {
+    let resp = self
+      .request_with_form(method, path, version, action, parameters)
+      .await?;
+    decode_xml(resp).await
+  }
+
+  pub async fn request_xml_with_body<P, B, T>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+    body: B,
+    content_md5: String,
+    content_type: String,
+  ) -> MwsResult<T>
+  where
+    P: SerializeMwsParams,
+    B: Into<reqwest::Body>,
+    T: FromXmlStream<AsyncXmlStream>,
+  {
+    let resp = self
+      .request_with_body(
+        method,
+        path,
+        version,
+        action,
+        parameters,
+        body,
+        content_md5,
+        content_type,
+      )
+      .await?;
+    decode_xml(resp).await
+  }
+
+  pub async fn request_raw<P>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+  ) -> MwsResult<(StatusCode, Vec<(String, String)>, Vec<u8>)>
+  where
+    P: SerializeMwsParams,
+  {
+    let resp = self
+      .request(method, path, version, action, parameters)
+      .await?;
+    let status = resp.status();
+    let headers = resp
+      .headers()
+      .iter()
+      .map(|view| {
+        (
+          view.0.to_string(),
+          String::from_utf8_lossy(view.1.as_bytes()).into_owned(),
+        )
+      })
+      .collect();
+    let body = resp.bytes().await?.to_vec();
+    Ok((status, headers, body))
+  }
+
+  async fn send(&self, path: &str, action: &str, request: RequestBuilder) -> MwsResult<Response> {
+    let throttler = self.options.throttler.as_ref();
+    let seller_id = &self.options.seller_id;
+    if let Some(throttler) = throttler {
+      while let Some(wait) = throttler.reserve(path, action, seller_id) {
+        delay_for(wait).await;
+      }
+    }
+    let res = match request.send().await {
+      Ok(resp) => {
+        let meta = ResponseMeta::from_headers(resp.headers());
+        if let Some(throttler) = throttler {
+          throttler.update_from_meta(path, action, seller_id, &meta);
+        }
+        if let Some(ref last_meta) = self.last_meta {
+          *last_meta
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(meta);
+        }
+        handle_error_status(resp).await
+      }
+      Err(err) => Err(err.into()),
+    };
+    if let (Some(throttler), Err(ref err)) = (throttler, &res) {
+      if err.is_throttled() {
+        throttler.throttled(path, action, seller_id);
+      }
+    }
+    res
+  }
+
+  /// Sends the request returned by `build`, retrying according to `ClientOptions::retry_policy`.
+  ///
+  /// `build` is called for every attempt, so each attempt is signed with a fresh timestamp.
+  /// A request failing with `RequestExpired` is sent again once after the clock offset
+  /// has been corrected, this does not count as an attempt.
+  async fn send_with_retry<F>(&self, path: &str, action: &str, build: F) -> MwsResult<Response>
+  where
+    F: Fn() -> MwsResult<RequestBuilder>,
+  {
+    let policy = &self.options.retry_policy;
+    let mut attempt = 1;
+    let mut clock_synced = false;
+    loop {
+      let sent_at = Utc::now();
+      match self.send(path, action, build()?).await {
+        Err(ref err) if !clock_synced && self.sync_clock(sent_at, err) => {
+          clock_synced = true;
+        }
+        Err(ref err) if policy.should_retry(attempt, err) => {
+          delay_for(policy.delay(attempt)).await;
+          attempt += 1;
+        }
+        res => return res,
+      }
+    }
+  }
+
+  /// Updates the clock offset from the server time of a `RequestExpired` response.
+  ///
+  /// Returns `true` if the offset has been changed.
+  fn sync_clock(&self, sent_at: DateTime<Utc>, err: &MwsError) -> bool {
+    match server_clock_offset(sent_at, err) {
+      // the server time has a precision of one second
+      Some(offset) if (offset - self.clock_offset()).num_seconds() != 0 => {
+        self.set_clock_offset(offset);
+        true
+      }
+      _ => false,
+    }
+  }
+}
+
+async fn handle_error_status(resp: Response) -> MwsResult<Response> {
+  if resp.status().is_success() {
+    Ok(resp)
+  } else {
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let body = resp.text().await?;
+    Err(error_response(status, &headers, body))
+  }
+}
+
+async fn decode_xml<T>(resp: Response) -> MwsResult<T>
+where
+  T: FromXmlStream<AsyncXmlStream>,
+{
+  let body = resp.bytes().await?.to_vec();
+  let mut stream = Stream::new(Cursor::new(body));
+  T::from_xml(&mut stream)
+}
@@ -1,34 +1,193 @@
-use reqwest::blocking::*;
-use reqwest::blocking::Response;
 pub use reqwest::{Method, StatusCode};
+use chrono::{DateTime, Duration, Utc};
 use crate::result::{MwsError, MwsResult};
 use crate::sign::SignatureV2;
 use std::io::Read;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use crate::xmlhelper::decode::{FromXmlStream, Stream};
+use crate::types::ToIso8601;
 use crate::SerializeMwsParams;
-use reqwest::header::HeaderValue;
+use reqwest::header::{HeaderMap, HeaderValue};
+pub use crate::transport::{Request, RequestBody, Response, Transport};
+pub use crate::retry::RetryPolicy;
+pub use crate::throttle::Throttler;
+use crate::transport::ReqwestTransport;
+
+#[cfg(feature = "async")]
+pub use crate::async_client::AsyncClient;
 
 #[derive(Debug)]
 pub struct ErrorResponse {
   pub status: StatusCode,
   pub info: Option<ErrorResponseInfo>,
   pub raw: String,
+  pub meta: ResponseMeta,
+}
+
+/// Metadata returned in the headers of every MWS response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseMeta {
+  /// The `RequestId` of the response, Amazon support asks for it when investigating a problem.
+  pub request_id: String,
+  /// The total number of requests allowed during the hourly quota period.
+  pub quota_max: Option<f64>,
+  /// The number of requests remaining in the current hourly quota period.
+  pub quota_remaining: Option<f64>,
+  /// The time the current hourly quota period resets.
+  pub quota_resets_on: Option<DateTime<Utc>>,
+  pub response_context: Option<String>,
+  /// The time MWS processed the request.
+  pub timestamp: Option<DateTime<Utc>>,
+  /// The `Date` header, the clock of the server.
+  pub date: Option<DateTime<Utc>>,
+}
+
+impl ResponseMeta {
+  /// Reads the `x-mws-*` headers of a response.
+  pub fn from_headers(headers: &HeaderMap) -> ResponseMeta {
+    fn get<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+      headers.get(name).and_then(|v| v.to_str().ok())
+    }
+    fn get_datetime(headers: &HeaderMap, name: &str) -> Option<DateTime<Utc>> {
+      get(headers, name)
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|v| v.with_timezone(&Utc))
+    }
+
+    ResponseMeta {
+      request_id: get(headers, "x-mws-request-id")
+        .unwrap_or_default()
+        .to_owned(),
+      quota_max: get(headers, "x-mws-quota-max").and_then(|v| v.parse().ok()),
+      quota_remaining: get(headers, "x-mws-quota-remaining").and_then(|v| v.parse().ok()),
+      quota_resets_on: get_datetime(headers, "x-mws-quota-resetsOn"),
+      response_context: get(headers, "x-mws-response-context").map(ToOwned::to_owned),
+      timestamp: get_datetime(headers, "x-mws-timestamp"),
+      date: get(headers, "date")
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .map(|v| v.with_timezone(&Utc)),
+    }
+  }
 }
 
 #[derive(Debug, Default)]
 pub struct ErrorResponseInfo {
   pub errors: Vec<ErrorResponseError>,
   pub request_id: String,
+  /// The server time, present in some `RequestExpired` responses.
+  pub timestamp: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Default, PartialEq)]
 pub struct ErrorResponseError {
   pub error_type: String,
-  pub code: String,
+  pub code: MwsErrorCode,
   pub message: String,
   pub detail: String,
 }
 
+str_enum! {
+  /// Error codes returned in `ErrorResponse/Error/Code`
+  ///
+  /// [Documentation](http://docs.developer.amazonservices.com/en_US/dev_guide/DG_ResponseFormat.html)
+  pub enum MwsErrorCode {
+    // Common errors
+    AccessDenied,
+    InputStreamDisconnected,
+    InternalError,
+    InvalidAccessKeyId,
+    InvalidAddress,
+    InvalidParameterValue,
+    MissingParameter,
+    QuotaExceeded,
+    RequestExpired,
+    RequestThrottled,
+    ServiceUnavailable,
+    SignatureDoesNotMatch,
+    // Orders
+    InvalidMarketplace,
+    // Feeds
+    ContentMD5DoesNotMatch,
+    ContentMD5Missing,
+    FeedCanceled,
+    FeedProcessingResultNotReady,
+    InputDataError,
+    InvalidFeedSubmissionId,
+    InvalidFeedType,
+    InvalidRequest,
+    // Reports
+    InvalidReportId,
+    InvalidReportType,
+    InvalidScheduleFrequency,
+    ReportNoLongerAvailable,
+    ReportNotReady,
+    // Products
+    InvalidUPCIdentifier,
+    // Merchant Fulfillment
+    InvalidShipmentId,
+    InvalidShippingServiceOfferId,
+    InvalidState,
+    ShippingServiceOfferNotAvailable,
+  }
+}
+
+impl MwsErrorCode {
+  /// The request was rejected because the request quota or the hourly quota was used up.
+  pub fn is_throttling(&self) -> bool {
+    match *self {
+      MwsErrorCode::RequestThrottled | MwsErrorCode::QuotaExceeded => true,
+      _ => false,
+    }
+  }
+
+  /// The credentials, the signature or the authorization of the request are invalid.
+  pub fn is_auth(&self) -> bool {
+    match *self {
+      MwsErrorCode::AccessDenied
+      | MwsErrorCode::InvalidAccessKeyId
+      | MwsErrorCode::SignatureDoesNotMatch
+      | MwsErrorCode::RequestExpired => true,
+      _ => false,
+    }
+  }
+
+  /// The request parameters or the submitted content are invalid,
+  /// sending the same request again will fail again.
+  pub fn is_client_input(&self) -> bool {
+    match *self {
+      MwsErrorCode::InvalidAddress
+      | MwsErrorCode::InvalidParameterValue
+      | MwsErrorCode::MissingParameter
+      | MwsErrorCode::InvalidMarketplace
+      | MwsErrorCode::ContentMD5DoesNotMatch
+      | MwsErrorCode::ContentMD5Missing
+      | MwsErrorCode::InputDataError
+      | MwsErrorCode::InvalidFeedSubmissionId
+      | MwsErrorCode::InvalidFeedType
+      | MwsErrorCode::InvalidRequest
+      | MwsErrorCode::InvalidReportId
+      | MwsErrorCode::InvalidReportType
+      | MwsErrorCode::InvalidScheduleFrequency
+      | MwsErrorCode::InvalidUPCIdentifier
+      | MwsErrorCode::InvalidShipmentId
+      | MwsErrorCode::InvalidShippingServiceOfferId => true,
+      _ => false,
+    }
+  }
+
+  /// The request may succeed if it is sent again later.
+  pub fn is_retryable(&self) -> bool {
+    match *self {
+      MwsErrorCode::InternalError
+      | MwsErrorCode::ServiceUnavailable
+      | MwsErrorCode::InputStreamDisconnected => true,
+      _ => self.is_throttling(),
+    }
+  }
+}
+
 impl ErrorResponseInfo {
   fn from_xml_stream<R: ::std::io::Read>(s: &mut Stream<R>) -> MwsResult<ErrorResponseInfo> {
     use crate::xmlhelper::decode::{characters, element, fold_elements, start_document};
@@ -60,6 +219,12 @@ impl ErrorResponseInfo {
           "RequestId" => {
             resp.request_id = characters(s)?;
           }
+          "Timestamp" => {
+            let value: String = characters(s)?;
+            resp.timestamp = DateTime::parse_from_rfc3339(&value)
+              .ok()
+              .map(|v| v.with_timezone(&Utc));
+          }
           _ => {}
         }
         Ok(())
@@ -69,8 +234,8 @@ impl ErrorResponseInfo {
   }
 }
 
-impl FromXmlStream<Stream<reqwest::blocking::Response>> for ErrorResponseInfo {
-  fn from_xml(s: &mut Stream<reqwest::blocking::Response>) -> MwsResult<ErrorResponseInfo> {
+impl FromXmlStream<Stream<Response>> for ErrorResponseInfo {
+  fn from_xml(s: &mut Stream<Response>) -> MwsResult<ErrorResponseInfo> {
     ErrorResponseInfo::from_xml_stream(s)
   }
 }
@@ -82,7 +247,7 @@ impl FromXmlStream<Stream<::std::io::Cursor<String>>> for ErrorResponseInfo {
 }
 
 /// [Reference](http://docs.developer.amazonservices.com/en_CA/dev_guide/DG_Endpoints.html)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ClientOptions {
   /// Your software can access Amazon Marketplace Web Service (Amazon MWS) using region-specific endpoints.
   pub endpoint: String,
@@ -96,29 +261,123 @@ pub struct ClientOptions {
   /// Your Amazon MWS account is identified by your access key Id, which Amazon MWS uses to look up your Secret Access Key.
   pub aws_access_key_id: String,
   pub secret_key: String,
+
+  /// How `Client` and `AsyncClient` retry failed requests. Retries are disabled by default.
+  pub retry_policy: RetryPolicy,
+
+  /// Makes `Client` and `AsyncClient` wait for the request quota of an operation before sending a request.
+  /// Clone the same `Throttler` into the options of every client working for a seller.
+  pub throttler: Option<Throttler>,
+
+  /// Disables the verification of downloaded reports against their `Content-MD5` header.
+  /// Reports are verified by default, see `content_md5`.
+  pub skip_content_md5_check: bool,
+}
+
+impl ClientOptions {
+  /// Creates a signature generator with the credentials, `SellerId` and request parameters added.
+  pub(crate) fn new_signature<P>(&self, parameters: P) -> SignatureV2
+  where
+    P: SerializeMwsParams,
+  {
+    let mut sign = SignatureV2::new(
+      &self.endpoint,
+      &self.aws_access_key_id,
+      &self.secret_key,
+      self.mws_auth_token.as_ref().map(AsRef::as_ref),
+    );
+    for (k, v) in parameters.into_mws_params() {
+      sign.add(&k, v);
+    }
+    sign.add("SellerId", &self.seller_id);
+    sign
+  }
 }
 
 #[derive(Debug, Clone)]
 pub struct Client {
   options: ClientOptions,
-  http_client: reqwest::blocking::Client,
+  transport: Arc<dyn Transport>,
+  /// Receives the metadata of every response, set by `with_meta`.
+  last_meta: Option<Arc<Mutex<Option<ResponseMeta>>>>,
+  /// Milliseconds added to the local clock when signing requests, shared by clones.
+  clock_offset: Arc<AtomicI64>,
 }
 
 impl Client {
   pub fn new(options: ClientOptions) -> MwsResult<Client> {
-    Ok(Client {
-      options: options,
-      http_client: reqwest::blocking::Client::new(),
-    })
+    Ok(Client::with_transport(options, ReqwestTransport::default()))
   }
 
   pub fn with_http_client(options: ClientOptions, http_client: reqwest::blocking::Client) -> Client {
+    Client::with_transport(options, ReqwestTransport::new(http_client))
+  }
+
+  /// Constructs a client which sends requests through `transport`
+  pub fn with_transport<T>(options: ClientOptions, transport: T) -> Client
+  where
+    T: Transport + 'static,
+  {
     Client {
       options: options,
-      http_client: http_client,
+      transport: Arc::new(transport),
+      last_meta: None,
+      clock_offset: Arc::new(AtomicI64::new(0)),
     }
   }
 
+  pub fn options(&self) -> &ClientOptions {
+    &self.options
+  }
+
+  /// The difference between the server clock and the local clock.
+  ///
+  /// The offset is updated when a request fails with `RequestExpired`.
+  pub fn clock_offset(&self) -> Duration {
+    Duration::milliseconds(self.clock_offset.load(Ordering::Relaxed))
+  }
+
+  /// Sets the difference between the server clock and the local clock,
+  /// the `Timestamp` of every request is shifted by `offset`.
+  pub fn set_clock_offset(&self, offset: Duration) {
+    self
+      .clock_offset
+      .store(offset.num_milliseconds(), Ordering::Relaxed);
+  }
+
+  fn new_signature<P>(&self, parameters: P) -> SignatureV2
+  where
+    P: SerializeMwsParams,
+  {
+    let mut sign = self.options.new_signature(parameters);
+    sign.add("Timestamp", (Utc::now() + self.clock_offset()).to_iso8601());
+    sign
+  }
+
+  /// Calls `f` with a client which records the metadata of the responses it receives,
+  /// and returns the value returned by `f` along with the metadata of the last response.
+  ///
+  /// This works with every operation, e.g.
+  /// `client.with_meta(|client| orders::ListOrders(client, params))`.
+  /// Failed requests carry their metadata in `MwsError::ErrorResponse`.
+  pub fn with_meta<F, T>(&self, f: F) -> MwsResult<(T, ResponseMeta)>
+  where
+    F: FnOnce(&Client) -> MwsResult<T>,
+  {
+    let last_meta = Arc::new(Mutex::new(None));
+    let client = Client {
+      last_meta: Some(last_meta.clone()),
+      ..self.clone()
+    };
+    let value = f(&client)?;
+    let meta = last_meta
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .take()
+      .unwrap_or_default();
+    Ok((value, meta))
+  }
+
   pub fn request<P>(
     &self,
     method: Method,
@@ -126,33 +385,26 @@ impl Client {
     version: &str,
     action: &str,
     parameters: P,
-  ) -> MwsResult<reqwest::blocking::Response>
+  ) -> MwsResult<Response>
   where
     P: SerializeMwsParams,
   {
-    let mut sign = SignatureV2::new(
-      &self.options.endpoint,
-      &self.options.aws_access_key_id,
-      &self.options.secret_key,
-      self.options.mws_auth_token.as_ref().map(AsRef::as_ref),
-    );
-    for (k, v) in parameters.into_mws_params() {
-      sign.add(&k, v);
-    }
-    sign.add("SellerId", &self.options.seller_id);
-    //sign.add("Merchant", self.options.seller_id.as_ref());
-    let url = sign
-      .generate_url(method.clone(), path, version, action)?
-      .to_string();
-    // println!("request: {}", url);
-    self
-      .http_client
-      .request(method, &url)
-      .send()
-      .map_err(MwsError::from)
-      .and_then(handle_error_status)
+    let params = parameters.into_mws_params();
+    self.send_with_retry(path, action, || {
+      let sign = self.new_signature(params.clone());
+      //sign.add("Merchant", self.options.seller_id.as_ref());
+      let url = sign
+        .generate_url(method.clone(), path, version, action)?
+        .to_string();
+      // println!("request: {}", url);
+      Ok(Request::new(method.clone(), url))
+    })
   }
 
+  /// Sends a request with a streamed body.
+  ///
+  /// The body can only be read once, so the request is never retried.
+  /// Use `request_with_bytes` to allow retries.
   pub fn request_with_body<P, R>(
     &self,
     method: Method,
@@ -163,21 +415,12 @@ impl Client {
     body: R,
     content_md5: String,
     content_type: String,
-  ) -> MwsResult<reqwest::blocking::Response>
+  ) -> MwsResult<Response>
   where
     P: SerializeMwsParams,
     R: Read + Send + 'static,
   {
-    let mut sign = SignatureV2::new(
-      &self.options.endpoint,
-      &self.options.aws_access_key_id,
-      &self.options.secret_key,
-      self.options.mws_auth_token.as_ref().map(AsRef::as_ref),
-    );
-    for (k, v) in parameters.into_mws_params() {
-      sign.add(&k, v);
-    }
-    sign.add("SellerId", &self.options.seller_id);
+    let mut sign = self.new_signature(parameters);
     sign.add("ContentMD5Value", content_md5);
     //sign.add("Merchant", self.options.seller_id.as_ref());
     let url = sign
@@ -185,56 +428,77 @@ impl Client {
       .to_string();
     //println!("request: {}", url);
 
-    self
-      .http_client
-      .request(method, &url)
-      .header(reqwest::header::CONTENT_TYPE, HeaderValue::from_str(&content_type).unwrap())
-      .body(reqwest::blocking::Body::new(body))
-      .send()
-      .map_err(MwsError::from)
-      .and_then(handle_error_status)
+    let mut request = Request::new(method, url);
+    request.headers.insert(
+      reqwest::header::CONTENT_TYPE,
+      HeaderValue::from_str(&content_type).unwrap(),
+    );
+    request.body = RequestBody::Reader(Box::new(body));
+    self.send(path, action, request)
   }
 
-  pub fn request_with_form<P>(
+  /// Sends a request with an in-memory body, retried according to `ClientOptions::retry_policy`.
+  pub fn request_with_bytes<P>(
     &self,
     method: Method,
     path: &str,
     version: &str,
     action: &str,
     parameters: P,
-  ) -> MwsResult<reqwest::blocking::Response>
+    body: Vec<u8>,
+    content_md5: String,
+    content_type: String,
+  ) -> MwsResult<Response>
   where
     P: SerializeMwsParams,
   {
-    use std::collections::HashMap;
-
-    let mut sign = SignatureV2::new(
-      &self.options.endpoint,
-      &self.options.aws_access_key_id,
-      &self.options.secret_key,
-      self.options.mws_auth_token.as_ref().map(AsRef::as_ref),
-    );
-    for (k, v) in parameters.into_mws_params() {
-      sign.add(&k, v);
-    }
-    sign.add("SellerId", &self.options.seller_id);
-    //sign.add("Merchant", self.options.seller_id.as_ref());
-    let url = sign.generate_url(method.clone(), path, version, action)?;
-    let post_url = url.get_url_without_query();
-
-    let mut form: HashMap<String, String> = url.pairs.into_iter().collect();
-    form.insert("Signature".to_string(), url.signature);
-
-    // println!("request url: {}", post_url);
-    // println!("request form: {:#?}", form);
+    let params = parameters.into_mws_params();
+    let content_type = HeaderValue::from_str(&content_type)
+      .map_err(|_| MwsError::Msg(format!("invalid content type: '{}'", content_type)))?;
+    self.send_with_retry(path, action, || {
+      let mut sign = self.new_signature(params.clone());
+      sign.add("ContentMD5Value", content_md5.clone());
+      let url = sign
+        .generate_url(method.clone(), path, version, action)?
+        .to_string();
+
+      let mut request = Request::new(method.clone(), url);
+      request
+        .headers
+        .insert(reqwest::header::CONTENT_TYPE, content_type.clone());
+      request.body = RequestBody::Bytes(body.clone());
+      Ok(request)
+    })
+  }
 
-    self
-      .http_client
-      .request(method, &post_url)
-      .form(&form)
-      .send()
-      .map_err(MwsError::from)
-      .and_then(handle_error_status)
+  pub fn request_with_form<P>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+  ) -> MwsResult<Response>
+  where
+    P: SerializeMwsParams,
+  {
+    let params = parameters.into_mws_params();
+    self.send_with_retry(path, action, || {
+      let sign = self.new_signature(params.clone());
+      //sign.add("Merchant", self.options.seller_id.as_ref());
+      let url = sign.generate_url(method.clone(), path, version, action)?;
+      let post_url = url.get_url_without_query();
+
+      let mut form = url.pairs;
+      form.push(("Signature".to_string(), url.signature));
+
+      // println!("request url: {}", post_url);
+      // println!("request form: {:#?}", form);
+
+      let mut request = Request::new(method.clone(), post_url);
+      request.body = RequestBody::Form(form);
+      Ok(request)
+    })
   }
 
   pub fn request_xml<P, T>(
@@ -247,7 +511,7 @@ impl Client {
   ) -> MwsResult<T>
   where
     P: SerializeMwsParams,
-    T: FromXmlStream<Stream<reqwest::blocking::Response>>,
+    T: FromXmlStream<Stream<Response>>,
   {
     let resp = self.request(method, path, version, action, parameters)?;
     let mut stream = Stream::new(resp);
@@ -265,7 +529,7 @@ impl Client {
   ) -> MwsResult<T>
   where
     P: SerializeMwsParams,
-    T: FromXmlStream<Stream<reqwest::blocking::Response>>,
+    T: FromXmlStream<Stream<Response>>,
   {
     let resp = self.request_with_form(method, path, version, action, parameters)?;
     let mut stream = Stream::new(resp);
@@ -286,7 +550,7 @@ impl Client {
   ) -> MwsResult<T>
   where
     P: SerializeMwsParams,
-    T: FromXmlStream<Stream<reqwest::blocking::Response>>,
+    T: FromXmlStream<Stream<Response>>,
     R: Read + Send + 'static,
   {
     let resp = self.request_with_body(
@@ -304,6 +568,36 @@ impl Client {
     Ok(v)
   }
 
+  pub fn request_xml_with_bytes<P, T>(
+    &self,
+    method: Method,
+    path: &str,
+    version: &str,
+    action: &str,
+    parameters: P,
+    body: Vec<u8>,
+    content_md5: String,
+    content_type: String,
+  ) -> MwsResult<T>
+  where
+    P: SerializeMwsParams,
+    T: FromXmlStream<Stream<Response>>,
+  {
+    let resp = self.request_with_bytes(
+      method,
+      path,
+      version,
+      action,
+      parameters,
+      body,
+      content_md5,
+      content_type,
+    )?;
+    let mut stream = Stream::new(resp);
+    let v = T::from_xml(&mut stream)?;
+    Ok(v)
+  }
+
   pub fn request_raw<P>(
     &self,
     method: Method,
@@ -315,25 +609,7 @@ impl Client {
   where
     P: SerializeMwsParams,
   {
-    let mut sign = SignatureV2::new(
-      &self.options.endpoint,
-      &self.options.aws_access_key_id,
-      &self.options.secret_key,
-      self.options.mws_auth_token.as_ref().map(AsRef::as_ref),
-    );
-    for (k, v) in parameters.into_mws_params() {
-      sign.add(&k, v);
-    }
-    sign.add("SellerId", &self.options.seller_id);
-    let url = sign
-      .generate_url(method.clone(), path, version, action)?
-      .to_string();
-    let mut resp = self
-      .http_client
-      .request(method, &url)
-      .send()
-      .map_err(MwsError::from)
-      .and_then(handle_error_status)?;
+    let mut resp = self.request(method, path, version, action, parameters)?;
 
     let headers = resp
       .headers()
@@ -343,33 +619,124 @@ impl Client {
 
     let mut body = vec![];
     resp.read_to_end(&mut body)?;
-    Ok((resp.status().clone(), headers, body))
+    Ok((resp.status(), headers, body))
+  }
+
+  fn send(&self, path: &str, action: &str, request: Request) -> MwsResult<Response> {
+    let throttler = self.options.throttler.as_ref();
+    let seller_id = &self.options.seller_id;
+    if let Some(throttler) = throttler {
+      throttler.acquire(path, action, seller_id);
+    }
+    let res = self.transport.send(request).and_then(|resp| {
+      let meta = ResponseMeta::from_headers(resp.headers());
+      if let Some(throttler) = throttler {
+        throttler.update_from_meta(path, action, seller_id, &meta);
+      }
+      if let Some(ref last_meta) = self.last_meta {
+        *last_meta
+          .lock()
+          .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(meta);
+      }
+      handle_error_status(resp)
+    });
+    if let (Some(throttler), Err(ref err)) = (throttler, &res) {
+      if err.is_throttled() {
+        throttler.throttled(path, action, seller_id);
+      }
+    }
+    res
+  }
+
+  /// Sends the request returned by `build`, retrying according to `ClientOptions::retry_policy`.
+  ///
+  /// `build` is called for every attempt, so each attempt is signed with a fresh timestamp.
+  /// A request failing with `RequestExpired` is sent again once after the clock offset
+  /// has been corrected, this does not count as an attempt.
+  fn send_with_retry<F>(&self, path: &str, action: &str, mut build: F) -> MwsResult<Response>
+  where
+    F: FnMut() -> MwsResult<Request>,
+  {
+    let policy = &self.options.retry_policy;
+    let mut attempt = 1;
+    let mut clock_synced = false;
+    loop {
+      let sent_at = Utc::now();
+      match self.send(path, action, build()?) {
+        Err(ref err) if !clock_synced && self.sync_clock(sent_at, err) => {
+          clock_synced = true;
+        }
+        Err(ref err) if policy.should_retry(attempt, err) => {
+          thread::sleep(policy.delay(attempt));
+          attempt += 1;
+        }
+        res => return res,
+      }
+    }
+  }
+
+  /// Updates the clock offset from the server time of a `RequestExpired` response.
+  ///
+  /// Returns `true` if the offset has been changed.
+  fn sync_clock(&self, sent_at: DateTime<Utc>, err: &MwsError) -> bool {
+    match server_clock_offset(sent_at, err) {
+      // the server time has a precision of one second
+      Some(offset) if (offset - self.clock_offset()).num_seconds() != 0 => {
+        self.set_clock_offset(offset);
+        true
+      }
+      _ => false,
+    }
+  }
+}
+
+/// The difference between the server clock and the local clock, from the server time of a
+/// `RequestExpired` response to a request sent at `sent_at`.
+pub(crate) fn server_clock_offset(sent_at: DateTime<Utc>, err: &MwsError) -> Option<Duration> {
+  let resp = match *err {
+    MwsError::ErrorResponse(ref resp) => resp,
+    _ => return None,
+  };
+  if err.error_code() != Some(&MwsErrorCode::RequestExpired) {
+    return None;
   }
+  resp
+    .meta
+    .date
+    .or(resp.meta.timestamp)
+    .or_else(|| resp.info.as_ref().and_then(|info| info.timestamp))
+    .map(|server_time| server_time - sent_at)
 }
 
 fn handle_error_status(resp: Response) -> MwsResult<Response> {
   if resp.status().is_success() {
     Ok(resp)
   } else {
-    use std::io::Cursor;
-
     let mut resp = resp;
     let mut body = String::new();
     resp.read_to_string(&mut body)?;
-    let mut s = Stream::new(Cursor::new(body.clone()));
-    match ErrorResponseInfo::from_xml(&mut s) {
-      Ok(info) => Err(MwsError::ErrorResponse(ErrorResponse {
-        status: resp.status().clone(),
-        raw: body,
-        info: Some(info),
-      })),
-      Err(_) => Err(MwsError::ErrorResponse(ErrorResponse {
-        status: resp.status().clone(),
-        raw: body,
-        info: None,
-      })),
+    Err(error_response(resp.status(), resp.headers(), body))
+  }
+}
+
+/// Builds a `MwsError::ErrorResponse` from the body of an unsuccessful response.
+pub(crate) fn error_response(status: StatusCode, headers: &HeaderMap, body: String) -> MwsError {
+  use std::io::Cursor;
+
+  let mut s = Stream::new(Cursor::new(body.clone()));
+  let info = ErrorResponseInfo::from_xml(&mut s).ok();
+  let mut meta = ResponseMeta::from_headers(headers);
+  if meta.request_id.is_empty() {
+    if let Some(ref info) = info {
+      meta.request_id = info.request_id.clone();
     }
   }
+  MwsError::ErrorResponse(ErrorResponse {
+    status: status,
+    raw: body,
+    info: info,
+    meta: meta,
+  })
 }
 
 #[cfg(test)]
@@ -381,6 +748,7 @@ pub fn get_test_client() -> Client {
     mws_auth_token: None,
     aws_access_key_id: env::var("AWSAccessKeyId").expect("get AWSAccessKeyId"),
     secret_key: env::var("SecretKey").expect("get SecretKey"),
+    ..Default::default()
   })
   .expect("create client")
 }
@@ -428,10 +796,110 @@ mod tests {
       err_info.errors[0],
       ErrorResponseError {
         error_type: "Sender".to_string(),
-        code: "InvalidAddress".to_string(),
+        code: MwsErrorCode::InvalidAddress,
         message: "Section Fake/2013-09-01 is invalid".to_string(),
         detail: "".to_string(),
       }
     );
   }
+
+  #[test]
+  fn test_response_meta_from_headers() {
+    let mut headers = HeaderMap::new();
+    for &(k, v) in &[
+      ("x-mws-request-id", "fd5b0e8b-3a4b-4b48-b9c8-4e2e5a1b3f7e"),
+      ("x-mws-quota-max", "200.0"),
+      ("x-mws-quota-remaining", "199.0"),
+      ("x-mws-quota-resetsOn", "2013-05-20T18:00:00.000Z"),
+      ("x-mws-response-context", "K1cAbm9eBNm+kBBIvgX/dsMD3Bmw/vZx"),
+      ("x-mws-timestamp", "2013-05-20T17:15:51.015Z"),
+      ("date", "Mon, 20 May 2013 17:15:51 GMT"),
+    ] {
+      headers.insert(k, HeaderValue::from_static(v));
+    }
+    let meta = ResponseMeta::from_headers(&headers);
+    assert_eq!(meta.request_id, "fd5b0e8b-3a4b-4b48-b9c8-4e2e5a1b3f7e");
+    assert_eq!(meta.quota_max, Some(200.0));
+    assert_eq!(meta.quota_remaining, Some(199.0));
+    assert_eq!(
+      meta.quota_resets_on.map(|t| t.to_rfc3339()),
+      Some("2013-05-20T18:00:00+00:00".to_owned())
+    );
+    assert_eq!(
+      meta.response_context,
+      Some("K1cAbm9eBNm+kBBIvgX/dsMD3Bmw/vZx".to_owned())
+    );
+    assert_eq!(
+      meta.timestamp.map(|t| t.timestamp_millis()),
+      Some(1369070151015)
+    );
+    assert_eq!(meta.date.map(|t| t.timestamp()), Some(1369070151));
+    assert_eq!(ResponseMeta::from_headers(&HeaderMap::new()), ResponseMeta::default());
+  }
+
+  #[test]
+  fn test_error_code() {
+    assert_eq!(MwsErrorCode::from("RequestThrottled"), MwsErrorCode::RequestThrottled);
+    assert_eq!(
+      MwsErrorCode::from("SomethingNew"),
+      MwsErrorCode::UnknownValue("SomethingNew".to_owned())
+    );
+    assert!(MwsErrorCode::QuotaExceeded.is_throttling());
+    assert!(MwsErrorCode::QuotaExceeded.is_retryable());
+    assert!(MwsErrorCode::InternalError.is_retryable());
+    assert!(MwsErrorCode::SignatureDoesNotMatch.is_auth());
+    assert!(!MwsErrorCode::SignatureDoesNotMatch.is_retryable());
+    assert!(MwsErrorCode::InvalidParameterValue.is_client_input());
+    assert!(!MwsErrorCode::from("SomethingNew").is_client_input());
+
+    let err = error_response(
+      StatusCode::UNAUTHORIZED,
+      &HeaderMap::new(),
+      r#"<?xml version="1.0"?>
+      <ErrorResponse xmlns="https://mws.amazonservices.com/Orders/2013-09-01">
+        <Error>
+          <Type>Sender</Type>
+          <Code>InvalidAccessKeyId</Code>
+          <Message>The AWS Access Key Id you provided does not exist in our records.</Message>
+        </Error>
+        <RequestId>f1b0b7f4-7a1b-4e2a-9d2e-2c3c6c3b9a5e</RequestId>
+      </ErrorResponse>"#
+        .to_owned(),
+    );
+    assert_eq!(err.error_code(), Some(&MwsErrorCode::InvalidAccessKeyId));
+    assert!(err.is_auth_error());
+    assert!(!err.is_client_input_error());
+    assert!(!err.is_throttled());
+    assert!(!err.should_try_again());
+  }
+
+  #[test]
+  fn test_sync_clock() {
+    let client = Client::with_transport(Default::default(), crate::transport::MockTransport::new());
+    let sent_at = Utc::now();
+    let err = error_response(
+      StatusCode::FORBIDDEN,
+      &HeaderMap::new(),
+      format!(
+        r#"<?xml version="1.0"?>
+        <ErrorResponse xmlns="https://mws.amazonservices.com/">
+          <Error>
+            <Type>Sender</Type>
+            <Code>RequestExpired</Code>
+            <Message>Request has expired.</Message>
+          </Error>
+          <Timestamp>{}</Timestamp>
+          <RequestId>f1b0b7f4-7a1b-4e2a-9d2e-2c3c6c3b9a5e</RequestId>
+        </ErrorResponse>"#,
+        (sent_at - Duration::minutes(20)).to_iso8601()
+      ),
+    );
+    assert!(client.sync_clock(sent_at, &err));
+    assert_eq!(client.clock_offset().num_seconds(), -20 * 60);
+    // already synced
+    assert!(!client.sync_clock(sent_at, &err));
+
+    let err = MwsError::Msg("error".to_owned());
+    assert!(!client.sync_clock(sent_at, &err));
+  }
 }
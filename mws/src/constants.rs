@@ -1,3 +1,5 @@
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_8, WINDOWS_1252};
+
 pub struct AmazonRegion {
   pub id: &'static str,
   pub name: &'static str,
@@ -60,7 +62,7 @@ lazy_static! {
 
     items.push(item!(MARKETPLACE_ID_JP, "fe", "Japan", "JP"));
     items.push(item!(MARKETPLACE_ID_AU, "fe", "Australia", "AU"));
-    items.push(item!(MARKETPLACE_ID_SG, "fe", "Australia", "AU"));
+    items.push(item!(MARKETPLACE_ID_SG, "fe", "Singapore", "SG"));
 
     items.push(item!(MARKETPLACE_ID_CN, "cn", "China", "CN"));
 
@@ -145,6 +147,24 @@ pub fn get_marketplace(id: &str) -> Option<&'static AmazonMarketplace> {
   MARKETPLACES.iter().find(|r| r.id == id)
 }
 
+/// The character encoding of the tab-delimited flat file reports of a marketplace id.
+///
+/// Shift_JIS in Japan, UTF-8 in China and India, windows-1252 in the other marketplaces. Ids
+/// which are not in `MARKETPLACES` are assumed to be windows-1252 as well.
+///
+/// [Reference](https://docs.developer.amazonservices.com/en_US/reports/Reports_ReportType.html)
+pub fn get_flat_file_encoding(marketplace_id: &str) -> &'static Encoding {
+  match marketplace_id {
+    MARKETPLACE_ID_JP => SHIFT_JIS,
+    MARKETPLACE_ID_CN | MARKETPLACE_ID_IN => UTF_8,
+    MARKETPLACE_ID_CA | MARKETPLACE_ID_MX | MARKETPLACE_ID_US => WINDOWS_1252,
+    MARKETPLACE_ID_DE | MARKETPLACE_ID_ES | MARKETPLACE_ID_FR | MARKETPLACE_ID_IT
+    | MARKETPLACE_ID_GB => WINDOWS_1252,
+    MARKETPLACE_ID_AU | MARKETPLACE_ID_SG => WINDOWS_1252,
+    _ => WINDOWS_1252,
+  }
+}
+
 impl AmazonMarketplace {
   pub fn resolve_state_code(&self, country_id: &str, state: &str) -> Option<String> {
     match self.id {
@@ -152,6 +172,13 @@ impl AmazonMarketplace {
       _ => Some(state.to_string()),
     }
   }
+
+  /// The character encoding of the tab-delimited flat file reports of the marketplace.
+  ///
+  /// Shift_JIS in Japan, UTF-8 in China and India, windows-1252 in the other marketplaces.
+  pub fn flat_file_encoding(&self) -> &'static Encoding {
+    get_flat_file_encoding(self.id)
+  }
 }
 
 const US_STATES: &'static [(&'static str, &'static str)] = &[
@@ -437,3 +464,37 @@ fn normalize(text: &str) -> String {
     .collect::<Vec<_>>()
     .join(" ")
 }
+
+#[test]
+fn test_flat_file_encoding() {
+  let expected = [
+    (MARKETPLACE_ID_CA, WINDOWS_1252),
+    (MARKETPLACE_ID_MX, WINDOWS_1252),
+    (MARKETPLACE_ID_US, WINDOWS_1252),
+    (MARKETPLACE_ID_DE, WINDOWS_1252),
+    (MARKETPLACE_ID_ES, WINDOWS_1252),
+    (MARKETPLACE_ID_FR, WINDOWS_1252),
+    (MARKETPLACE_ID_IT, WINDOWS_1252),
+    (MARKETPLACE_ID_GB, WINDOWS_1252),
+    (MARKETPLACE_ID_IN, UTF_8),
+    (MARKETPLACE_ID_JP, SHIFT_JIS),
+    (MARKETPLACE_ID_AU, WINDOWS_1252),
+    (MARKETPLACE_ID_SG, WINDOWS_1252),
+    (MARKETPLACE_ID_CN, UTF_8),
+  ];
+  assert_eq!(expected.len(), MARKETPLACES.len());
+  for marketplace in MARKETPLACES.iter() {
+    let encoding = expected
+      .iter()
+      .find(|&&(id, _)| id == marketplace.id)
+      .map(|&(_, encoding)| encoding)
+      .unwrap_or_else(|| panic!("no expected encoding for {}", marketplace.name));
+    assert_eq!(
+      marketplace.flat_file_encoding(),
+      encoding,
+      "{}",
+      marketplace.name
+    );
+  }
+  assert_eq!(get_flat_file_encoding("UNKNOWN"), WINDOWS_1252);
+}
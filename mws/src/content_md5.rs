@@ -0,0 +1,192 @@
+//! Verification of the `Content-MD5` header of downloaded documents
+//!
+//! `reports::GetReport` and `feeds::GetFeedSubmissionResult` hash the body while it is written
+//! to the output, and fail with `MwsError::ContentMD5Mismatch` if it is incomplete or corrupted.
+//! `reports::fetch_report_rows_with` hashes the body while it is parsed, with `Md5Reader`.
+//! Set `ClientOptions::skip_content_md5_check` to turn the verification off.
+
+use crate::result::{MwsError, MwsResult};
+use crypto::digest::Digest;
+use crypto::md5::Md5;
+use reqwest::header::HeaderMap;
+use std::io::{self, Read, Write};
+
+/// MD5 digest and size of the bytes seen so far
+#[derive(Clone, Copy)]
+struct Md5State {
+  md5: Md5,
+  size: u64,
+}
+
+impl Md5State {
+  fn new() -> Self {
+    Md5State {
+      md5: Md5::new(),
+      size: 0,
+    }
+  }
+
+  fn input(&mut self, buf: &[u8]) {
+    self.md5.input(buf);
+    self.size += buf.len() as u64;
+  }
+
+  fn content_md5(&self) -> String {
+    let mut md5 = self.md5;
+    let mut digest = [0; 16];
+    md5.result(&mut digest);
+    base64::encode(&digest)
+  }
+
+  fn verify(&self, expected: &str) -> MwsResult<()> {
+    let actual = self.content_md5();
+    if actual == expected.trim() {
+      Ok(())
+    } else {
+      Err(MwsError::ContentMD5Mismatch {
+        expected: expected.to_owned(),
+        actual,
+        size: self.size,
+      })
+    }
+  }
+}
+
+/// Writer which computes the MD5 digest of everything written through it
+pub struct Md5Writer<W> {
+  inner: W,
+  state: Md5State,
+}
+
+impl<W: Write> Md5Writer<W> {
+  pub fn new(inner: W) -> Self {
+    Md5Writer {
+      inner,
+      state: Md5State::new(),
+    }
+  }
+
+  /// The number of bytes written so far.
+  pub fn size(&self) -> u64 {
+    self.state.size
+  }
+
+  /// The base64 encoded digest of the bytes written so far, as sent in `Content-MD5` headers.
+  pub fn content_md5(&self) -> String {
+    self.state.content_md5()
+  }
+
+  /// Returns `MwsError::ContentMD5Mismatch` if the bytes written so far do not match `expected`.
+  pub fn verify(&self, expected: &str) -> MwsResult<()> {
+    self.state.verify(expected)
+  }
+}
+
+impl<W: Write> Write for Md5Writer<W> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let written = self.inner.write(buf)?;
+    self.state.input(&buf[..written]);
+    Ok(written)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.inner.flush()
+  }
+}
+
+/// Reader which computes the MD5 digest of everything read through it
+///
+/// Used to verify a body which is consumed while it is downloaded, e.g. by a parser.
+pub struct Md5Reader<R> {
+  inner: R,
+  state: Md5State,
+}
+
+impl<R: Read> Md5Reader<R> {
+  pub fn new(inner: R) -> Self {
+    Md5Reader {
+      inner,
+      state: Md5State::new(),
+    }
+  }
+
+  /// The number of bytes read so far.
+  pub fn size(&self) -> u64 {
+    self.state.size
+  }
+
+  /// The base64 encoded digest of the bytes read so far, as sent in `Content-MD5` headers.
+  pub fn content_md5(&self) -> String {
+    self.state.content_md5()
+  }
+
+  /// Returns `MwsError::ContentMD5Mismatch` if the bytes read so far do not match `expected`.
+  pub fn verify(&self, expected: &str) -> MwsResult<()> {
+    self.state.verify(expected)
+  }
+}
+
+impl<R: Read> Read for Md5Reader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let read = self.inner.read(buf)?;
+    self.state.input(&buf[..read]);
+    Ok(read)
+  }
+}
+
+/// Returns the `Content-MD5` header.
+pub(crate) fn header(headers: &HeaderMap) -> MwsResult<Option<String>> {
+  match headers.get("Content-MD5") {
+    Some(value) => Ok(Some(::std::str::from_utf8(value.as_bytes())?.to_owned())),
+    None => Ok(None),
+  }
+}
+
+/// Copies `body` to `out`, verifying it against `content_md5` if it is set.
+pub(crate) fn copy<R: Read, W: Write>(
+  body: &mut R,
+  out: &mut W,
+  content_md5: Option<&str>,
+) -> MwsResult<u64> {
+  match content_md5 {
+    Some(content_md5) => {
+      let mut writer = Md5Writer::new(out);
+      let size = io::copy(body, &mut writer)?;
+      writer.verify(content_md5)?;
+      Ok(size)
+    }
+    None => Ok(io::copy(body, out)?),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_md5_writer() {
+    let mut out = vec![];
+    let mut body = &b"sku\tasin\tprice\tquantity\n"[..];
+    let size = copy(&mut body, &mut out, Some("TUsVmrJzq3KOWC64dutmcQ==")).unwrap();
+    assert_eq!(size, 24);
+    assert_eq!(&out[..], &b"sku\tasin\tprice\tquantity\n"[..]);
+
+    let mut body = &b"sku\tasin\tprice\tquan"[..];
+    match copy(&mut body, &mut vec![], Some("TUsVmrJzq3KOWC64dutmcQ==")) {
+      Err(MwsError::ContentMD5Mismatch { expected, size, .. }) => {
+        assert_eq!(expected, "TUsVmrJzq3KOWC64dutmcQ==");
+        assert_eq!(size, 19);
+      }
+      other => panic!("unexpected result: {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_md5_reader() {
+    let mut reader = Md5Reader::new(&b"sku\tasin\tprice\tquantity\n"[..]);
+    io::copy(&mut reader, &mut io::sink()).unwrap();
+    assert_eq!(reader.size(), 24);
+    reader.verify("TUsVmrJzq3KOWC64dutmcQ==").unwrap();
+    assert!(reader.verify("agLXebc4tF+Ux/Pa1cmomg==").is_err());
+  }
+}
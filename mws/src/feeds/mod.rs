@@ -4,11 +4,15 @@
 
 use chrono::{DateTime, Utc};
 use crate::client::{Client, Method};
+use crate::content_md5;
+use crate::paginator::{Page, Paginator};
 use crate::result::MwsResult;
 use std::io::{Read, Write};
 use crate::xmlhelper::encode;
 
 pub mod message;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 
 static PATH: &'static str = "/";
 static VERSION: &'static str = "2009-01-01";
@@ -173,6 +177,10 @@ where
     .map_err(Into::into)
 }
 
+/// Writes the feed processing report to `out` and returns its size.
+///
+/// If the response has a Content-MD5 header, the report is verified against it while it is
+/// written to `out`, like the contents of `reports::GetReport`.
 #[allow(non_snake_case)]
 pub fn GetFeedSubmissionResult<W: Write>(
   client: &Client,
@@ -187,8 +195,13 @@ pub fn GetFeedSubmissionResult<W: Write>(
     "GetFeedSubmissionResult",
     params,
   )?;
-  let size = ::std::io::copy(&mut resp, out)?;
-  Ok(size)
+  let content_md5 = content_md5::header(resp.headers())?;
+  let verify = if client.options().skip_content_md5_check {
+    None
+  } else {
+    content_md5.as_ref().map(AsRef::as_ref)
+  };
+  content_md5::copy(&mut resp, out, verify)
 }
 
 /// Parameters for `GetFeedSubmissionList`
@@ -221,6 +234,7 @@ pub struct GetFeedSubmissionListResponse {
   #[from_xml_stream(no_list_wrapper)]
   pub FeedSubmissionInfo: Vec<FeedSubmissionInfo>,
   pub NextToken: Option<String>,
+  pub HasNext: bool,
 }
 
 response_envelope_type!(
@@ -269,3 +283,24 @@ pub fn GetFeedSubmissionListByNextToken(
     .map(|e: GetFeedSubmissionListByNextTokenEnvelope| e.into_inner())
     .map_err(|err| err.into())
 }
+
+/// Iterates over the feed submissions returned by `GetFeedSubmissionList` and `GetFeedSubmissionListByNextToken`.
+pub fn iter_feed_submissions(
+  client: &Client,
+  parameters: GetFeedSubmissionListParameters,
+) -> Paginator<'_, FeedSubmissionInfo> {
+  Paginator::new(
+    client,
+    move |client| GetFeedSubmissionList(client, parameters).map(Into::into),
+    |client, next_token| GetFeedSubmissionListByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<GetFeedSubmissionListResponse> for Page<FeedSubmissionInfo> {
+  fn from(res: GetFeedSubmissionListResponse) -> Self {
+    Page {
+      items: res.FeedSubmissionInfo,
+      next_token: if res.HasNext { res.NextToken } else { None },
+    }
+  }
+}
@@ -0,0 +1,100 @@
+//! Async variants of the Feeds API operations
+
+use super::*;
+use crate::client::AsyncClient;
+use crate::content_md5::Md5Writer;
+
+/// Async version of `feeds::SubmitFeed`
+#[allow(non_snake_case)]
+pub async fn SubmitFeed<B>(
+  client: &AsyncClient,
+  parameters: SubmitFeedParameters,
+  content: B,
+  content_md5: String,
+  content_type: String,
+) -> MwsResult<SubmitFeedResponse>
+where
+  B: Into<reqwest::Body>,
+{
+  client
+    .request_xml_with_body(
+      Method::POST,
+      PATH,
+      VERSION,
+      "SubmitFeed",
+      parameters,
+      content,
+      content_md5,
+      content_type,
+    )
+    .await
+    .map(|e: SubmitFeedEnvelope| e.into_inner())
+}
+
+/// Async version of `feeds::GetFeedSubmissionResult`, the body is written to `out` chunk by chunk.
+#[allow(non_snake_case)]
+pub async fn GetFeedSubmissionResult<W: Write>(
+  client: &AsyncClient,
+  FeedSubmissionId: String,
+  out: &mut W,
+) -> MwsResult<u64> {
+  let params = vec![("FeedSubmissionId".to_string(), FeedSubmissionId)];
+  let mut resp = client
+    .request(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetFeedSubmissionResult",
+      params,
+    )
+    .await?;
+  let content_md5 = content_md5::header(resp.headers())?;
+  let mut writer = Md5Writer::new(out);
+  while let Some(chunk) = resp.chunk().await? {
+    writer.write_all(&chunk)?;
+  }
+  match content_md5 {
+    Some(ref content_md5) if !client.options().skip_content_md5_check => {
+      writer.verify(content_md5)?
+    }
+    _ => {}
+  }
+  Ok(writer.size())
+}
+
+/// Async version of `feeds::GetFeedSubmissionList`
+#[allow(non_snake_case)]
+pub async fn GetFeedSubmissionList(
+  client: &AsyncClient,
+  parameters: GetFeedSubmissionListParameters,
+) -> MwsResult<GetFeedSubmissionListResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetFeedSubmissionList",
+      parameters,
+    )
+    .await
+    .map(|e: GetFeedSubmissionListEnvelope| e.into_inner())
+}
+
+/// Async version of `feeds::GetFeedSubmissionListByNextToken`
+#[allow(non_snake_case)]
+pub async fn GetFeedSubmissionListByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<GetFeedSubmissionListResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetFeedSubmissionListByNextToken",
+      params,
+    )
+    .await
+    .map(|e: GetFeedSubmissionListByNextTokenEnvelope| e.into_inner())
+}
@@ -4,9 +4,12 @@
 
 use chrono::{DateTime, Utc};
 use crate::client::{Client, Method};
+use crate::paginator::{Page, Paginator};
 use crate::result::MwsResult;
 
 mod types;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub use self::types::*;
 
 static PATH: &'static str = "/FulfillmentInboundShipment/2010-10-01";
@@ -80,6 +83,27 @@ pub fn ListInboundShipmentsByNextToken(
     .map_err(|err| err.into())
 }
 
+/// Iterates over the shipments returned by `ListInboundShipments` and `ListInboundShipmentsByNextToken`.
+pub fn iter_inbound_shipments(
+  client: &Client,
+  parameters: ListInboundShipmentsParameters,
+) -> Paginator<'_, InboundShipmentInfo> {
+  Paginator::new(
+    client,
+    move |client| ListInboundShipments(client, parameters).map(Into::into),
+    |client, next_token| ListInboundShipmentsByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<ListInboundShipmentsResponse> for Page<InboundShipmentInfo> {
+  fn from(res: ListInboundShipmentsResponse) -> Self {
+    Page {
+      items: res.ShipmentData,
+      next_token: res.NextToken,
+    }
+  }
+}
+
 /// Parameters for `ListInboundShipments`
 #[allow(non_snake_case)]
 #[derive(Debug, Default, Serialize, SerializeMwsParams)]
@@ -147,6 +171,27 @@ pub fn ListInboundShipmentItemsByNextToken(
     .map_err(|err| err.into())
 }
 
+/// Iterates over the items returned by `ListInboundShipmentItems` and `ListInboundShipmentItemsByNextToken`.
+pub fn iter_inbound_shipment_items(
+  client: &Client,
+  parameters: ListInboundShipmentItemsParameters,
+) -> Paginator<'_, InboundShipmentItem> {
+  Paginator::new(
+    client,
+    move |client| ListInboundShipmentItems(client, parameters).map(Into::into),
+    |client, next_token| ListInboundShipmentItemsByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<ListInboundShipmentItemsResponse> for Page<InboundShipmentItem> {
+  fn from(res: ListInboundShipmentItemsResponse) -> Self {
+    Page {
+      items: res.ItemData,
+      next_token: res.NextToken,
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,78 @@
+//! Async variants of the Fulfillment Inbound Shipment API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `fulfillment_inbound_shipment::ListInboundShipments`
+#[allow(non_snake_case)]
+pub async fn ListInboundShipments(
+  client: &AsyncClient,
+  parameters: ListInboundShipmentsParameters,
+) -> MwsResult<ListInboundShipmentsResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListInboundShipments",
+      parameters,
+    )
+    .await
+    .map(|e: ListInboundShipmentsEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_inbound_shipment::ListInboundShipmentsByNextToken`
+#[allow(non_snake_case)]
+pub async fn ListInboundShipmentsByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<ListInboundShipmentsResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListInboundShipmentsByNextToken",
+      params,
+    )
+    .await
+    .map(|e: ListInboundShipmentsByNextTokenEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_inbound_shipment::ListInboundShipmentItems`
+#[allow(non_snake_case)]
+pub async fn ListInboundShipmentItems(
+  client: &AsyncClient,
+  parameters: ListInboundShipmentItemsParameters,
+) -> MwsResult<ListInboundShipmentItemsResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListInboundShipmentItems",
+      parameters,
+    )
+    .await
+    .map(|e: ListInboundShipmentItemsEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_inbound_shipment::ListInboundShipmentItemsByNextToken`
+#[allow(non_snake_case)]
+pub async fn ListInboundShipmentItemsByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<ListInboundShipmentItemsResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListInboundShipmentItemsByNextToken",
+      params,
+    )
+    .await
+    .map(|e: ListInboundShipmentItemsByNextTokenEnvelope| e.into_inner())
+}
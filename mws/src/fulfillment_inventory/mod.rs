@@ -5,9 +5,12 @@
 use chrono::{DateTime, Utc};
 use crate::client::{Client, Method};
 mod types;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub use self::types::{
   Condition, InventorySupply, InventorySupplyDetail, SupplyType, Timepoint, TimepointType,
 };
+use crate::paginator::{Page, Paginator};
 use crate::result::MwsResult;
 
 static PATH: &'static str = "/FulfillmentInventory/2010-10-01";
@@ -105,6 +108,27 @@ pub fn ListInventorySupplyByNextToken(
     .map_err(|err| err.into())
 }
 
+/// Iterates over the supplies returned by `ListInventorySupply` and `ListInventorySupplyByNextToken`.
+pub fn iter_inventory_supply(
+  client: &Client,
+  parameters: ListInventorySupplyParameters,
+) -> Paginator<'_, InventorySupply> {
+  Paginator::new(
+    client,
+    move |client| ListInventorySupply(client, parameters).map(Into::into),
+    |client, next_token| ListInventorySupplyByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<ListInventorySupplyResponse> for Page<InventorySupply> {
+  fn from(res: ListInventorySupplyResponse) -> Self {
+    Page {
+      items: res.InventorySupplyList,
+      next_token: res.NextToken,
+    }
+  }
+}
+
 // #[cfg(test)]
 // mod tests {
 //   use dotenv::dotenv;
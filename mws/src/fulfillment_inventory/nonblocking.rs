@@ -0,0 +1,41 @@
+//! Async variants of the Fulfillment Inventory API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `fulfillment_inventory::ListInventorySupply`
+#[allow(non_snake_case)]
+pub async fn ListInventorySupply(
+  client: &AsyncClient,
+  parameters: ListInventorySupplyParameters,
+) -> MwsResult<ListInventorySupplyResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListInventorySupply",
+      parameters,
+    )
+    .await
+    .map(|e: ListInventorySupplyResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_inventory::ListInventorySupplyByNextToken`
+#[allow(non_snake_case)]
+pub async fn ListInventorySupplyByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<ListInventorySupplyResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListInventorySupplyByNextToken",
+      params,
+    )
+    .await
+    .map(|e: ListInventorySupplyByNextTokenResponseEnvelope| e.into_inner())
+}
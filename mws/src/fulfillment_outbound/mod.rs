@@ -5,8 +5,11 @@
 use chrono::{DateTime, Utc};
 use crate::client::{Client, Method};
 mod types;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub use self::types::*;
 use super::types::ToIso8601;
+use crate::paginator::{Page, Paginator};
 use crate::result::MwsResult;
 
 static PATH: &'static str = "/FulfillmentOutboundShipment/2010-10-01";
@@ -73,6 +76,27 @@ pub fn ListAllFulfillmentOrdersByNextToken(
     .map_err(|err| err.into())
 }
 
+/// Iterates over the fulfillment orders returned by `ListAllFulfillmentOrders` and `ListAllFulfillmentOrdersByNextToken`.
+pub fn iter_fulfillment_orders(
+  client: &Client,
+  query_start_date_time: DateTime<Utc>,
+) -> Paginator<'_, FulfillmentOrder> {
+  Paginator::new(
+    client,
+    move |client| ListAllFulfillmentOrders(client, query_start_date_time).map(Into::into),
+    |client, next_token| ListAllFulfillmentOrdersByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<ListAllFulfillmentOrdersResponse> for Page<FulfillmentOrder> {
+  fn from(res: ListAllFulfillmentOrdersResponse) -> Self {
+    Page {
+      items: res.FulfillmentOrders,
+      next_token: res.NextToken,
+    }
+  }
+}
+
 response_envelope_type!(
   GetFulfillmentOrderEnvelope<GetFulfillmentOrderResponse>,
   "GetFulfillmentOrderResponse",
@@ -0,0 +1,130 @@
+//! Async variants of the Fulfillment Outbound Shipment API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `fulfillment_outbound::ListAllFulfillmentOrders`
+#[allow(non_snake_case)]
+pub async fn ListAllFulfillmentOrders(
+  client: &AsyncClient,
+  query_start_date_time: DateTime<Utc>,
+) -> MwsResult<ListAllFulfillmentOrdersResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListAllFulfillmentOrders",
+      vec![(
+        "QueryStartDateTime".to_string(),
+        query_start_date_time.to_iso8601(),
+      )],
+    )
+    .await
+    .map(|e: ListAllFulfillmentOrdersEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_outbound::ListAllFulfillmentOrdersByNextToken`
+#[allow(non_snake_case)]
+pub async fn ListAllFulfillmentOrdersByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<ListAllFulfillmentOrdersResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListAllFulfillmentOrdersByNextToken",
+      params,
+    )
+    .await
+    .map(|e: ListAllFulfillmentOrdersByNextTokenEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_outbound::GetFulfillmentOrder`
+#[allow(non_snake_case)]
+pub async fn GetFulfillmentOrder(
+  client: &AsyncClient,
+  seller_fulfillment_order_id: String,
+) -> MwsResult<GetFulfillmentOrderResponse> {
+  let params = vec![(
+    "SellerFulfillmentOrderId".to_string(),
+    seller_fulfillment_order_id,
+  )];
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetFulfillmentOrder", params)
+    .await
+    .map(|e: GetFulfillmentOrderEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_outbound::GetPackageTrackingDetails`
+#[allow(non_snake_case)]
+pub async fn GetPackageTrackingDetails(
+  client: &AsyncClient,
+  package_number: &str,
+) -> MwsResult<GetPackageTrackingDetailsResponse> {
+  let params = vec![("PackageNumber".to_string(), package_number.to_owned())];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetPackageTrackingDetails",
+      params,
+    )
+    .await
+    .map(|e: GetPackageTrackingDetailsEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_outbound::GetFulfillmentPreview`
+#[allow(non_snake_case)]
+pub async fn GetFulfillmentPreview(
+  client: &AsyncClient,
+  params: GetFulfillmentPreviewParameters,
+) -> MwsResult<GetFulfillmentPreviewResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetFulfillmentPreview", params)
+    .await
+    .map(|e: GetFulfillmentPreviewEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_outbound::CreateFulfillmentOrder`
+#[allow(non_snake_case)]
+pub async fn CreateFulfillmentOrder(
+  client: &AsyncClient,
+  params: CreateFulfillmentOrderParameters,
+) -> MwsResult<()> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "CreateFulfillmentOrder",
+      params,
+    )
+    .await
+    .map(|e: CreateFulfillmentOrderEnvelope| e.into_inner())
+}
+
+/// Async version of `fulfillment_outbound::CancelFulfillmentOrder`
+#[allow(non_snake_case)]
+pub async fn CancelFulfillmentOrder(
+  client: &AsyncClient,
+  seller_fulfillment_order_id: &str,
+) -> MwsResult<()> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "CancelFulfillmentOrder",
+      vec![(
+        "SellerFulfillmentOrderId".to_owned(),
+        seller_fulfillment_order_id.to_owned(),
+      )],
+    )
+    .await
+    .map(|e: CancelFulfillmentOrderEnvelope| e.into_inner())
+}
@@ -6,8 +6,12 @@
 extern crate base64;
 extern crate chrono;
 extern crate crypto;
+extern crate encoding_rs;
+extern crate encoding_rs_io;
 extern crate failure;
+extern crate flate2;
 extern crate reqwest;
+extern crate rust_decimal;
 extern crate url;
 extern crate xml;
 #[macro_use]
@@ -16,11 +20,15 @@ extern crate csv;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
 #[macro_use]
 extern crate mws_derive;
 
 pub use mws_derive::FromTdffRow;
 
+// `FromTdffRow` expands to `mws::tdff` paths.
+extern crate self as mws;
+
 #[cfg(test)]
 extern crate dotenv;
 #[cfg(test)]
@@ -38,8 +46,15 @@ mod macros;
 #[macro_use]
 pub mod tdff;
 pub mod client;
+pub mod transport;
+pub mod retry;
+pub mod throttle;
+pub mod paginator;
+#[cfg(feature = "async")]
+pub mod async_client;
 pub mod constants;
-mod sign;
+pub mod sign;
+pub mod content_md5;
 
 pub mod feeds;
 pub mod fulfillment_inbound_shipment;
@@ -49,6 +64,7 @@ pub mod merchant_fulfillment;
 pub mod orders;
 pub mod products;
 pub mod reports;
+pub mod spapi;
 pub mod subscriptions;
 
 pub use self::types::{ResponseEnvelope, SerializeMwsParams, SerializeMwsParamsContext};
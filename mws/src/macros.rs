@@ -120,31 +120,93 @@ macro_rules! str_enum {
   };
 }
 
+// Variants are serialized by serde by name, like a derived `Serialize`.
+// With `#[serde_as_value]` the mapped string is serialized and deserialized instead.
 macro_rules! string_map_enum {
   (
     $(#[doc = $doc:expr])*
+    #[serde_as_value]
     pub enum $name:ident {
       $($variant:ident = $value:expr),+
       $(,)*
     }
+  ) => (
+    string_map_enum! {
+      @enum []
+      $(#[doc = $doc])*
+      pub enum $name {
+        $($variant = $value),+
+      }
+    }
+
+    impl ::serde::Serialize for $name {
+      fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_ref())
+      }
+    }
+
+    impl<'de> ::serde::Deserialize<'de> for $name {
+      fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        <String as ::serde::Deserialize>::deserialize(deserializer).map($name::from)
+      }
+    }
+  );
+
+  (
+    $(#[doc = $doc:expr])*
+    pub enum $name:ident {
+      $($variant:ident = $value:expr),+
+      $(,)*
+    }
+  ) => (
+    string_map_enum! {
+      @enum [Serialize]
+      $(#[doc = $doc])*
+      pub enum $name {
+        $($variant = $value),+
+      }
+    }
+  );
+
+  (
+    @enum [$($derive:ident),*]
+    $(#[doc = $doc:expr])*
+    pub enum $name:ident {
+      $($variant:ident = $value:expr),+
+    }
   ) => (
     $(#[doc = $doc])*
     #[allow(non_camel_case_types)]
-    #[derive(Clone, Debug, Serialize)]
+    #[derive(Clone, Debug $(, $derive)*)]
     pub enum $name {
       $($variant,)*
       UnknownValue(String)
     }
 
-    impl ::std::str::FromStr for $name {
-      type Err = ::std::io::Error;
-      fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
-        Ok(match s {
+    impl<'a> From<&'a str> for $name {
+      fn from(v: &'a str) -> Self {
+        match v {
           $(
             $value => $name::$variant,
           )*
-          _ => $name::UnknownValue(s.to_owned()),
-        })
+          _ => $name::UnknownValue(v.to_owned()),
+        }
+      }
+    }
+
+    impl From<String> for $name {
+      fn from(v: String) -> Self {
+        match $name::from(v.as_ref()) {
+          $name::UnknownValue(_) => $name::UnknownValue(v),
+          known => known,
+        }
+      }
+    }
+
+    impl ::std::str::FromStr for $name {
+      type Err = ::std::io::Error;
+      fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        Ok($name::from(s))
       }
     }
 
@@ -273,3 +335,25 @@ macro_rules! response_envelope_batch_type {
     }
   };
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::feeds::FeedType;
+  use crate::fulfillment_inbound_shipment::BoxContentsSource;
+
+  #[test]
+  fn test_string_map_enum_serialize() {
+    assert_eq!(
+      serde_json::to_string(&FeedType::Product).unwrap(),
+      r#""Product""#
+    );
+    assert_eq!(
+      serde_json::to_string(&FeedType::UnknownValue("_POST_NEW_DATA_".to_owned())).unwrap(),
+      r#"{"UnknownValue":"_POST_NEW_DATA_"}"#
+    );
+    assert_eq!(
+      serde_json::to_string(&BoxContentsSource::_2D_BARCODE).unwrap(),
+      r#""_2D_BARCODE""#
+    );
+  }
+}
@@ -7,6 +7,8 @@ use crate::client::{Client, Method};
 use crate::result::MwsResult;
 
 mod types;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 
 static PATH: &'static str = "/MerchantFulfillment/2015-06-01";
 static VERSION: &'static str = "2015-06-01";
@@ -0,0 +1,64 @@
+//! Async variants of the Merchant Fulfillment API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `merchant_fulfillment::GetEligibleShippingServices`
+#[allow(non_snake_case)]
+pub async fn GetEligibleShippingServices(
+  client: &AsyncClient,
+  params: GetEligibleShippingServicesParameters,
+) -> MwsResult<GetEligibleShippingServicesResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetEligibleShippingServices",
+      params,
+    )
+    .await
+    .map(|e: GetEligibleShippingServicesEnvelope| e.into_inner())
+}
+
+/// Async version of `merchant_fulfillment::CreateShipment`
+#[allow(non_snake_case)]
+pub async fn CreateShipment(
+  client: &AsyncClient,
+  params: CreateShipmentParameters,
+) -> MwsResult<CreateShipmentResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "CreateShipment", params)
+    .await
+    .map(|e: CreateShipmentEnvelope| e.into_inner())
+}
+
+/// Async version of `merchant_fulfillment::GetShipment`
+#[allow(non_snake_case)]
+pub async fn GetShipment(client: &AsyncClient, id: &str) -> MwsResult<GetShipmentResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetShipment",
+      vec![("ShipmentId".to_string(), id.to_string())],
+    )
+    .await
+    .map(|e: GetShipmentEnvelope| e.into_inner())
+}
+
+/// Async version of `merchant_fulfillment::CancelShipment`
+#[allow(non_snake_case)]
+pub async fn CancelShipment(client: &AsyncClient, id: &str) -> MwsResult<CancelShipmentResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "CancelShipment",
+      vec![("ShipmentId".to_string(), id.to_string())],
+    )
+    .await
+    .map(|e: CancelShipmentEnvelope| e.into_inner())
+}
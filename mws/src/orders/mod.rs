@@ -5,7 +5,10 @@
 use chrono::{DateTime, Utc};
 use crate::client::{Client, Method};
 mod types;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub use self::types::*;
+use crate::paginator::{Page, Paginator};
 use crate::result::MwsResult;
 
 static PATH: &'static str = "/Orders/2013-09-01";
@@ -91,6 +94,24 @@ pub fn ListOrdersByNextToken(client: &Client, next_token: String) -> MwsResult<L
     .map_err(|err| err.into())
 }
 
+/// Iterates over the orders returned by `ListOrders` and `ListOrdersByNextToken`.
+pub fn iter_orders(client: &Client, parameters: ListOrdersParameters) -> Paginator<'_, Order> {
+  Paginator::new(
+    client,
+    move |client| ListOrders(client, parameters).map(Into::into),
+    |client, next_token| ListOrdersByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<ListOrdersResponse> for Page<Order> {
+  fn from(res: ListOrdersResponse) -> Self {
+    Page {
+      items: res.Orders,
+      next_token: res.NextToken,
+    }
+  }
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug, Default, Serialize, FromXmlStream)]
 pub struct ListOrderItemsResponse {
@@ -142,6 +163,24 @@ pub fn ListOrderItemsByNextToken(
     .map_err(|err| err.into())
 }
 
+/// Iterates over the items of an order returned by `ListOrderItems` and `ListOrderItemsByNextToken`.
+pub fn iter_order_items(client: &Client, amazon_order_id: String) -> Paginator<'_, OrderItem> {
+  Paginator::new(
+    client,
+    move |client| ListOrderItems(client, amazon_order_id).map(Into::into),
+    |client, next_token| ListOrderItemsByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<ListOrderItemsResponse> for Page<OrderItem> {
+  fn from(res: ListOrderItemsResponse) -> Self {
+    Page {
+      items: res.OrderItems,
+      next_token: res.NextToken,
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::super::client::get_test_client;
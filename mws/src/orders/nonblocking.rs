@@ -0,0 +1,61 @@
+//! Async variants of the Orders API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `orders::ListOrders`
+#[allow(non_snake_case)]
+pub async fn ListOrders(
+  client: &AsyncClient,
+  parameters: ListOrdersParameters,
+) -> MwsResult<ListOrdersResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "ListOrders", parameters)
+    .await
+    .map(|e: ListOrdersEnvelope| e.into_inner())
+}
+
+/// Async version of `orders::ListOrdersByNextToken`
+#[allow(non_snake_case)]
+pub async fn ListOrdersByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<ListOrdersResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(Method::POST, PATH, VERSION, "ListOrdersByNextToken", params)
+    .await
+    .map(|e: ListOrdersByNextTokenEnvelope| e.into_inner())
+}
+
+/// Async version of `orders::ListOrderItems`
+#[allow(non_snake_case)]
+pub async fn ListOrderItems(
+  client: &AsyncClient,
+  amazon_order_id: String,
+) -> MwsResult<ListOrderItemsResponse> {
+  let params = vec![("AmazonOrderId".to_string(), amazon_order_id)];
+  client
+    .request_xml(Method::POST, PATH, VERSION, "ListOrderItems", params)
+    .await
+    .map(|e: ListOrderItemsEnvelope| e.into_inner())
+}
+
+/// Async version of `orders::ListOrderItemsByNextToken`
+#[allow(non_snake_case)]
+pub async fn ListOrderItemsByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<ListOrderItemsResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListOrderItemsByNextToken",
+      params,
+    )
+    .await
+    .map(|e: ListOrderItemsByNextTokenEnvelope| e.into_inner())
+}
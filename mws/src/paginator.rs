@@ -0,0 +1,130 @@
+//! Iterators following the `NextToken` of list operations
+//!
+//! Every section with a paginated operation has helper functions returning a `Paginator`,
+//! e.g. `orders::iter_orders`. Requests go through the `Client`, so they are retried and
+//! throttled according to `ClientOptions`.
+//!
+//! The Selling Partner API helpers in `spapi` use the same iterator with an `SpClient`.
+
+use crate::client::Client;
+use crate::result::MwsResult;
+use std::vec;
+
+/// Records of one page and the token of the next page
+#[derive(Debug)]
+pub struct Page<T> {
+  pub items: Vec<T>,
+  /// `None` if this is the last page.
+  pub next_token: Option<String>,
+}
+
+type FirstPageFn<'a, T, C> = Box<dyn FnOnce(&C) -> MwsResult<Page<T>> + 'a>;
+type NextPageFn<'a, T, C> = Box<dyn Fn(&C, String) -> MwsResult<Page<T>> + 'a>;
+
+/// Lazily requests pages and yields their records
+///
+/// The iteration stops after the first error. To continue later, e.g. after a crash,
+/// save `page_token` after processing each record and pass it to `resume_from`.
+/// The records of that page which were already yielded are yielded again.
+pub struct Paginator<'a, T, C = Client> {
+  client: &'a C,
+  first: Option<FirstPageFn<'a, T, C>>,
+  next: NextPageFn<'a, T, C>,
+  items: vec::IntoIter<T>,
+  page_token: Option<String>,
+  next_token: Option<String>,
+  done: bool,
+}
+
+impl<'a, T, C> Paginator<'a, T, C> {
+  /// `first` requests the first page, `next` requests the page of a `NextToken`.
+  pub fn new<F, N>(client: &'a C, first: F, next: N) -> Self
+  where
+    F: FnOnce(&C) -> MwsResult<Page<T>> + 'a,
+    N: Fn(&C, String) -> MwsResult<Page<T>> + 'a,
+  {
+    Paginator {
+      client,
+      first: Some(Box::new(first)),
+      next: Box::new(next),
+      items: vec![].into_iter(),
+      page_token: None,
+      next_token: None,
+      done: false,
+    }
+  }
+
+  /// Skips the first request and continues with the page of `next_token`.
+  pub fn resume_from(mut self, next_token: String) -> Self {
+    self.first = None;
+    self.page_token = Some(next_token.clone());
+    self.next_token = Some(next_token);
+    self.done = false;
+    self
+  }
+
+  /// The token of the page the records currently yielded belong to,
+  /// `None` for the first page. This is the token to save to resume after a crash.
+  ///
+  /// If the last request failed, this is the token of the failed page.
+  pub fn page_token(&self) -> Option<&str> {
+    self.page_token.as_ref().map(AsRef::as_ref)
+  }
+
+  /// The token of the page which will be requested next.
+  ///
+  /// It is already set while the records of the current page are yielded, resuming from it
+  /// skips the rest of the current page. Use `page_token` unless pages are processed
+  /// as a whole with `next_page`.
+  pub fn next_token(&self) -> Option<&str> {
+    self.next_token.as_ref().map(AsRef::as_ref)
+  }
+
+  /// Requests the next page. Records of the current page which have not been yielded are dropped.
+  pub fn next_page(&mut self) -> Option<MwsResult<Page<T>>> {
+    self.items = vec![].into_iter();
+    if self.done {
+      return None;
+    }
+
+    let res = match (self.first.take(), self.next_token.clone()) {
+      (Some(first), _) => first(self.client),
+      (None, Some(next_token)) => {
+        self.page_token = Some(next_token.clone());
+        (self.next)(self.client, next_token)
+      }
+      (None, None) => {
+        self.done = true;
+        return None;
+      }
+    };
+
+    match res {
+      Ok(page) => {
+        self.next_token = page.next_token.clone();
+        self.done = self.next_token.is_none();
+        Some(Ok(page))
+      }
+      Err(err) => {
+        self.done = true;
+        Some(Err(err))
+      }
+    }
+  }
+}
+
+impl<'a, T, C> Iterator for Paginator<'a, T, C> {
+  type Item = MwsResult<T>;
+
+  fn next(&mut self) -> Option<MwsResult<T>> {
+    loop {
+      if let Some(item) = self.items.next() {
+        return Some(Ok(item));
+      }
+      match self.next_page()? {
+        Ok(page) => self.items = page.items.into_iter(),
+        Err(err) => return Some(Err(err)),
+      }
+    }
+  }
+}
@@ -7,6 +7,8 @@ use crate::result::MwsResult;
 
 pub mod types;
 pub use self::types::*;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 use crate::products::types::product::Product;
 
 static PATH: &'static str = "/Products/2011-10-01";
@@ -0,0 +1,64 @@
+//! Async variants of the Products API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `products::GetLowestPricedOffersForSKU`
+#[allow(non_snake_case)]
+pub async fn GetLowestPricedOffersForSKU(
+  client: &AsyncClient,
+  params: GetLowestPricedOffersForSKUParameters,
+) -> MwsResult<GetLowestPricedOffersForSKUResponse> {
+  client
+    .request_xml_with_form(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetLowestPricedOffersForSKU",
+      params,
+    )
+    .await
+    .map(|e: GetLowestPricedOffersForSKUResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `products::GetMyPriceForASIN`
+#[allow(non_snake_case)]
+pub async fn GetMyPriceForASIN(
+  client: &AsyncClient,
+  params: GetMyPriceForASINParameters,
+) -> MwsResult<Vec<GetMyPriceForASINResult>> {
+  client
+    .request_xml_with_form(Method::POST, PATH, VERSION, "GetMyPriceForASIN", params)
+    .await
+    .map(|e: GetMyPriceForASINResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `products::GetMyPriceForSKU`
+#[allow(non_snake_case)]
+pub async fn GetMyPriceForSKU(
+  client: &AsyncClient,
+  params: GetMyPriceForSKUParameters,
+) -> MwsResult<Vec<GetMyPriceForSKUResult>> {
+  client
+    .request_xml_with_form(Method::POST, PATH, VERSION, "GetMyPriceForSKU", params)
+    .await
+    .map(|e: GetMyPriceForSKUResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `products::GetMatchingProductForId`
+#[allow(non_snake_case)]
+pub async fn GetMatchingProductForId(
+  client: &AsyncClient,
+  params: GetMatchingProductForIdParameters,
+) -> MwsResult<Vec<GetMatchingProductForIdResult>> {
+  client
+    .request_xml_with_form(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetMatchingProductForId",
+      params,
+    )
+    .await
+    .map(|e: GetMatchingProductForIdResponseEnvelope| e.into_inner())
+}
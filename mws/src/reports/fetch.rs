@@ -0,0 +1,336 @@
+//! Report lifecycle: request, wait, download, acknowledge
+//!
+//! `fetch_report` runs `RequestReport`, polls `GetReportRequestList` until the request is
+//! processed, downloads the generated report with `GetReport` and marks it as acknowledged.
+//! `fetch_report_rows_with` parses a flat file report while it is downloaded.
+//!
+//! Throttled requests are sent again after `FetchReportOptions::throttle_delay`. A `Throttler`
+//! configured on the client additionally keeps the requests within the documented quotas.
+
+use super::{
+  request_report, GetReport, GetReportList, GetReportListParameters, GetReportRequestList,
+  GetReportRequestListParameters, ReportProcessingStatus, ReportRequestInfo, RequestReport,
+  RequestReportParameters, UpdateReportAcknowledgements, UpdateReportAcknowledgementsParameters,
+};
+use crate::client::Client;
+use crate::content_md5::Md5Reader;
+use crate::result::{MwsError, MwsResult};
+use crate::tdff::{decoding_reader, Encoding, FromTdffRow, TdffParser};
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How `fetch_report` waits for a report
+#[derive(Debug, Clone)]
+pub struct FetchReportOptions {
+  /// How long to wait between `GetReportRequestList` requests, 45 seconds by default.
+  /// The request is polled right after `RequestReport`, and a last time at the deadline.
+  pub poll_interval: Duration,
+  /// How long to wait for the report before giving up with `MwsError::Timeout`, unlimited by default.
+  pub timeout: Option<Duration>,
+  /// How long to wait before sending a throttled request again, 60 seconds by default.
+  pub throttle_delay: Duration,
+  /// Mark the report as acknowledged once it is downloaded, enabled by default.
+  pub acknowledge: bool,
+  /// The encoding `fetch_report_rows_with` decodes the report from. By default it is the encoding
+  /// of the report type in the first marketplace of `MarketplaceIdList`, or UTF-8 without a list.
+  pub encoding: Option<&'static Encoding>,
+}
+
+impl Default for FetchReportOptions {
+  fn default() -> Self {
+    FetchReportOptions {
+      poll_interval: Duration::from_secs(45),
+      timeout: None,
+      throttle_delay: Duration::from_secs(60),
+      acknowledge: true,
+      encoding: None,
+    }
+  }
+}
+
+/// How a report request ended
+#[derive(Debug)]
+pub enum FetchReportOutcome<T> {
+  /// The report was generated and downloaded.
+  Done {
+    request: ReportRequestInfo,
+    report_id: String,
+    output: T,
+  },
+  /// The request was processed, but there was no data to report.
+  DoneNoData(ReportRequestInfo),
+  /// The request was cancelled.
+  Cancelled(ReportRequestInfo),
+}
+
+impl<T> FetchReportOutcome<T> {
+  pub fn request(&self) -> &ReportRequestInfo {
+    match *self {
+      FetchReportOutcome::Done { ref request, .. } => request,
+      FetchReportOutcome::DoneNoData(ref request) => request,
+      FetchReportOutcome::Cancelled(ref request) => request,
+    }
+  }
+
+  /// Returns the output of a downloaded report.
+  pub fn into_output(self) -> Option<T> {
+    match self {
+      FetchReportOutcome::Done { output, .. } => Some(output),
+      _ => None,
+    }
+  }
+}
+
+/// Requests a report, waits until it is generated and writes it to `out`.
+///
+/// Returns the number of bytes written for a downloaded report.
+pub fn fetch_report<W: Write>(
+  client: &Client,
+  params: RequestReportParameters,
+  options: &FetchReportOptions,
+  out: &mut W,
+) -> MwsResult<FetchReportOutcome<u64>> {
+  let fetcher = Fetcher::new(options);
+  let (request, report_id) = match fetcher.wait(client, params)? {
+    FetchReportOutcome::Done {
+      request, report_id, ..
+    } => (request, report_id),
+    FetchReportOutcome::DoneNoData(request) => return Ok(FetchReportOutcome::DoneNoData(request)),
+    FetchReportOutcome::Cancelled(request) => return Ok(FetchReportOutcome::Cancelled(request)),
+  };
+  let (size, _) = fetcher.retry_throttled(|| GetReport(client, report_id.clone(), out))?;
+  fetcher.acknowledge(client, &report_id)?;
+  Ok(FetchReportOutcome::Done {
+    request,
+    report_id,
+    output: size,
+  })
+}
+
+/// Requests a tab-delimited flat file report, waits until it is generated and parses its rows.
+///
+/// The rows are collected in a `Vec`, so the whole report is held in memory. Use
+/// `fetch_report_rows_with` to process a large report one row at a time.
+pub fn fetch_report_rows<T: FromTdffRow>(
+  client: &Client,
+  params: RequestReportParameters,
+  options: &FetchReportOptions,
+) -> MwsResult<FetchReportOutcome<Vec<T>>> {
+  let mut rows = vec![];
+  let outcome = fetch_report_rows_with(client, params, options, |row| {
+    rows.push(row);
+    Ok(())
+  })?;
+  Ok(match outcome {
+    FetchReportOutcome::Done {
+      request, report_id, ..
+    } => FetchReportOutcome::Done {
+      request,
+      report_id,
+      output: rows,
+    },
+    FetchReportOutcome::DoneNoData(request) => FetchReportOutcome::DoneNoData(request),
+    FetchReportOutcome::Cancelled(request) => FetchReportOutcome::Cancelled(request),
+  })
+}
+
+/// Requests a tab-delimited flat file report, waits until it is generated and passes its rows to
+/// `f` while the report is downloaded.
+///
+/// The report is decoded from `FetchReportOptions::encoding` and parsed one row at a time, it is
+/// never held in memory. An error returned by `f` or a row which cannot be parsed stops the
+/// download. The Content-MD5 header is verified once the report is read to the end: on
+/// `MwsError::ContentMD5Mismatch`, the rows passed to `f` came from an incomplete or corrupted
+/// report. The report is only acknowledged after it is verified.
+///
+/// Returns the number of rows of a downloaded report.
+pub fn fetch_report_rows_with<T, F>(
+  client: &Client,
+  params: RequestReportParameters,
+  options: &FetchReportOptions,
+  mut f: F,
+) -> MwsResult<FetchReportOutcome<u64>>
+where
+  T: FromTdffRow,
+  F: FnMut(T) -> MwsResult<()>,
+{
+  let encoding = options.encoding.or_else(|| {
+    params
+      .MarketplaceIdList
+      .as_ref()
+      .and_then(|ids| ids.first())
+      .map(|id| params.ReportType.encoding(id))
+  });
+  let fetcher = Fetcher::new(options);
+  let (request, report_id) = match fetcher.wait(client, params)? {
+    FetchReportOutcome::Done {
+      request, report_id, ..
+    } => (request, report_id),
+    FetchReportOutcome::DoneNoData(request) => return Ok(FetchReportOutcome::DoneNoData(request)),
+    FetchReportOutcome::Cancelled(request) => return Ok(FetchReportOutcome::Cancelled(request)),
+  };
+  let (resp, content_md5) =
+    fetcher.retry_throttled(|| request_report(client, report_id.clone()))?;
+  let mut body = Md5Reader::new(resp);
+  let mut count = 0;
+  for row in TdffParser::new(decoding_reader(&mut body, encoding))?.rows() {
+    f(row?)?;
+    count += 1;
+  }
+  if !client.options().skip_content_md5_check {
+    body.verify(&content_md5)?;
+  }
+  fetcher.acknowledge(client, &report_id)?;
+  Ok(FetchReportOutcome::Done {
+    request,
+    report_id,
+    output: count,
+  })
+}
+
+struct Fetcher<'a> {
+  options: &'a FetchReportOptions,
+  deadline: Option<Instant>,
+}
+
+impl<'a> Fetcher<'a> {
+  fn new(options: &'a FetchReportOptions) -> Self {
+    Fetcher {
+      options,
+      deadline: options.timeout.map(|timeout| Instant::now() + timeout),
+    }
+  }
+
+  /// Requests the report and waits until the request is processed.
+  fn wait(
+    &self,
+    client: &Client,
+    params: RequestReportParameters,
+  ) -> MwsResult<FetchReportOutcome<()>> {
+    let request_id = self
+      .retry_throttled(|| RequestReport(client, params.clone()))?
+      .ReportRequestInfo
+      .ReportRequestId;
+
+    let request = loop {
+      let mut res = self.retry_throttled(|| {
+        GetReportRequestList(
+          client,
+          GetReportRequestListParameters {
+            ReportRequestIdList: Some(vec![request_id.clone()]),
+            ..Default::default()
+          },
+        )
+      })?;
+      let index = res
+        .ReportRequestInfo
+        .iter()
+        .position(|info| info.ReportRequestId == request_id);
+      if let Some(index) = index {
+        let request = res.ReportRequestInfo.swap_remove(index);
+        match request.ReportProcessingStatus {
+          ReportProcessingStatus::_DONE_ => break request,
+          ReportProcessingStatus::_DONE_NO_DATA_ => {
+            return Ok(FetchReportOutcome::DoneNoData(request))
+          }
+          ReportProcessingStatus::_CANCELLED_ => return Ok(FetchReportOutcome::Cancelled(request)),
+          _ => {}
+        }
+      }
+      self.sleep_until_next_poll(&request_id)?;
+    };
+
+    let report_id = match request.GeneratedReportId {
+      Some(ref report_id) => report_id.clone(),
+      None => self.find_report_id(client, &request_id)?,
+    };
+    Ok(FetchReportOutcome::Done {
+      request,
+      report_id,
+      output: (),
+    })
+  }
+
+  /// Looks up the report generated for a request which did not return `GeneratedReportId`.
+  fn find_report_id(&self, client: &Client, request_id: &str) -> MwsResult<String> {
+    let res = self.retry_throttled(|| {
+      GetReportList(
+        client,
+        GetReportListParameters {
+          ReportRequestIdList: Some(vec![request_id.to_owned()]),
+          ..Default::default()
+        },
+      )
+    })?;
+    res
+      .ReportInfo
+      .into_iter()
+      .find(|info| info.ReportRequestId == request_id)
+      .map(|info| info.ReportId)
+      .ok_or_else(|| {
+        MwsError::Msg(format!(
+          "no report found for report request '{}'",
+          request_id
+        ))
+      })
+  }
+
+  fn acknowledge(&self, client: &Client, report_id: &str) -> MwsResult<()> {
+    if !self.options.acknowledge {
+      return Ok(());
+    }
+    self
+      .retry_throttled(|| {
+        UpdateReportAcknowledgements(
+          client,
+          UpdateReportAcknowledgementsParameters {
+            ReportIdList: vec![report_id.to_owned()],
+            Acknowledged: Some(true),
+          },
+        )
+      })
+      .map(|_| ())
+  }
+
+  fn retry_throttled<T, F>(&self, mut f: F) -> MwsResult<T>
+  where
+    F: FnMut() -> MwsResult<T>,
+  {
+    loop {
+      match f() {
+        Err(ref err) if err.is_throttled() && !self.expires_within(self.options.throttle_delay) => {
+          thread::sleep(self.options.throttle_delay)
+        }
+        res => return res,
+      }
+    }
+  }
+
+  /// Waits `poll_interval`, or until the deadline if it is closer so that the request is
+  /// polled one last time. Fails with `MwsError::Timeout` once the deadline has passed.
+  fn sleep_until_next_poll(&self, request_id: &str) -> MwsResult<()> {
+    let delay = match self.deadline {
+      Some(deadline) => {
+        let now = Instant::now();
+        if now >= deadline {
+          return Err(MwsError::Timeout(format!(
+            "report request '{}'",
+            request_id
+          )));
+        }
+        self.options.poll_interval.min(deadline - now)
+      }
+      None => self.options.poll_interval,
+    };
+    thread::sleep(delay);
+    Ok(())
+  }
+
+  fn expires_within(&self, delay: Duration) -> bool {
+    self
+      .deadline
+      .map(|deadline| Instant::now() + delay > deadline)
+      .unwrap_or(false)
+  }
+}
@@ -3,11 +3,26 @@
 //! [Documentation](http://docs.developer.amazonservices.com/en_US/reports/Reports_Overview.html)
 
 use chrono::{DateTime, Utc};
-use crate::client::{Client, Method};
+use crate::client::{Client, Method, Response};
+use crate::content_md5;
+mod report_type;
 mod types;
-pub use self::types::{ReportInfo, ReportProcessingStatus, ReportRequestInfo, ReportSchedule};
+pub mod fetch;
+pub mod orders;
+pub mod rows;
+pub mod settlement;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub use self::fetch::{
+  fetch_report, fetch_report_rows, fetch_report_rows_with, FetchReportOptions, FetchReportOutcome,
+};
+pub use self::report_type::{ReportFormat, ReportType};
+pub use self::types::{
+  ReportInfo, ReportProcessingStatus, ReportRequestInfo, ReportSchedule, Schedule,
+};
+use crate::paginator::{Page, Paginator};
 use crate::result::{MwsError, MwsResult};
-use std::io::{self, Write};
+use std::io::Write;
 
 static PATH: &'static str = "/";
 static VERSION: &'static str = "2009-01-01";
@@ -18,7 +33,7 @@ static VERSION: &'static str = "2009-01-01";
 pub struct GetReportListParameters {
   pub MaxCount: Option<i32>,
   #[mws_param(list_item_type_name = "Type")]
-  pub ReportTypeList: Option<Vec<String>>,
+  pub ReportTypeList: Option<Vec<ReportType>>,
   pub Acknowledged: Option<bool>,
   pub AvailableFromDate: Option<DateTime<Utc>>,
   pub AvailableToDate: Option<DateTime<Utc>>,
@@ -79,32 +94,99 @@ pub fn GetReportListByNextToken(
     .map_err(|err| err.into())
 }
 
+/// Iterates over the reports returned by `GetReportList` and `GetReportListByNextToken`.
+pub fn iter_reports(client: &Client, params: GetReportListParameters) -> Paginator<'_, ReportInfo> {
+  Paginator::new(
+    client,
+    move |client| GetReportList(client, params).map(Into::into),
+    |client, next_token| GetReportListByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<GetReportListResponse> for Page<ReportInfo> {
+  fn from(res: GetReportListResponse) -> Self {
+    Page {
+      items: res.ReportInfo,
+      next_token: if res.HasNext { res.NextToken } else { None },
+    }
+  }
+}
+
+/// Parameters for `GetReportCount`
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Serialize, SerializeMwsParams)]
+pub struct GetReportCountParameters {
+  #[mws_param(list_item_type_name = "Type")]
+  pub ReportTypeList: Option<Vec<ReportType>>,
+  pub Acknowledged: Option<bool>,
+  pub AvailableFromDate: Option<DateTime<Utc>>,
+  pub AvailableToDate: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, FromXmlStream)]
+#[allow(non_snake_case)]
+pub struct GetReportCountResponse {
+  pub Count: i32,
+}
+
+response_envelope_type!(
+  GetReportCountEnvelope<GetReportCountResponse>,
+  "GetReportCountResponse",
+  "GetReportCountResult"
+);
+
+/// Returns a count of the reports, created in the previous 90 days, with a status of _DONE_
+/// and that are available for download.
+#[allow(non_snake_case)]
+pub fn GetReportCount(
+  client: &Client,
+  params: GetReportCountParameters,
+) -> MwsResult<GetReportCountResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportCount", params)
+    .map(|e: GetReportCountEnvelope| e.into_inner())
+    .map_err(|err| err.into())
+}
+
 /// Returns the contents of a report and the Content-MD5 header for the returned report body.
+///
+/// The contents are verified against the Content-MD5 header while they are written to `out`,
+/// unless `ClientOptions::skip_content_md5_check` is set. On `MwsError::ContentMD5Mismatch`,
+/// `out` holds a corrupted report, reset it before downloading the report again,
+/// e.g. with `RetryPolicy::run`.
 #[allow(non_snake_case)]
 pub fn GetReport<W: Write>(
   client: &Client,
   report_id: String,
   out: &mut W,
 ) -> MwsResult<(u64, String)> {
-  let params = vec![("ReportId".to_string(), report_id)];
-  let mut resp = client.request(Method::POST, PATH, VERSION, "GetReport", params)?;
-  let content_md5 = resp
-    .headers()
-    .get("Content-MD5")
-    .ok_or_else(|| MwsError::ContentMD5HeaderMissing)
-    .and_then(|data| ::std::str::from_utf8(&data.as_bytes()).map_err(Into::into))?
-    .to_owned();
-  let size = io::copy(&mut resp, out)?;
+  let (mut resp, content_md5) = request_report(client, report_id)?;
+  let verify = if client.options().skip_content_md5_check {
+    None
+  } else {
+    Some(content_md5.as_ref())
+  };
+  let size = content_md5::copy(&mut resp, out, verify)?;
   Ok((size, content_md5))
 }
 
+/// Sends `GetReport` and returns the response, with the body not read yet, and its Content-MD5
+/// header.
+pub(crate) fn request_report(client: &Client, report_id: String) -> MwsResult<(Response, String)> {
+  let params = vec![("ReportId".to_string(), report_id)];
+  let resp = client.request(Method::POST, PATH, VERSION, "GetReport", params)?;
+  let content_md5 =
+    content_md5::header(resp.headers())?.ok_or_else(|| MwsError::ContentMD5HeaderMissing)?;
+  Ok((resp, content_md5))
+}
+
 /// Parameters for `GetReportRequestList`
 #[allow(non_snake_case)]
 #[derive(Debug, Default, Serialize, SerializeMwsParams)]
 pub struct GetReportRequestListParameters {
   pub MaxCount: Option<i32>,
   #[mws_param(list_item_type_name = "Type")]
-  pub ReportTypeList: Option<Vec<String>>,
+  pub ReportTypeList: Option<Vec<ReportType>>,
   pub RequestedFromDate: Option<DateTime<Utc>>,
   pub RequestedToDate: Option<DateTime<Utc>>,
   #[mws_param(list_item_type_name = "Id")]
@@ -166,11 +248,111 @@ pub fn GetReportRequestListByNextToken(
     .map_err(|err| err.into())
 }
 
-/// Parameters for `RequestReport`
+/// Iterates over the report requests returned by `GetReportRequestList` and `GetReportRequestListByNextToken`.
+pub fn iter_report_requests(
+  client: &Client,
+  params: GetReportRequestListParameters,
+) -> Paginator<'_, ReportRequestInfo> {
+  Paginator::new(
+    client,
+    move |client| GetReportRequestList(client, params).map(Into::into),
+    |client, next_token| GetReportRequestListByNextToken(client, next_token).map(Into::into),
+  )
+}
+
+impl From<GetReportRequestListResponse> for Page<ReportRequestInfo> {
+  fn from(res: GetReportRequestListResponse) -> Self {
+    Page {
+      items: res.ReportRequestInfo,
+      next_token: if res.HasNext { res.NextToken } else { None },
+    }
+  }
+}
+
+/// Parameters for `GetReportRequestCount`
 #[allow(non_snake_case)]
 #[derive(Debug, Default, Serialize, SerializeMwsParams)]
+pub struct GetReportRequestCountParameters {
+  #[mws_param(list_item_type_name = "Type")]
+  pub ReportTypeList: Option<Vec<ReportType>>,
+  #[mws_param(list_item_type_name = "Status")]
+  pub ReportProcessingStatusList: Option<Vec<ReportProcessingStatus>>,
+  pub RequestedFromDate: Option<DateTime<Utc>>,
+  pub RequestedToDate: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, FromXmlStream)]
+#[allow(non_snake_case)]
+pub struct GetReportRequestCountResponse {
+  pub Count: i32,
+}
+
+response_envelope_type!(
+  GetReportRequestCountEnvelope<GetReportRequestCountResponse>,
+  "GetReportRequestCountResponse",
+  "GetReportRequestCountResult"
+);
+
+/// Returns a count of report requests that have been submitted to Amazon MWS for processing.
+#[allow(non_snake_case)]
+pub fn GetReportRequestCount(
+  client: &Client,
+  params: GetReportRequestCountParameters,
+) -> MwsResult<GetReportRequestCountResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportRequestCount", params)
+    .map(|e: GetReportRequestCountEnvelope| e.into_inner())
+    .map_err(|err| err.into())
+}
+
+/// Parameters for `CancelReportRequests`
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Serialize, SerializeMwsParams)]
+pub struct CancelReportRequestsParameters {
+  #[mws_param(list_item_type_name = "Id")]
+  pub ReportRequestIdList: Option<Vec<String>>,
+  #[mws_param(list_item_type_name = "Type")]
+  pub ReportTypeList: Option<Vec<ReportType>>,
+  #[mws_param(list_item_type_name = "Status")]
+  pub ReportProcessingStatusList: Option<Vec<ReportProcessingStatus>>,
+  pub RequestedFromDate: Option<DateTime<Utc>>,
+  pub RequestedToDate: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, FromXmlStream)]
+#[allow(non_snake_case)]
+pub struct CancelReportRequestsResponse {
+  pub Count: i32,
+  #[from_xml_stream(no_list_wrapper)]
+  pub ReportRequestInfo: Vec<ReportRequestInfo>,
+}
+
+response_envelope_type!(
+  CancelReportRequestsEnvelope<CancelReportRequestsResponse>,
+  "CancelReportRequestsResponse",
+  "CancelReportRequestsResult"
+);
+
+/// Cancels one or more report requests.
+///
+/// Without parameters, all `_SUBMITTED_` report requests of the last 90 days are cancelled.
+#[allow(non_snake_case)]
+pub fn CancelReportRequests(
+  client: &Client,
+  params: CancelReportRequestsParameters,
+) -> MwsResult<CancelReportRequestsResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "CancelReportRequests", params)
+    .map(|e: CancelReportRequestsEnvelope| e.into_inner())
+    .map_err(|err| err.into())
+}
+
+/// Parameters for `RequestReport`
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Clone, Serialize, SerializeMwsParams)]
 pub struct RequestReportParameters {
-  pub ReportType: String,
+  /// A `ReportType`, or any report type string with `"_GET_NEW_REPORT_TYPE_".into()`.
+  pub ReportType: ReportType,
   pub StartDate: Option<DateTime<Utc>>,
   pub EndDate: Option<DateTime<Utc>>,
   pub ReportOptions: Option<String>,
@@ -205,17 +387,29 @@ pub fn RequestReport(
 #[allow(non_snake_case)]
 #[derive(Debug, Default, Serialize, SerializeMwsParams)]
 pub struct ManageReportScheduleParameters {
-  #[mws_param(list_item_type_name = "Type")]
-  pub ReportType: String,
-  pub Schedule: Option<String>,
+  pub ReportType: ReportType,
+  pub Schedule: Option<Schedule>,
   pub ScheduleDate: Option<DateTime<Utc>>
 }
 
+impl ManageReportScheduleParameters {
+  fn validate(&self) -> MwsResult<()> {
+    if self.ReportType.is_schedulable() {
+      Ok(())
+    } else {
+      Err(MwsError::Msg(format!(
+        "report type '{}' cannot be scheduled",
+        self.ReportType.as_ref()
+      )))
+    }
+  }
+}
+
 #[derive(Debug, Default, Serialize, FromXmlStream)]
 #[allow(non_snake_case)]
 pub struct ManageReportScheduleResponse {
-  #[from_xml_stream(no_list_wrapper)]
   pub Count: i32,
+  #[from_xml_stream(no_list_wrapper)]
   pub ReportSchedule: Vec<ReportSchedule>
 }
 
@@ -226,11 +420,15 @@ response_envelope_type!(
 );
 
 /// Creates, updates, or deletes a report request schedule for a specified report type.
+///
+/// Fails without sending a request if the report type cannot be scheduled,
+/// see `ReportType::is_schedulable`.
 #[allow(non_snake_case)]
 pub fn ManageReportSchedule(
   client: &Client,
   params: ManageReportScheduleParameters,
 ) -> MwsResult<ManageReportScheduleResponse> {
+  params.validate()?;
   client
       .request_xml(Method::POST, PATH, VERSION, "ManageReportSchedule", params)
       .map(|e: ManageReportScheduleEnvelope| e.into_inner())
@@ -242,7 +440,7 @@ pub fn ManageReportSchedule(
 #[derive(Debug, Default, Serialize, SerializeMwsParams)]
 pub struct GetReportScheduleListParameters {
   #[mws_param(list_item_type_name = "Type")]
-  pub ReportTypeList: Option<Vec<String>>,
+  pub ReportTypeList: Option<Vec<ReportType>>,
 }
 
 #[derive(Debug, Default, Serialize, FromXmlStream)]
@@ -277,7 +475,7 @@ pub fn GetReportScheduleList(
 #[derive(Debug, Default, Serialize, SerializeMwsParams)]
 pub struct GetReportScheduleCountParameters {
   #[mws_param(list_item_type_name = "Type")]
-  pub ReportTypeList: Option<Vec<String>>,
+  pub ReportTypeList: Option<Vec<ReportType>>,
 }
 
 #[derive(Debug, Default, Serialize, FromXmlStream)]
@@ -352,7 +550,7 @@ mod tests {
     dotenv().ok();
     let c = get_test_client();
     let mut params = GetReportListParameters::default();
-    params.ReportTypeList = Some(vec!["_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_".to_owned()]);
+    params.ReportTypeList = Some(vec![ReportType::FlatFileSettlement]);
     let res = GetReportList(&c, params).expect("GetReportList");
     println!("{:?}", res);
   }
@@ -373,7 +571,7 @@ mod tests {
     dotenv().ok();
     let c = get_test_client();
     let mut params = GetReportRequestListParameters::default();
-    params.ReportTypeList = Some(vec!["_GET_AFN_INVENTORY_DATA_".to_owned()]);
+    params.ReportTypeList = Some(vec![ReportType::AfnInventory]);
     let res = GetReportRequestList(&c, params).expect("GetReportRequestList");
     println!("{:?}", res);
 
@@ -0,0 +1,193 @@
+//! Async variants of the Reports API operations
+
+use super::*;
+use crate::client::AsyncClient;
+use crate::content_md5::Md5Writer;
+
+/// Async version of `reports::GetReportList`
+#[allow(non_snake_case)]
+pub async fn GetReportList(
+  client: &AsyncClient,
+  params: GetReportListParameters,
+) -> MwsResult<GetReportListResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportList", params)
+    .await
+    .map(|e: GetReportListEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReportListByNextToken`
+#[allow(non_snake_case)]
+pub async fn GetReportListByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<GetReportListResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetReportListByNextToken",
+      params,
+    )
+    .await
+    .map(|e: GetReportListByNextTokenEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReportCount`
+#[allow(non_snake_case)]
+pub async fn GetReportCount(
+  client: &AsyncClient,
+  params: GetReportCountParameters,
+) -> MwsResult<GetReportCountResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportCount", params)
+    .await
+    .map(|e: GetReportCountEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReport`, the body is written to `out` chunk by chunk.
+#[allow(non_snake_case)]
+pub async fn GetReport<W: Write>(
+  client: &AsyncClient,
+  report_id: String,
+  out: &mut W,
+) -> MwsResult<(u64, String)> {
+  let params = vec![("ReportId".to_string(), report_id)];
+  let mut resp = client
+    .request(Method::POST, PATH, VERSION, "GetReport", params)
+    .await?;
+  let content_md5 =
+    content_md5::header(resp.headers())?.ok_or_else(|| MwsError::ContentMD5HeaderMissing)?;
+  let mut writer = Md5Writer::new(out);
+  while let Some(chunk) = resp.chunk().await? {
+    writer.write_all(&chunk)?;
+  }
+  if !client.options().skip_content_md5_check {
+    writer.verify(&content_md5)?;
+  }
+  Ok((writer.size(), content_md5))
+}
+
+/// Async version of `reports::GetReportRequestList`
+#[allow(non_snake_case)]
+pub async fn GetReportRequestList(
+  client: &AsyncClient,
+  params: GetReportRequestListParameters,
+) -> MwsResult<GetReportRequestListResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportRequestList", params)
+    .await
+    .map(|e: GetReportRequestListEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReportRequestListByNextToken`
+#[allow(non_snake_case)]
+pub async fn GetReportRequestListByNextToken(
+  client: &AsyncClient,
+  next_token: String,
+) -> MwsResult<GetReportRequestListResponse> {
+  let params = vec![("NextToken".to_string(), next_token)];
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "GetReportRequestListByNextToken",
+      params,
+    )
+    .await
+    .map(|e: GetReportRequestListByNextTokenEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReportRequestCount`
+#[allow(non_snake_case)]
+pub async fn GetReportRequestCount(
+  client: &AsyncClient,
+  params: GetReportRequestCountParameters,
+) -> MwsResult<GetReportRequestCountResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportRequestCount", params)
+    .await
+    .map(|e: GetReportRequestCountEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::CancelReportRequests`
+#[allow(non_snake_case)]
+pub async fn CancelReportRequests(
+  client: &AsyncClient,
+  params: CancelReportRequestsParameters,
+) -> MwsResult<CancelReportRequestsResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "CancelReportRequests", params)
+    .await
+    .map(|e: CancelReportRequestsEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::RequestReport`
+#[allow(non_snake_case)]
+pub async fn RequestReport(
+  client: &AsyncClient,
+  params: RequestReportParameters,
+) -> MwsResult<RequestReportResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "RequestReport", params)
+    .await
+    .map(|e: RequestReportEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::ManageReportSchedule`
+#[allow(non_snake_case)]
+pub async fn ManageReportSchedule(
+  client: &AsyncClient,
+  params: ManageReportScheduleParameters,
+) -> MwsResult<ManageReportScheduleResponse> {
+  params.validate()?;
+  client
+    .request_xml(Method::POST, PATH, VERSION, "ManageReportSchedule", params)
+    .await
+    .map(|e: ManageReportScheduleEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReportScheduleList`
+#[allow(non_snake_case)]
+pub async fn GetReportScheduleList(
+  client: &AsyncClient,
+  params: GetReportScheduleListParameters,
+) -> MwsResult<GetReportScheduleListResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportScheduleList", params)
+    .await
+    .map(|e: GetReportScheduleListEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::GetReportScheduleCount`
+#[allow(non_snake_case)]
+pub async fn GetReportScheduleCount(
+  client: &AsyncClient,
+  params: GetReportScheduleCountParameters,
+) -> MwsResult<GetReportScheduleCountResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetReportScheduleCount", params)
+    .await
+    .map(|e: GetReportScheduleCountEnvelope| e.into_inner())
+}
+
+/// Async version of `reports::UpdateReportAcknowledgements`
+#[allow(non_snake_case)]
+pub async fn UpdateReportAcknowledgements(
+  client: &AsyncClient,
+  params: UpdateReportAcknowledgementsParameters,
+) -> MwsResult<UpdateReportAcknowledgementsResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "UpdateReportAcknowledgements",
+      params,
+    )
+    .await
+    .map(|e: UpdateReportAcknowledgementsEnvelope| e.into_inner())
+}
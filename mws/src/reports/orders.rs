@@ -0,0 +1,593 @@
+//! Order report parsers
+//!
+//! The all orders reports are an alternative to `ListOrders` to backfill orders, they are not
+//! subject to its throttling. `_GET_FLAT_FILE_ALL_ORDERS_DATA_BY_LAST_UPDATE_` and
+//! `_GET_FLAT_FILE_ALL_ORDERS_DATA_BY_ORDER_DATE_` have one `AllOrdersRow` per order item,
+//! `_GET_XML_ALL_ORDERS_DATA_BY_LAST_UPDATE_` and `_GET_XML_ALL_ORDERS_DATA_BY_ORDER_DATE_` are
+//! decoded one `XmlOrder` at a time by `XmlOrderReader`.
+//!
+//! Both convert to `ReportOrder`, an `orders::Order` with its `orders::OrderItem`s. The reports do
+//! not have the order total, the order item ids (flat file only) and the buyer information, those
+//! fields are left empty. Discounts are negative amounts, as in the reports. The discounts of an
+//! item with several promotions are summed up.
+
+use crate::orders::{
+  CurrencyAmount, FulfillmentChannel, Order, OrderItem, OrderStatus, ShippingAddress,
+};
+use crate::result::{MwsError, MwsResult};
+use crate::tdff::{FromTdffRow, TdffError, TdffParser, TdffRow};
+use crate::xmlhelper::decode::{
+  element, fold_elements, skip_element, start_document, start_element, FromXmlStream, Stream,
+  XmlEventStream,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::io::Read;
+use xml::reader::XmlEvent;
+
+/// An order with its items, from an order report
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportOrder {
+  pub order: Order,
+  pub items: Vec<OrderItem>,
+}
+
+impl ReportOrder {
+  /// Parses a flat file all orders report.
+  ///
+  /// The source must be UTF-8, wrap it in `tdff::decoding_reader` to decode other encodings.
+  /// Invalid dates are `MwsError::Tdff` errors with the position of the row.
+  pub fn parse_flat_file<R: Read>(source: R) -> MwsResult<Vec<ReportOrder>> {
+    let rows = TdffParser::new(source)?.parse_all::<CheckedAllOrdersRow>()?;
+    ReportOrder::from_rows(rows.into_iter().map(|row| row.0))
+  }
+
+  /// Groups the rows of a flat file all orders report per order, in the order of the report.
+  pub fn from_rows<I>(rows: I) -> MwsResult<Vec<ReportOrder>>
+  where
+    I: IntoIterator<Item = AllOrdersRow>,
+  {
+    let mut index = HashMap::new();
+    let mut orders: Vec<ReportOrder> = vec![];
+    for row in rows {
+      let i = match index.get(&row.amazon_order_id) {
+        Some(i) => *i,
+        None => {
+          orders.push(ReportOrder {
+            order: row.to_order()?,
+            items: vec![],
+          });
+          index.insert(row.amazon_order_id.clone(), orders.len() - 1);
+          orders.len() - 1
+        }
+      };
+      let item = row.to_order_item();
+      let order = &mut orders[i];
+      if is_shipped(&row.item_status) {
+        order.order.NumberOfItemsShipped += item.QuantityOrdered;
+      } else {
+        order.order.NumberOfItemsUnshipped += item.QuantityOrdered;
+      }
+      order.items.push(item);
+    }
+    Ok(orders)
+  }
+}
+
+/// A row of `_GET_FLAT_FILE_ALL_ORDERS_DATA_BY_LAST_UPDATE_` and
+/// `_GET_FLAT_FILE_ALL_ORDERS_DATA_BY_ORDER_DATE_`, one per order item
+#[derive(Debug, Default, Clone, PartialEq, FromTdffRow)]
+pub struct AllOrdersRow {
+  #[from_tdff_row(key = "amazon-order-id")]
+  pub amazon_order_id: String,
+  #[from_tdff_row(key = "merchant-order-id")]
+  pub merchant_order_id: String,
+  #[from_tdff_row(key = "purchase-date")]
+  pub purchase_date: String,
+  #[from_tdff_row(key = "last-updated-date,last-update-date")]
+  pub last_updated_date: String,
+  /// `Pending`, `Shipped`, `Shipping` or `Cancelled`.
+  #[from_tdff_row(key = "order-status")]
+  pub order_status: String,
+  /// `Amazon` or `Merchant`.
+  #[from_tdff_row(key = "fulfillment-channel")]
+  pub fulfillment_channel: String,
+  #[from_tdff_row(key = "sales-channel")]
+  pub sales_channel: String,
+  #[from_tdff_row(key = "order-channel")]
+  pub order_channel: String,
+  pub url: String,
+  #[from_tdff_row(key = "ship-service-level")]
+  pub ship_service_level: String,
+  #[from_tdff_row(key = "product-name")]
+  pub product_name: String,
+  pub sku: String,
+  pub asin: String,
+  /// `Unshipped`, `Shipped` or `Cancelled`.
+  #[from_tdff_row(key = "item-status")]
+  pub item_status: String,
+  pub quantity: i32,
+  pub currency: String,
+  #[from_tdff_row(key = "item-price")]
+  pub item_price: String,
+  #[from_tdff_row(key = "item-tax")]
+  pub item_tax: String,
+  #[from_tdff_row(key = "shipping-price")]
+  pub shipping_price: String,
+  #[from_tdff_row(key = "shipping-tax")]
+  pub shipping_tax: String,
+  #[from_tdff_row(key = "gift-wrap-price")]
+  pub gift_wrap_price: String,
+  #[from_tdff_row(key = "gift-wrap-tax")]
+  pub gift_wrap_tax: String,
+  #[from_tdff_row(key = "item-promotion-discount")]
+  pub item_promotion_discount: String,
+  #[from_tdff_row(key = "ship-promotion-discount")]
+  pub ship_promotion_discount: String,
+  #[from_tdff_row(key = "ship-city")]
+  pub ship_city: String,
+  #[from_tdff_row(key = "ship-state")]
+  pub ship_state: String,
+  #[from_tdff_row(key = "ship-postal-code")]
+  pub ship_postal_code: String,
+  #[from_tdff_row(key = "ship-country")]
+  pub ship_country: String,
+  #[from_tdff_row(key = "promotion-ids")]
+  pub promotion_ids: String,
+  #[from_tdff_row(key = "is-business-order")]
+  pub is_business_order: bool,
+  #[from_tdff_row(key = "purchase-order-number")]
+  pub purchase_order_number: String,
+}
+
+impl AllOrdersRow {
+  /// The order of the row, without the item counts.
+  pub fn to_order(&self) -> MwsResult<Order> {
+    Ok(Order {
+      AmazonOrderId: self.amazon_order_id.clone(),
+      SellerOrderId: self.merchant_order_id.clone(),
+      PurchaseDate: parse_date_time("purchase-date", &self.purchase_date)?,
+      LastUpdateDate: parse_date_time("last-updated-date", &self.last_updated_date)?,
+      OrderStatus: order_status(&self.order_status),
+      FulfillmentChannel: fulfillment_channel_value(&self.fulfillment_channel),
+      SalesChannel: self.sales_channel.clone(),
+      OrderChannel: self.order_channel.clone(),
+      ShipServiceLevel: self.ship_service_level.clone(),
+      ShippingAddress: if self.ship_country.is_empty() {
+        None
+      } else {
+        Some(ShippingAddress {
+          City: self.ship_city.clone(),
+          StateOrRegion: self.ship_state.clone(),
+          PostalCode: self.ship_postal_code.clone(),
+          CountryCode: self.ship_country.clone(),
+          ..Default::default()
+        })
+      },
+      IsBusinessOrder: self.is_business_order,
+      PurchaseOrderNumber: self.purchase_order_number.clone(),
+      ..Default::default()
+    })
+  }
+
+  /// The order item of the row.
+  pub fn to_order_item(&self) -> OrderItem {
+    let amount = |value: &str| {
+      if value.is_empty() {
+        None
+      } else {
+        Some(CurrencyAmount {
+          CurrencyCode: self.currency.clone(),
+          Amount: value.to_owned(),
+        })
+      }
+    };
+    OrderItem {
+      QuantityOrdered: self.quantity,
+      Title: self.product_name.clone(),
+      ASIN: self.asin.clone(),
+      SellerSKU: self.sku.clone(),
+      QuantityShipped: if is_shipped(&self.item_status) {
+        self.quantity
+      } else {
+        0
+      },
+      ItemPrice: amount(&self.item_price),
+      ItemTax: amount(&self.item_tax),
+      GiftWrapPrice: amount(&self.gift_wrap_price),
+      GiftWrapTax: amount(&self.gift_wrap_tax),
+      PromotionDiscount: amount(&self.item_promotion_discount),
+      ShippingPrice: amount(&self.shipping_price),
+      ShippingDiscount: amount(&self.ship_promotion_discount),
+      ShippingTax: amount(&self.shipping_tax),
+      ..Default::default()
+    }
+  }
+}
+
+/// An `AllOrdersRow` with valid dates, checked while the row is read so that errors have the
+/// position of the row
+#[derive(Default)]
+struct CheckedAllOrdersRow(AllOrdersRow);
+
+impl FromTdffRow for CheckedAllOrdersRow {
+  fn from_tdff_row(row: &TdffRow) -> MwsResult<Self> {
+    let row = AllOrdersRow::from_tdff_row(row)?;
+    parse_date_time("purchase-date", &row.purchase_date)?;
+    parse_date_time("last-updated-date", &row.last_updated_date)?;
+    Ok(CheckedAllOrdersRow(row))
+  }
+}
+
+/// An order of `_GET_XML_ALL_ORDERS_DATA_BY_LAST_UPDATE_` and `_GET_XML_ALL_ORDERS_DATA_BY_ORDER_DATE_`
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlOrder {
+  pub AmazonOrderID: String,
+  pub MerchantOrderID: String,
+  pub PurchaseDate: Option<DateTime<Utc>>,
+  pub LastUpdatedDate: Option<DateTime<Utc>>,
+  pub OrderStatus: String,
+  pub SalesChannel: String,
+  pub FulfillmentData: Option<XmlFulfillmentData>,
+  pub IsBusinessOrder: bool,
+  pub PurchaseOrderNumber: String,
+  #[from_xml_stream(no_list_wrapper)]
+  pub OrderItem: Vec<XmlOrderItem>,
+}
+
+impl XmlOrder {
+  /// Converts the order and its items.
+  ///
+  /// Fails if the discounts of an item with several promotions cannot be summed up.
+  pub fn into_report_order(self) -> MwsResult<ReportOrder> {
+    let (fulfillment_channel, ship_service_level, address) = match self.FulfillmentData {
+      Some(data) => (data.FulfillmentChannel, data.ShipServiceLevel, data.Address),
+      None => Default::default(),
+    };
+    let mut order = Order {
+      AmazonOrderId: self.AmazonOrderID,
+      SellerOrderId: self.MerchantOrderID,
+      PurchaseDate: self.PurchaseDate,
+      LastUpdateDate: self.LastUpdatedDate,
+      OrderStatus: order_status(&self.OrderStatus),
+      FulfillmentChannel: fulfillment_channel_value(&fulfillment_channel),
+      SalesChannel: self.SalesChannel,
+      ShipServiceLevel: ship_service_level,
+      ShippingAddress: address.map(|address| ShippingAddress {
+        City: address.City,
+        StateOrRegion: address.State,
+        PostalCode: address.PostalCode,
+        CountryCode: address.Country,
+        ..Default::default()
+      }),
+      IsBusinessOrder: self.IsBusinessOrder,
+      PurchaseOrderNumber: self.PurchaseOrderNumber,
+      ..Default::default()
+    };
+    let items = self
+      .OrderItem
+      .into_iter()
+      .map(|item| {
+        if is_shipped(&item.ItemStatus) {
+          order.NumberOfItemsShipped += item.Quantity;
+        } else {
+          order.NumberOfItemsUnshipped += item.Quantity;
+        }
+        item.into_order_item()
+      })
+      .collect::<MwsResult<_>>()?;
+    Ok(ReportOrder { order, items })
+  }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlFulfillmentData {
+  pub FulfillmentChannel: String,
+  pub ShipServiceLevel: String,
+  pub Address: Option<XmlAddress>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlAddress {
+  pub City: String,
+  pub State: String,
+  pub PostalCode: String,
+  pub Country: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlOrderItem {
+  pub AmazonOrderItemCode: String,
+  pub ASIN: String,
+  pub SKU: String,
+  pub ItemStatus: String,
+  pub ProductName: String,
+  pub Quantity: i32,
+  pub ItemPrice: Option<XmlItemPrice>,
+  #[from_xml_stream(no_list_wrapper)]
+  pub Promotion: Vec<XmlPromotion>,
+}
+
+impl XmlOrderItem {
+  /// The amount of a price component, e.g. `Principal` or `Tax`.
+  pub fn price_component(&self, component_type: &str) -> Option<&XmlAmount> {
+    self.ItemPrice.as_ref().and_then(|price| {
+      price
+        .Component
+        .iter()
+        .find(|c| c.Type == component_type)
+        .map(|c| &c.Amount)
+    })
+  }
+
+  /// Converts the item, fails if the discounts of several promotions cannot be summed up.
+  pub fn into_order_item(self) -> MwsResult<OrderItem> {
+    let amount = |component_type: &str| self.price_component(component_type).map(Into::into);
+    let currency = self
+      .ItemPrice
+      .as_ref()
+      .and_then(|price| price.Component.first())
+      .map(|c| c.Amount.currency.clone())
+      .unwrap_or_default();
+    let promotion_discount = |f: fn(&XmlPromotion) -> &str| {
+      let values: Vec<_> = self
+        .Promotion
+        .iter()
+        .map(f)
+        .filter(|value| !value.is_empty())
+        .collect();
+      if values.is_empty() {
+        Ok(None)
+      } else {
+        sum_amounts(&values).map(|amount| {
+          Some(CurrencyAmount {
+            CurrencyCode: currency.clone(),
+            Amount: amount,
+          })
+        })
+      }
+    };
+    Ok(OrderItem {
+      OrderItemId: self.AmazonOrderItemCode.clone(),
+      QuantityOrdered: self.Quantity,
+      Title: self.ProductName.clone(),
+      ASIN: self.ASIN.clone(),
+      SellerSKU: self.SKU.clone(),
+      QuantityShipped: if is_shipped(&self.ItemStatus) {
+        self.Quantity
+      } else {
+        0
+      },
+      ItemPrice: amount("Principal"),
+      ItemTax: amount("Tax"),
+      GiftWrapPrice: amount("GiftWrap"),
+      GiftWrapTax: amount("GiftWrapTax"),
+      PromotionDiscount: promotion_discount(|p| &p.ItemPromotionDiscount)?,
+      ShippingPrice: amount("Shipping"),
+      ShippingDiscount: promotion_discount(|p| &p.ShipPromotionDiscount)?,
+      ShippingTax: amount("ShippingTax"),
+    })
+  }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlItemPrice {
+  #[from_xml_stream(no_list_wrapper)]
+  pub Component: Vec<XmlPriceComponent>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlPriceComponent {
+  /// `Principal`, `Tax`, `Shipping`, `ShippingTax`, `GiftWrap` or `GiftWrapTax`.
+  pub Type: String,
+  pub Amount: XmlAmount,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlAmount {
+  #[from_xml_stream(from_attr = "currency")]
+  pub currency: String,
+  #[from_xml_stream(from_content)]
+  pub Value: String,
+}
+
+impl<'a> From<&'a XmlAmount> for CurrencyAmount {
+  fn from(v: &'a XmlAmount) -> Self {
+    CurrencyAmount {
+      CurrencyCode: v.currency.clone(),
+      Amount: v.Value.clone(),
+    }
+  }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromXmlStream)]
+#[serde(default)]
+pub struct XmlPromotion {
+  pub PromotionIDs: String,
+  pub ItemPromotionDiscount: String,
+  pub ShipPromotionDiscount: String,
+}
+
+/// Decodes the orders of a XML all orders report, one `Message` at a time
+///
+/// The report is an `AmazonEnvelope` of `Message/Order` elements, it is not read into memory.
+pub struct XmlOrderReader<R: Read> {
+  stream: Stream<R>,
+  done: bool,
+}
+
+impl<R: Read> XmlOrderReader<R> {
+  /// Reads the start of the envelope.
+  pub fn new(source: R) -> MwsResult<XmlOrderReader<R>> {
+    let mut stream = Stream::new(source);
+    start_document(&mut stream)?;
+    start_element(&mut stream, "AmazonEnvelope")?;
+    Ok(XmlOrderReader {
+      stream,
+      done: false,
+    })
+  }
+
+  fn read_next(&mut self) -> MwsResult<Option<XmlOrder>> {
+    loop {
+      let name = match self.stream.peek() {
+        Some(Ok(XmlEvent::StartElement { name, .. })) => name.local_name.clone(),
+        Some(Ok(XmlEvent::EndElement { .. })) | None => return Ok(None),
+        Some(Ok(_)) => {
+          self.stream.next();
+          continue;
+        }
+        Some(Err(err)) => return Err(err.clone().into()),
+      };
+
+      if name != "Message" {
+        skip_element(&mut self.stream)?;
+        continue;
+      }
+
+      let order = element(&mut self.stream, "Message", |s| {
+        fold_elements(s, None, |s, order| {
+          if s.local_name() == "Order" {
+            *order = Some(XmlOrder::from_xml(s)?);
+          }
+          Ok(())
+        })
+      })?;
+      if let Some(order) = order {
+        return Ok(Some(order));
+      }
+    }
+  }
+}
+
+impl<R: Read> Iterator for XmlOrderReader<R> {
+  type Item = MwsResult<XmlOrder>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.done {
+      return None;
+    }
+    match self.read_next() {
+      Ok(Some(order)) => Some(Ok(order)),
+      Ok(None) => {
+        self.done = true;
+        None
+      }
+      Err(err) => {
+        self.done = true;
+        Some(Err(err))
+      }
+    }
+  }
+}
+
+fn is_shipped(item_status: &str) -> bool {
+  item_status == "Shipped"
+}
+
+fn order_status(value: &str) -> OrderStatus {
+  match value {
+    "Cancelled" => OrderStatus::Canceled,
+    "Shipping" => OrderStatus::PartiallyShipped,
+    _ => OrderStatus::from(value),
+  }
+}
+
+fn fulfillment_channel_value(value: &str) -> FulfillmentChannel {
+  match value {
+    "Amazon" => FulfillmentChannel::AFN,
+    "Merchant" => FulfillmentChannel::MFN,
+    _ => FulfillmentChannel::from(value),
+  }
+}
+
+/// Parses the date of the column `header`, errors are `MwsError::Tdff` without position.
+fn parse_date_time(header: &str, value: &str) -> MwsResult<Option<DateTime<Utc>>> {
+  if value.is_empty() {
+    return Ok(None);
+  }
+  DateTime::parse_from_rfc3339(value)
+    .map(|v| Some(v.with_timezone(&Utc)))
+    .map_err(|err| {
+      MwsError::Tdff(TdffError {
+        position: None,
+        header: header.to_owned(),
+        value: value.to_owned(),
+        target_type: "DateTime<Utc>",
+        message: err.to_string(),
+      })
+    })
+}
+
+/// Sums the discounts of an item with several promotions.
+fn sum_amounts(values: &[&str]) -> MwsResult<String> {
+  if values.len() == 1 {
+    return Ok(values[0].to_owned());
+  }
+  let mut sum = Decimal::new(0, 0);
+  for value in values {
+    sum += value
+      .parse::<Decimal>()
+      .map_err(|err| MwsError::ParseString {
+        what: "promotion discount".to_owned(),
+        message: format!("'{}': {}", value, err),
+      })?;
+  }
+  Ok(sum.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_order_status() {
+    assert_eq!(order_status("Cancelled"), OrderStatus::Canceled);
+    assert_eq!(order_status("Shipped"), OrderStatus::Shipped);
+    assert_eq!(order_status("Shipping"), OrderStatus::PartiallyShipped);
+    assert_eq!(fulfillment_channel_value("Amazon"), FulfillmentChannel::AFN);
+    assert_eq!(
+      fulfillment_channel_value("Merchant"),
+      FulfillmentChannel::MFN
+    );
+  }
+
+  #[test]
+  fn test_parse_date_time() {
+    assert_eq!(
+      parse_date_time("purchase-date", "2019-02-10T00:12:33-08:00").unwrap(),
+      Some("2019-02-10T08:12:33Z".parse().unwrap())
+    );
+    assert_eq!(parse_date_time("purchase-date", "").unwrap(), None);
+    match parse_date_time("purchase-date", "10.02.2019") {
+      Err(MwsError::Tdff(err)) => {
+        assert_eq!(err.header, "purchase-date");
+        assert_eq!(err.value, "10.02.2019");
+      }
+      other => panic!("unexpected result: {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_sum_amounts() {
+    assert_eq!(sum_amounts(&["-5.00"]).unwrap(), "-5.00");
+    assert_eq!(sum_amounts(&["-5.00", "-1.50"]).unwrap(), "-6.50");
+    assert!(sum_amounts(&["-5.00", "n/a"]).is_err());
+  }
+}
@@ -0,0 +1,226 @@
+//! Report type catalogue
+//!
+//! [Documentation](http://docs.developer.amazonservices.com/en_US/reports/Reports_ReportType.html)
+//!
+//! Report types missing from `ReportType` can still be used through `ReportType::UnknownValue`,
+//! e.g. `ReportType::from("_GET_NEW_REPORT_TYPE_")`.
+
+use crate::constants::get_flat_file_encoding;
+use encoding_rs::{Encoding, UTF_8};
+
+string_map_enum! {
+  /// Enumerates the report types that are available through the Reports API section.
+  ///
+  /// Serialized as the report type value, e.g. `"_GET_AFN_INVENTORY_DATA_"`.
+  #[serde_as_value]
+  pub enum ReportType {
+    // Listings Reports
+    FlatFileOpenListings = "_GET_FLAT_FILE_OPEN_LISTINGS_DATA_",
+    MerchantListingsAll = "_GET_MERCHANT_LISTINGS_ALL_DATA_",
+    MerchantListings = "_GET_MERCHANT_LISTINGS_DATA_",
+    MerchantListingsInactive = "_GET_MERCHANT_LISTINGS_INACTIVE_DATA_",
+    MerchantListingsBackCompat = "_GET_MERCHANT_LISTINGS_DATA_BACK_COMPAT_",
+    MerchantListingsLite = "_GET_MERCHANT_LISTINGS_DATA_LITE_",
+    MerchantListingsLiter = "_GET_MERCHANT_LISTINGS_DATA_LITER_",
+    MerchantCancelledListings = "_GET_MERCHANT_CANCELLED_LISTINGS_DATA_",
+    MerchantListingsDefect = "_GET_MERCHANT_LISTINGS_DEFECT_DATA_",
+    PanEUOfferStatus = "_GET_PAN_EU_OFFER_STATUS_",
+    MfnPanEUOfferStatus = "_GET_MFN_PAN_EU_OFFER_STATUS_",
+    ReferralFeePreview = "_GET_REFERRAL_FEE_PREVIEW_REPORT_",
+    XmlBrowseTree = "_GET_XML_BROWSE_TREE_DATA_",
+
+    // Order Reports
+    FlatFileActionableOrder = "_GET_FLAT_FILE_ACTIONABLE_ORDER_DATA_",
+    XmlOrders = "_GET_ORDERS_DATA_",
+    FlatFileOrders = "_GET_FLAT_FILE_ORDERS_DATA_",
+    ConvergedFlatFileOrders = "_GET_CONVERGED_FLAT_FILE_ORDER_REPORT_DATA_",
+
+    // Order Tracking Reports
+    FlatFileAllOrdersByLastUpdate = "_GET_FLAT_FILE_ALL_ORDERS_DATA_BY_LAST_UPDATE_",
+    FlatFileAllOrdersByOrderDate = "_GET_FLAT_FILE_ALL_ORDERS_DATA_BY_ORDER_DATE_",
+    XmlAllOrdersByLastUpdate = "_GET_XML_ALL_ORDERS_DATA_BY_LAST_UPDATE_",
+    XmlAllOrdersByOrderDate = "_GET_XML_ALL_ORDERS_DATA_BY_ORDER_DATE_",
+
+    // Pending Order Reports
+    FlatFilePendingOrders = "_GET_FLAT_FILE_PENDING_ORDERS_DATA_",
+    XmlPendingOrders = "_GET_PENDING_ORDERS_DATA_",
+    ConvergedFlatFilePendingOrders = "_GET_CONVERGED_FLAT_FILE_PENDING_ORDERS_DATA_",
+
+    // Returns Reports
+    XmlReturnsByReturnDate = "_GET_XML_RETURNS_DATA_BY_RETURN_DATE_",
+    FlatFileReturnsByReturnDate = "_GET_FLAT_FILE_RETURNS_DATA_BY_RETURN_DATE_",
+    XmlMfnPrimeReturns = "_GET_XML_MFN_PRIME_RETURNS_REPORT_",
+    CsvMfnPrimeReturns = "_GET_CSV_MFN_PRIME_RETURNS_REPORT_",
+    XmlMfnSkuReturnAttributes = "_GET_XML_MFN_SKU_RETURN_ATTRIBUTES_REPORT_",
+    FlatFileMfnSkuReturnAttributes = "_GET_FLAT_FILE_MFN_SKU_RETURN_ATTRIBUTES_REPORT_",
+
+    // Performance Reports
+    SellerFeedback = "_GET_SELLER_FEEDBACK_DATA_",
+    XmlSellerPerformance = "_GET_V1_SELLER_PERFORMANCE_REPORT_",
+
+    // Settlement Reports
+    FlatFileSettlement = "_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_",
+    FlatFileSettlementV2 = "_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_V2_",
+    XmlSettlement = "_GET_V2_SETTLEMENT_REPORT_DATA_XML_",
+
+    // Fulfillment by Amazon (FBA) Reports
+    AmazonFulfilledShipments = "_GET_AMAZON_FULFILLED_SHIPMENTS_DATA_",
+    FbaCustomerShipmentSales = "_GET_FBA_FULFILLMENT_CUSTOMER_SHIPMENT_SALES_DATA_",
+    FbaCustomerShipmentPromotion = "_GET_FBA_FULFILLMENT_CUSTOMER_SHIPMENT_PROMOTION_DATA_",
+    FbaCustomerTaxes = "_GET_FBA_FULFILLMENT_CUSTOMER_TAXES_DATA_",
+    FbaMyiUnsuppressedInventory = "_GET_FBA_MYI_UNSUPPRESSED_INVENTORY_DATA_",
+    FbaMyiAllInventory = "_GET_FBA_MYI_ALL_INVENTORY_DATA_",
+    AfnInventory = "_GET_AFN_INVENTORY_DATA_",
+    AfnInventoryByCountry = "_GET_AFN_INVENTORY_DATA_BY_COUNTRY_",
+    FbaCurrentInventory = "_GET_FBA_FULFILLMENT_CURRENT_INVENTORY_DATA_",
+    FbaMonthlyInventory = "_GET_FBA_FULFILLMENT_MONTHLY_INVENTORY_DATA_",
+    FbaInventoryReceipts = "_GET_FBA_FULFILLMENT_INVENTORY_RECEIPTS_DATA_",
+    ReservedInventory = "_GET_RESERVED_INVENTORY_DATA_",
+    FbaInventorySummary = "_GET_FBA_FULFILLMENT_INVENTORY_SUMMARY_DATA_",
+    FbaInventoryAdjustments = "_GET_FBA_FULFILLMENT_INVENTORY_ADJUSTMENTS_DATA_",
+    FbaInventoryHealth = "_GET_FBA_FULFILLMENT_INVENTORY_HEALTH_DATA_",
+    FbaInventoryAged = "_GET_FBA_INVENTORY_AGED_DATA_",
+    ExcessInventory = "_GET_EXCESS_INVENTORY_DATA_",
+    FbaStorageFeeCharges = "_GET_FBA_STORAGE_FEE_CHARGES_DATA_",
+    StrandedInventory = "_GET_STRANDED_INVENTORY_UI_DATA_",
+    FbaEstimatedFees = "_GET_FBA_ESTIMATED_FBA_FEES_TXT_DATA_",
+    FbaReimbursements = "_GET_FBA_REIMBURSEMENTS_DATA_",
+    FbaCustomerReturns = "_GET_FBA_FULFILLMENT_CUSTOMER_RETURNS_DATA_",
+    FbaCustomerShipmentReplacement = "_GET_FBA_FULFILLMENT_CUSTOMER_SHIPMENT_REPLACEMENT_DATA_",
+    FbaRemovalOrderDetail = "_GET_FBA_FULFILLMENT_REMOVAL_ORDER_DETAIL_DATA_",
+    FbaRemovalShipmentDetail = "_GET_FBA_FULFILLMENT_REMOVAL_SHIPMENT_DETAIL_DATA_",
+    RestockInventoryRecommendations = "_GET_RESTOCK_INVENTORY_RECOMMENDATIONS_REPORT_",
+    FbaRecommendedRemoval = "_GET_FBA_RECOMMENDED_REMOVAL_DATA_",
+
+    // Tax Reports
+    FlatFileSalesTax = "_GET_FLAT_FILE_SALES_TAX_DATA_",
+    VatTax = "_SC_VAT_TAX_REPORT_",
+  }
+}
+
+/// The file format of a report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+  /// Tab-delimited flat file, see `tdff`.
+  TabDelimited,
+  Xml,
+  Csv,
+}
+
+impl ReportType {
+  /// Returns `false` for report types which can only be scheduled with `ManageReportSchedule`,
+  /// or are generated by Amazon, and cannot be requested with `RequestReport`.
+  pub fn is_requestable(&self) -> bool {
+    !matches!(
+      *self,
+      ReportType::XmlOrders
+        | ReportType::FlatFileOrders
+        | ReportType::ConvergedFlatFileOrders
+        | ReportType::FlatFileSettlement
+        | ReportType::FlatFileSettlementV2
+        | ReportType::XmlSettlement
+    )
+  }
+
+  /// Returns `false` for report types which cannot be scheduled with `ManageReportSchedule`,
+  /// e.g. settlement reports, which Amazon schedules itself.
+  pub fn is_schedulable(&self) -> bool {
+    !matches!(
+      *self,
+      ReportType::FlatFileSettlement
+        | ReportType::FlatFileSettlementV2
+        | ReportType::XmlSettlement
+    )
+  }
+
+  /// The `ReportOptions` supported by the report type, e.g. `ShowSalesChannel` for
+  /// `ReportOptions: Some("ShowSalesChannel=true")`.
+  pub fn report_options(&self) -> &'static [&'static str] {
+    match *self {
+      ReportType::FlatFileActionableOrder
+      | ReportType::FlatFileOrders
+      | ReportType::ConvergedFlatFileOrders => &["ShowSalesChannel"],
+      ReportType::XmlBrowseTree => &["RootNodesOnly", "BrowseNodeId", "MarketplaceId"],
+      _ => &[],
+    }
+  }
+
+  /// The file format of the report, `None` for unknown report types.
+  pub fn format(&self) -> Option<ReportFormat> {
+    match *self {
+      ReportType::XmlBrowseTree
+      | ReportType::XmlOrders
+      | ReportType::XmlAllOrdersByLastUpdate
+      | ReportType::XmlAllOrdersByOrderDate
+      | ReportType::XmlPendingOrders
+      | ReportType::XmlReturnsByReturnDate
+      | ReportType::XmlMfnPrimeReturns
+      | ReportType::XmlMfnSkuReturnAttributes
+      | ReportType::XmlSellerPerformance
+      | ReportType::XmlSettlement => Some(ReportFormat::Xml),
+      ReportType::CsvMfnPrimeReturns => Some(ReportFormat::Csv),
+      ReportType::UnknownValue(_) => None,
+      _ => Some(ReportFormat::TabDelimited),
+    }
+  }
+
+  /// The character encoding of the report in a marketplace.
+  ///
+  /// XML reports are encoded in UTF-8, flat files in the encoding of the marketplace, see
+  /// `constants::get_flat_file_encoding`.
+  pub fn encoding(&self, marketplace_id: &str) -> &'static Encoding {
+    if self.format() == Some(ReportFormat::Xml) {
+      return UTF_8;
+    }
+    get_flat_file_encoding(marketplace_id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::{
+    MARKETPLACE_ID_CN, MARKETPLACE_ID_IN, MARKETPLACE_ID_JP, MARKETPLACE_ID_US,
+  };
+  use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+  #[test]
+  fn test_report_type() {
+    let report_type = ReportType::from("_GET_FLAT_FILE_OPEN_LISTINGS_DATA_");
+    assert_eq!(report_type, "_GET_FLAT_FILE_OPEN_LISTINGS_DATA_");
+    assert!(matches!(report_type, ReportType::FlatFileOpenListings));
+    assert_eq!(report_type.format(), Some(ReportFormat::TabDelimited));
+    assert_eq!(report_type.encoding(MARKETPLACE_ID_JP), SHIFT_JIS);
+    assert_eq!(report_type.encoding(MARKETPLACE_ID_CN), UTF_8);
+    assert_eq!(report_type.encoding(MARKETPLACE_ID_IN), UTF_8);
+    assert_eq!(report_type.encoding(MARKETPLACE_ID_US), WINDOWS_1252);
+
+    assert!(!ReportType::FlatFileSettlementV2.is_requestable());
+    assert!(!ReportType::FlatFileSettlementV2.is_schedulable());
+    assert!(ReportType::FlatFileOrders.is_schedulable());
+    assert_eq!(
+      ReportType::FlatFileActionableOrder.report_options(),
+      &["ShowSalesChannel"]
+    );
+    assert_eq!(
+      ReportType::XmlAllOrdersByOrderDate.encoding(MARKETPLACE_ID_JP),
+      UTF_8
+    );
+
+    let report_type = ReportType::from("_GET_AFN_INVENTORY_DATA".to_owned());
+    assert!(match report_type {
+      ReportType::UnknownValue(ref v) => v == "_GET_AFN_INVENTORY_DATA",
+      _ => false,
+    });
+    assert!(report_type.is_requestable());
+    assert_eq!(report_type.format(), None);
+    assert_eq!(
+      serde_json::to_string(&ReportType::AfnInventory).unwrap(),
+      r#""_GET_AFN_INVENTORY_DATA_""#
+    );
+    assert_eq!(
+      serde_json::from_str::<ReportType>(r#""_GET_AFN_INVENTORY_DATA_""#).unwrap(),
+      "_GET_AFN_INVENTORY_DATA_"
+    );
+  }
+}
@@ -0,0 +1,160 @@
+//! Row types of tab-delimited flat file reports
+//!
+//! Parse a report with `TdffParser::parse_all` or `fetch_report_rows`. Column names differ between
+//! marketplaces, e.g. `sku` and `seller-sku`, or the Japanese names of `_GET_MERCHANT_LISTINGS_ALL_DATA_`,
+//! the known variants of a column are all accepted.
+//!
+//! Prices are kept as found in the report, they are formatted for the marketplace.
+
+use std::io;
+use std::str::FromStr;
+
+/// A `Yes`/`No` column, e.g. `afn-listing-exists`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct YesNo(pub bool);
+
+impl FromStr for YesNo {
+  type Err = io::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_ascii_lowercase().as_ref() {
+      "yes" | "y" | "true" => Ok(YesNo(true)),
+      "no" | "n" | "false" => Ok(YesNo(false)),
+      _ => Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "expected 'Yes' or 'No'",
+      )),
+    }
+  }
+}
+
+/// A row of `_GET_AFN_INVENTORY_DATA_` and `_GET_AFN_INVENTORY_DATA_BY_COUNTRY_`
+#[derive(Debug, Default, Clone, PartialEq, FromTdffRow)]
+pub struct AfnInventoryRow {
+  #[from_tdff_row(key = "seller-sku,sku")]
+  pub seller_sku: String,
+  #[from_tdff_row(key = "fulfillment-channel-sku,fnsku")]
+  pub fulfillment_channel_sku: String,
+  pub asin: String,
+  #[from_tdff_row(key = "condition-type")]
+  pub condition_type: String,
+  /// `SELLABLE` or `UNSELLABLE`.
+  #[from_tdff_row(key = "Warehouse-Condition-code,warehouse-condition-code")]
+  pub warehouse_condition_code: String,
+  #[from_tdff_row(key = "Quantity Available,quantity-available")]
+  pub quantity_available: i32,
+  /// Only in `_GET_AFN_INVENTORY_DATA_BY_COUNTRY_`.
+  #[from_tdff_row(key = "country")]
+  pub country: String,
+}
+
+/// A row of `_GET_FBA_MYI_UNSUPPRESSED_INVENTORY_DATA_` and `_GET_FBA_MYI_ALL_INVENTORY_DATA_`
+#[derive(Debug, Default, Clone, PartialEq, FromTdffRow)]
+pub struct FbaMyiInventoryRow {
+  #[from_tdff_row(key = "sku,seller-sku")]
+  pub sku: String,
+  pub fnsku: String,
+  pub asin: String,
+  #[from_tdff_row(key = "product-name")]
+  pub product_name: String,
+  pub condition: String,
+  #[from_tdff_row(key = "your-price")]
+  pub your_price: String,
+  #[from_tdff_row(key = "mfn-listing-exists")]
+  pub mfn_listing_exists: YesNo,
+  #[from_tdff_row(key = "mfn-fulfillable-quantity")]
+  pub mfn_fulfillable_quantity: i32,
+  #[from_tdff_row(key = "afn-listing-exists")]
+  pub afn_listing_exists: YesNo,
+  #[from_tdff_row(key = "afn-warehouse-quantity")]
+  pub afn_warehouse_quantity: i32,
+  #[from_tdff_row(key = "afn-fulfillable-quantity")]
+  pub afn_fulfillable_quantity: i32,
+  #[from_tdff_row(key = "afn-unsellable-quantity")]
+  pub afn_unsellable_quantity: i32,
+  #[from_tdff_row(key = "afn-reserved-quantity")]
+  pub afn_reserved_quantity: i32,
+  #[from_tdff_row(key = "afn-total-quantity")]
+  pub afn_total_quantity: i32,
+  #[from_tdff_row(key = "per-unit-volume")]
+  pub per_unit_volume: String,
+  #[from_tdff_row(key = "afn-inbound-working-quantity")]
+  pub afn_inbound_working_quantity: i32,
+  #[from_tdff_row(key = "afn-inbound-shipped-quantity")]
+  pub afn_inbound_shipped_quantity: i32,
+  #[from_tdff_row(key = "afn-inbound-receiving-quantity")]
+  pub afn_inbound_receiving_quantity: i32,
+  #[from_tdff_row(key = "afn-researching-quantity")]
+  pub afn_researching_quantity: i32,
+  #[from_tdff_row(key = "afn-reserved-future-supply")]
+  pub afn_reserved_future_supply: i32,
+  #[from_tdff_row(key = "afn-future-supply-buyable")]
+  pub afn_future_supply_buyable: i32,
+}
+
+/// A row of `_GET_MERCHANT_LISTINGS_ALL_DATA_`, `_GET_MERCHANT_LISTINGS_DATA_`
+/// and `_GET_MERCHANT_LISTINGS_INACTIVE_DATA_`
+#[derive(Debug, Default, Clone, PartialEq, FromTdffRow)]
+pub struct MerchantListingRow {
+  #[from_tdff_row(key = "item-name,商品名")]
+  pub item_name: String,
+  #[from_tdff_row(key = "item-description,商品の説明")]
+  pub item_description: String,
+  #[from_tdff_row(key = "listing-id,出品ID")]
+  pub listing_id: String,
+  #[from_tdff_row(key = "seller-sku,sku,出品者SKU")]
+  pub seller_sku: String,
+  #[from_tdff_row(key = "price,価格")]
+  pub price: String,
+  #[from_tdff_row(key = "quantity,数量")]
+  pub quantity: i32,
+  #[from_tdff_row(key = "open-date,出品日")]
+  pub open_date: String,
+  #[from_tdff_row(key = "image-url,商品イメージURL")]
+  pub image_url: String,
+  #[from_tdff_row(key = "item-is-marketplace,マーケットプレイス出品")]
+  pub item_is_marketplace: String,
+  #[from_tdff_row(key = "product-id-type,商品IDタイプ")]
+  pub product_id_type: String,
+  #[from_tdff_row(key = "item-note,コンディション説明")]
+  pub item_note: String,
+  /// The condition code, e.g. `11` for new.
+  #[from_tdff_row(key = "item-condition,コンディション")]
+  pub item_condition: String,
+  #[from_tdff_row(key = "asin1,ASIN1")]
+  pub asin1: String,
+  #[from_tdff_row(key = "asin2,ASIN2")]
+  pub asin2: String,
+  #[from_tdff_row(key = "asin3,ASIN3")]
+  pub asin3: String,
+  #[from_tdff_row(key = "will-ship-internationally,国外へ配送可")]
+  pub will_ship_internationally: String,
+  #[from_tdff_row(key = "expedited-shipping,迅速な配送")]
+  pub expedited_shipping: String,
+  #[from_tdff_row(key = "product-id,商品ID")]
+  pub product_id: String,
+  #[from_tdff_row(key = "pending-quantity,保留中の数量")]
+  pub pending_quantity: i32,
+  /// `DEFAULT` for merchant fulfilled listings, `AMAZON_NA`, `AMAZON_EU`, ... for FBA.
+  #[from_tdff_row(key = "fulfillment-channel,フルフィルメント・チャンネル")]
+  pub fulfillment_channel: String,
+  #[from_tdff_row(key = "merchant-shipping-group,出品者配送グループ")]
+  pub merchant_shipping_group: String,
+  /// `Active` or `Inactive`, only in `_GET_MERCHANT_LISTINGS_ALL_DATA_`.
+  #[from_tdff_row(key = "status,ステータス")]
+  pub status: String,
+}
+
+/// A row of `_GET_FLAT_FILE_OPEN_LISTINGS_DATA_`
+#[derive(Debug, Default, Clone, PartialEq, FromTdffRow)]
+pub struct OpenListingRow {
+  #[from_tdff_row(key = "sku,seller-sku")]
+  pub sku: String,
+  pub asin: String,
+  pub price: String,
+  pub quantity: i32,
+  #[from_tdff_row(key = "Business Price,business-price")]
+  pub business_price: String,
+  #[from_tdff_row(key = "Quantity Price Type,quantity-price-type")]
+  pub quantity_price_type: String,
+}
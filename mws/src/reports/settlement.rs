@@ -0,0 +1,416 @@
+//! Settlement report parser
+//!
+//! Parses the tab-delimited flat file settlement reports, `_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_`
+//! and `_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_V2_`.
+//!
+//! The first row of a report is a summary of the settlement (start, end and deposit dates, total
+//! amount and currency), the following rows are the transactions. Amounts and dates are formatted
+//! for the marketplace, e.g. `-1.234,56` and `20.02.2019 08:00:00 UTC` in Germany, pick the
+//! `SettlementLocale` of the marketplace the report was requested for.
+
+use super::ReportType;
+use crate::constants::{
+  MARKETPLACE_ID_DE, MARKETPLACE_ID_ES, MARKETPLACE_ID_FR, MARKETPLACE_ID_GB, MARKETPLACE_ID_IT,
+  MARKETPLACE_ID_JP,
+};
+use crate::result::{MwsError, MwsResult};
+use crate::tdff::TdffParser;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+pub use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+/// The settlement report types this module parses.
+pub const REPORT_TYPES: &[ReportType] = &[
+  ReportType::FlatFileSettlement,
+  ReportType::FlatFileSettlementV2,
+];
+
+/// How amounts and dates are formatted in a settlement report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettlementLocale {
+  /// `1234.56` and `2019-02-20 08:00:00 UTC`, used in North America, China and India.
+  Default,
+  /// `1.234,56` and `20.02.2019 08:00:00 UTC`, used in Germany, France, Italy and Spain.
+  European,
+  /// `1,234.56` and `20/02/2019 08:00:00 UTC`, used in the United Kingdom.
+  British,
+  /// `1234` and `2019/02/20 08:00:00 UTC`, used in Japan.
+  Japanese,
+}
+
+impl SettlementLocale {
+  pub fn for_marketplace(marketplace_id: &str) -> SettlementLocale {
+    match marketplace_id {
+      MARKETPLACE_ID_DE | MARKETPLACE_ID_FR | MARKETPLACE_ID_IT | MARKETPLACE_ID_ES => {
+        SettlementLocale::European
+      }
+      MARKETPLACE_ID_GB => SettlementLocale::British,
+      MARKETPLACE_ID_JP => SettlementLocale::Japanese,
+      _ => SettlementLocale::Default,
+    }
+  }
+
+  fn date_format(self) -> &'static str {
+    match self {
+      SettlementLocale::Default => "%Y-%m-%d",
+      SettlementLocale::European => "%d.%m.%Y",
+      SettlementLocale::British => "%d/%m/%Y",
+      SettlementLocale::Japanese => "%Y/%m/%d",
+    }
+  }
+
+  /// Parses an amount, e.g. `-1.234,56` in `European`.
+  pub fn parse_amount(self, value: &str) -> MwsResult<Decimal> {
+    let normalized: String = match self {
+      SettlementLocale::European => value
+        .chars()
+        .filter(|c| *c != '.' && !c.is_whitespace())
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect(),
+      _ => value
+        .chars()
+        .filter(|c| *c != ',' && !c.is_whitespace())
+        .collect(),
+    };
+    Decimal::from_str(&normalized).map_err(|err| MwsError::ParseString {
+      what: "amount".to_string(),
+      message: format!("{}: '{}'", err, value),
+    })
+  }
+
+  /// Parses a date and time in UTC, either RFC 3339 or the format of the locale.
+  pub fn parse_date_time(self, value: &str) -> MwsResult<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+      return Ok(date_time.with_timezone(&Utc));
+    }
+    let trimmed = value
+      .trim_end_matches(" UTC")
+      .trim_end_matches(" GMT")
+      .trim();
+    let with_time = format!("{} %H:%M:%S", self.date_format());
+    NaiveDateTime::parse_from_str(trimmed, &with_time)
+      .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S"))
+      .map(|date_time| Utc.from_utc_datetime(&date_time))
+      .map_err(|err| MwsError::ParseString {
+        what: "date time".to_string(),
+        message: format!("{}: '{}'", err, value),
+      })
+  }
+
+  /// Parses a date, either `%Y-%m-%d` or the format of the locale.
+  pub fn parse_date(self, value: &str) -> MwsResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, self.date_format())
+      .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+      .map_err(|err| MwsError::ParseString {
+        what: "date".to_string(),
+        message: format!("{}: '{}'", err, value),
+      })
+  }
+}
+
+/// A row of a settlement report, as found in the file
+#[derive(Debug, Default, Clone, PartialEq, FromTdffRow)]
+pub struct SettlementRow {
+  #[from_tdff_row(key = "settlement-id")]
+  pub settlement_id: String,
+  #[from_tdff_row(key = "settlement-start-date")]
+  pub settlement_start_date: String,
+  #[from_tdff_row(key = "settlement-end-date")]
+  pub settlement_end_date: String,
+  #[from_tdff_row(key = "deposit-date")]
+  pub deposit_date: String,
+  #[from_tdff_row(key = "total-amount")]
+  pub total_amount: String,
+  pub currency: String,
+  #[from_tdff_row(key = "transaction-type")]
+  pub transaction_type: String,
+  #[from_tdff_row(key = "order-id")]
+  pub order_id: String,
+  #[from_tdff_row(key = "merchant-order-id")]
+  pub merchant_order_id: String,
+  #[from_tdff_row(key = "adjustment-id")]
+  pub adjustment_id: String,
+  #[from_tdff_row(key = "shipment-id")]
+  pub shipment_id: String,
+  #[from_tdff_row(key = "marketplace-name")]
+  pub marketplace_name: String,
+  #[from_tdff_row(key = "amount-type")]
+  pub amount_type: String,
+  #[from_tdff_row(key = "amount-description")]
+  pub amount_description: String,
+  pub amount: String,
+  #[from_tdff_row(key = "fulfillment-id")]
+  pub fulfillment_id: String,
+  #[from_tdff_row(key = "posted-date")]
+  pub posted_date: String,
+  #[from_tdff_row(key = "posted-date-time")]
+  pub posted_date_time: String,
+  #[from_tdff_row(key = "order-item-code")]
+  pub order_item_code: String,
+  #[from_tdff_row(key = "merchant-order-item-id")]
+  pub merchant_order_item_id: String,
+  #[from_tdff_row(key = "merchant-adjustment-item-id")]
+  pub merchant_adjustment_item_id: String,
+  pub sku: String,
+  #[from_tdff_row(key = "quantity-purchased")]
+  pub quantity_purchased: String,
+  #[from_tdff_row(key = "promotion-id")]
+  pub promotion_id: String,
+}
+
+impl SettlementRow {
+  /// The summary row has a total amount and no transaction.
+  pub fn is_summary(&self) -> bool {
+    !self.total_amount.trim().is_empty() && self.transaction_type.trim().is_empty()
+  }
+}
+
+/// The summary row of a settlement report
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettlementSummary {
+  pub settlement_id: String,
+  pub settlement_start_date: DateTime<Utc>,
+  pub settlement_end_date: DateTime<Utc>,
+  pub deposit_date: Option<DateTime<Utc>>,
+  pub total_amount: Decimal,
+  pub currency: String,
+}
+
+/// An amount settled for a transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettlementLine {
+  pub transaction_type: String,
+  pub order_id: Option<String>,
+  pub merchant_order_id: Option<String>,
+  pub adjustment_id: Option<String>,
+  pub shipment_id: Option<String>,
+  pub marketplace_name: Option<String>,
+  /// e.g. `ItemPrice`, `ItemFees` or `Promotion`.
+  pub amount_type: String,
+  /// e.g. `Principal`, `Commission` or `FBAPerUnitFulfillmentFee`.
+  pub amount_description: String,
+  pub amount: Decimal,
+  pub fulfillment_id: Option<String>,
+  pub posted_date: Option<NaiveDate>,
+  pub posted_date_time: Option<DateTime<Utc>>,
+  pub order_item_code: Option<String>,
+  pub merchant_order_item_id: Option<String>,
+  pub merchant_adjustment_item_id: Option<String>,
+  pub sku: Option<String>,
+  pub quantity_purchased: Option<i32>,
+  pub promotion_id: Option<String>,
+}
+
+/// A parsed settlement report
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettlementReport {
+  pub summary: SettlementSummary,
+  pub lines: Vec<SettlementLine>,
+}
+
+/// The lines of an order in a settlement report, see `SettlementReport::orders`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderSettlement<'a> {
+  pub order_id: &'a str,
+  pub lines: Vec<&'a SettlementLine>,
+  pub total: Decimal,
+}
+
+/// The total of a fee type in a settlement report, see `SettlementReport::fee_totals`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeTotal<'a> {
+  pub amount_type: &'a str,
+  pub amount_description: &'a str,
+  pub count: usize,
+  pub total: Decimal,
+}
+
+impl SettlementReport {
+  /// Parses a settlement report.
+  ///
+  /// The source must be UTF-8, wrap it in `tdff::decoding_reader` to decode other encodings.
+  pub fn parse<R: Read>(source: R, locale: SettlementLocale) -> MwsResult<SettlementReport> {
+    let rows = TdffParser::new(source)?.parse_all::<SettlementRow>()?;
+    SettlementReport::from_rows(rows, locale)
+  }
+
+  /// Builds a report from the rows of a settlement report, the first one being the summary row.
+  pub fn from_rows<I>(rows: I, locale: SettlementLocale) -> MwsResult<SettlementReport>
+  where
+    I: IntoIterator<Item = SettlementRow>,
+  {
+    let mut rows = rows.into_iter();
+    let summary = match rows.next() {
+      Some(ref row) if row.is_summary() => parse_summary(row, locale)?,
+      _ => {
+        return Err(MwsError::Msg(
+          "settlement report does not start with a summary row".to_string(),
+        ))
+      }
+    };
+    let lines = rows
+      .map(|row| parse_line(&row, locale))
+      .collect::<MwsResult<_>>()?;
+    Ok(SettlementReport { summary, lines })
+  }
+
+  /// Groups the lines with an order id per order, in the order of the report.
+  pub fn orders(&self) -> Vec<OrderSettlement<'_>> {
+    let mut index = HashMap::new();
+    let mut orders: Vec<OrderSettlement> = vec![];
+    for line in &self.lines {
+      let order_id = match line.order_id {
+        Some(ref order_id) => order_id.as_str(),
+        None => continue,
+      };
+      let i = *index.entry(order_id).or_insert_with(|| {
+        orders.push(OrderSettlement {
+          order_id,
+          lines: vec![],
+          total: Decimal::new(0, 0),
+        });
+        orders.len() - 1
+      });
+      orders[i].lines.push(line);
+      orders[i].total += line.amount;
+    }
+    orders
+  }
+
+  /// Sums the amounts per amount type and description, in the order of the report.
+  pub fn fee_totals(&self) -> Vec<FeeTotal<'_>> {
+    let mut index = HashMap::new();
+    let mut totals: Vec<FeeTotal> = vec![];
+    for line in &self.lines {
+      let key = (line.amount_type.as_str(), line.amount_description.as_str());
+      let i = *index.entry(key).or_insert_with(|| {
+        totals.push(FeeTotal {
+          amount_type: key.0,
+          amount_description: key.1,
+          count: 0,
+          total: Decimal::new(0, 0),
+        });
+        totals.len() - 1
+      });
+      totals[i].count += 1;
+      totals[i].total += line.amount;
+    }
+    totals
+  }
+}
+
+fn parse_summary(row: &SettlementRow, locale: SettlementLocale) -> MwsResult<SettlementSummary> {
+  Ok(SettlementSummary {
+    settlement_id: row.settlement_id.clone(),
+    settlement_start_date: locale.parse_date_time(&row.settlement_start_date)?,
+    settlement_end_date: locale.parse_date_time(&row.settlement_end_date)?,
+    deposit_date: optional(&row.deposit_date)
+      .map(|v| locale.parse_date_time(v))
+      .transpose()?,
+    total_amount: locale.parse_amount(&row.total_amount)?,
+    currency: row.currency.clone(),
+  })
+}
+
+fn parse_line(row: &SettlementRow, locale: SettlementLocale) -> MwsResult<SettlementLine> {
+  Ok(SettlementLine {
+    transaction_type: row.transaction_type.clone(),
+    order_id: optional_string(&row.order_id),
+    merchant_order_id: optional_string(&row.merchant_order_id),
+    adjustment_id: optional_string(&row.adjustment_id),
+    shipment_id: optional_string(&row.shipment_id),
+    marketplace_name: optional_string(&row.marketplace_name),
+    amount_type: row.amount_type.clone(),
+    amount_description: row.amount_description.clone(),
+    amount: match optional(&row.amount) {
+      Some(amount) => locale.parse_amount(amount)?,
+      None => Decimal::new(0, 0),
+    },
+    fulfillment_id: optional_string(&row.fulfillment_id),
+    posted_date: optional(&row.posted_date)
+      .map(|v| locale.parse_date(v))
+      .transpose()?,
+    posted_date_time: optional(&row.posted_date_time)
+      .map(|v| locale.parse_date_time(v))
+      .transpose()?,
+    order_item_code: optional_string(&row.order_item_code),
+    merchant_order_item_id: optional_string(&row.merchant_order_item_id),
+    merchant_adjustment_item_id: optional_string(&row.merchant_adjustment_item_id),
+    sku: optional_string(&row.sku),
+    quantity_purchased: optional(&row.quantity_purchased)
+      .map(|v| {
+        v.parse().map_err(|err| MwsError::ParseString {
+          what: "quantity-purchased".to_string(),
+          message: format!("{}: '{}'", err, v),
+        })
+      })
+      .transpose()?,
+    promotion_id: optional_string(&row.promotion_id),
+  })
+}
+
+fn optional(value: &str) -> Option<&str> {
+  let trimmed = value.trim();
+  if trimmed.is_empty() {
+    None
+  } else {
+    Some(trimmed)
+  }
+}
+
+fn optional_string(value: &str) -> Option<String> {
+  optional(value).map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_amount() {
+    assert_eq!(
+      SettlementLocale::European
+        .parse_amount("-1.234,56")
+        .unwrap(),
+      Decimal::new(-123456, 2)
+    );
+    assert_eq!(
+      SettlementLocale::British.parse_amount("1,234.56").unwrap(),
+      Decimal::new(123456, 2)
+    );
+    assert_eq!(
+      SettlementLocale::Japanese.parse_amount("-350").unwrap(),
+      Decimal::new(-350, 0)
+    );
+    assert!(SettlementLocale::Default.parse_amount("12.34.5").is_err());
+  }
+
+  #[test]
+  fn test_parse_date_time() {
+    let expected = Utc.ymd(2019, 2, 20).and_hms(8, 0, 0);
+    assert_eq!(
+      SettlementLocale::European
+        .parse_date_time("20.02.2019 08:00:00 UTC")
+        .unwrap(),
+      expected
+    );
+    assert_eq!(
+      SettlementLocale::Japanese
+        .parse_date_time("2019/02/20 08:00:00 UTC")
+        .unwrap(),
+      expected
+    );
+    assert_eq!(
+      SettlementLocale::British
+        .parse_date_time("2019-02-20T09:00:00+01:00")
+        .unwrap(),
+      expected
+    );
+    assert_eq!(
+      SettlementLocale::European.parse_date("2019-02-20").unwrap(),
+      NaiveDate::from_ymd(2019, 2, 20)
+    );
+  }
+}
@@ -1,9 +1,10 @@
-use chrono::{DateTime, Utc};
+use super::ReportType;
+use chrono::{DateTime, Duration, Utc};
 
 #[allow(non_snake_case)]
 #[derive(Debug, Default, PartialEq, Serialize, FromXmlStream)]
 pub struct ReportInfo {
-  pub ReportType: String,
+  pub ReportType: ReportType,
   pub Acknowledged: bool,
   pub AcknowledgedDate: Option<DateTime<Utc>>,
   pub ReportId: String,
@@ -25,7 +26,7 @@ str_enum! {
 #[derive(Debug, Default, PartialEq, Serialize, FromXmlStream)]
 pub struct ReportRequestInfo {
   pub ReportRequestId: String,
-  pub ReportType: String,
+  pub ReportType: ReportType,
   pub StartDate: Option<DateTime<Utc>>,
   pub EndDate: Option<DateTime<Utc>>,
   pub Scheduled: bool,
@@ -36,10 +37,146 @@ pub struct ReportRequestInfo {
   pub CompletedDate: Option<DateTime<Utc>>,
 }
 
+str_enum! {
+  /// How often a scheduled report is generated, `_NEVER_` deletes a schedule.
+  pub enum Schedule {
+    _15_MINUTES_,
+    _30_MINUTES_,
+    _1_HOUR_,
+    _2_HOURS_,
+    _4_HOURS_,
+    _8_HOURS_,
+    _12_HOURS_,
+    _1_DAY_,
+    _2_DAYS_,
+    _72_HOURS_,
+    _1_WEEK_,
+    _14_DAYS_,
+    _15_DAYS_,
+    _30_DAYS_,
+    _NEVER_,
+  }
+}
+
+impl Schedule {
+  /// The interval between two reports, `None` for `_NEVER_`.
+  pub fn to_duration(&self) -> Option<Duration> {
+    self.minutes().map(Duration::minutes)
+  }
+
+  /// Returns the schedule with an interval of exactly `duration`, if there is one.
+  pub fn from_duration(duration: Duration) -> Option<Schedule> {
+    let minutes = duration.num_minutes();
+    if Duration::minutes(minutes) != duration {
+      return None;
+    }
+    Some(match minutes {
+      15 => Schedule::_15_MINUTES_,
+      30 => Schedule::_30_MINUTES_,
+      60 => Schedule::_1_HOUR_,
+      120 => Schedule::_2_HOURS_,
+      240 => Schedule::_4_HOURS_,
+      480 => Schedule::_8_HOURS_,
+      720 => Schedule::_12_HOURS_,
+      1440 => Schedule::_1_DAY_,
+      2880 => Schedule::_2_DAYS_,
+      4320 => Schedule::_72_HOURS_,
+      10080 => Schedule::_1_WEEK_,
+      20160 => Schedule::_14_DAYS_,
+      21600 => Schedule::_15_DAYS_,
+      43200 => Schedule::_30_DAYS_,
+      _ => return None,
+    })
+  }
+
+  fn minutes(&self) -> Option<i64> {
+    Some(match *self {
+      Schedule::_15_MINUTES_ => 15,
+      Schedule::_30_MINUTES_ => 30,
+      Schedule::_1_HOUR_ => 60,
+      Schedule::_2_HOURS_ => 120,
+      Schedule::_4_HOURS_ => 240,
+      Schedule::_8_HOURS_ => 480,
+      Schedule::_12_HOURS_ => 720,
+      Schedule::_1_DAY_ => 1440,
+      Schedule::_2_DAYS_ => 2880,
+      Schedule::_72_HOURS_ => 4320,
+      Schedule::_1_WEEK_ => 10080,
+      Schedule::_14_DAYS_ => 20160,
+      Schedule::_15_DAYS_ => 21600,
+      Schedule::_30_DAYS_ => 43200,
+      Schedule::_NEVER_ | Schedule::UnknownValue(_) => return None,
+    })
+  }
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug, Default, PartialEq, Serialize, FromXmlStream)]
 pub struct ReportSchedule {
-  pub ReportType: String,
-  pub Schedule: String,
+  pub ReportType: ReportType,
+  pub Schedule: Schedule,
+  /// The date when the next report is scheduled to run.
   pub ScheduleDate: Option<DateTime<Utc>>
+}
+
+impl ReportSchedule {
+  /// The date the next scheduled report is expected to be available.
+  ///
+  /// That is one interval after `last_available_date`, the `AvailableDate` of the latest report
+  /// of the schedule, or `ScheduleDate` if there is no report yet. Returns `None` for `_NEVER_`.
+  pub fn next_available_date(
+    &self,
+    last_available_date: Option<DateTime<Utc>>,
+  ) -> Option<DateTime<Utc>> {
+    let interval = self.Schedule.to_duration()?;
+    match last_available_date {
+      Some(date) => Some(date + interval),
+      None => self.ScheduleDate,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  #[test]
+  fn test_schedule_duration() {
+    assert_eq!(
+      Schedule::_72_HOURS_.to_duration(),
+      Some(Duration::hours(72))
+    );
+    assert_eq!(Schedule::_NEVER_.to_duration(), None);
+    assert_eq!(
+      Schedule::from_duration(Duration::days(14)),
+      Some(Schedule::_14_DAYS_)
+    );
+    assert_eq!(Schedule::from_duration(Duration::days(3)), Some(Schedule::_72_HOURS_));
+    assert_eq!(Schedule::from_duration(Duration::minutes(45)), None);
+    assert_eq!(Schedule::from_duration(Duration::seconds(901)), None);
+  }
+
+  #[test]
+  fn test_next_available_date() {
+    let schedule = ReportSchedule {
+      ReportType: ReportType::FlatFileOpenListings,
+      Schedule: Schedule::_8_HOURS_,
+      ScheduleDate: Some(Utc.ymd(2019, 2, 20).and_hms(8, 0, 0)),
+    };
+    assert_eq!(
+      schedule.next_available_date(None),
+      Some(Utc.ymd(2019, 2, 20).and_hms(8, 0, 0))
+    );
+    assert_eq!(
+      schedule.next_available_date(Some(Utc.ymd(2019, 2, 20).and_hms(8, 12, 15))),
+      Some(Utc.ymd(2019, 2, 20).and_hms(16, 12, 15))
+    );
+
+    let schedule = ReportSchedule {
+      Schedule: Schedule::_NEVER_,
+      ..schedule
+    };
+    assert_eq!(schedule.next_available_date(None), None);
+  }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 pub use crate::client::ErrorResponse as MwsErrorResponse;
+pub use crate::client::MwsErrorCode;
+pub use crate::spapi::client::SpErrorResponse;
 
 #[derive(Fail, Debug)]
 pub enum MwsError {
@@ -16,6 +18,8 @@ pub enum MwsError {
   Utf8(#[cause] ::std::str::Utf8Error),
   #[fail(display = "MWS request is unsuccessful: {:?}", _0)]
   ErrorResponse(MwsErrorResponse),
+  #[fail(display = "SP-API request is unsuccessful: {:?}", _0)]
+  SpErrorResponse(SpErrorResponse),
   #[fail(display = "unexpected end of xml: {}", _0)]
   UnexpectedEndOfXml(String),
   #[fail(
@@ -25,10 +29,34 @@ pub enum MwsError {
   UnexpectedXmlEvent { expected: String, found: String },
   #[fail(display = "parse string error: {} : {}", what, message)]
   ParseString { what: String, message: String },
+  #[fail(display = "tdff error: {}", _0)]
+  Tdff(crate::tdff::TdffError),
   #[fail(display = "invalid path name: '{}'", _0)]
   InvalidPath(String),
   #[fail(display = "Content-MD5 header missing")]
   ContentMD5HeaderMissing,
+  #[fail(
+    display = "Content-MD5 mismatch: expected '{}', computed '{}' over {} bytes",
+    expected, actual, size
+  )]
+  ContentMD5Mismatch {
+    expected: String,
+    actual: String,
+    size: u64,
+  },
+  #[fail(display = "json error: {}", _0)]
+  Json(#[cause] ::serde_json::Error),
+  #[fail(
+    display = "LWA token request is unsuccessful: {} {}: {}",
+    status, error, description
+  )]
+  LwaError {
+    status: ::reqwest::StatusCode,
+    error: String,
+    description: String,
+  },
+  #[fail(display = "timed out waiting for {}", _0)]
+  Timeout(String),
   #[fail(display = "{}", _0)]
   Msg(String),
 }
@@ -37,16 +65,60 @@ impl MwsError {
   /// The common response to a 500 or 503 service error is
   /// to try the request again. Such service errors are
   /// usually only temporary and will resolve themselves.
+  ///
+  /// Retryable error codes (see `MwsErrorCode::is_retryable`),
+  /// IO errors and connection failures are worth retrying too,
+  /// as are downloads which do not match their `Content-MD5` header.
   pub fn should_try_again(&self) -> bool {
     match *self {
       MwsError::ErrorResponse(ref res) => {
         let code = res.status.as_u16();
-        code >= 500 && code < 600
+        (code >= 500 && code < 600) || self.error_codes().any(MwsErrorCode::is_retryable)
+      }
+      MwsError::SpErrorResponse(ref res) => {
+        res.status == ::reqwest::StatusCode::TOO_MANY_REQUESTS || res.status.is_server_error()
       }
+      MwsError::LwaError { status, .. } => status.is_server_error(),
       MwsError::Io(_) => true,
+      MwsError::ContentMD5Mismatch { .. } => true,
+      MwsError::Http(ref err) => err.is_timeout() || err.is_connect(),
       _ => false,
     }
   }
+
+  /// Returns the error codes of an unsuccessful MWS response.
+  pub fn error_codes(&self) -> impl Iterator<Item = &MwsErrorCode> + '_ {
+    let errors = match *self {
+      MwsError::ErrorResponse(ref res) => res.info.as_ref().map(|info| &info.errors[..]),
+      _ => None,
+    };
+    errors.unwrap_or(&[]).iter().map(|e| &e.code)
+  }
+
+  /// Returns the first error code of an unsuccessful MWS response.
+  pub fn error_code(&self) -> Option<&MwsErrorCode> {
+    self.error_codes().next()
+  }
+
+  /// Returns `true` if MWS rejected the request with a
+  /// `RequestThrottled` or `QuotaExceeded` error code,
+  /// or the Selling Partner API with `429 Too Many Requests`.
+  pub fn is_throttled(&self) -> bool {
+    match *self {
+      MwsError::SpErrorResponse(ref res) => res.status == ::reqwest::StatusCode::TOO_MANY_REQUESTS,
+      _ => self.error_codes().any(MwsErrorCode::is_throttling),
+    }
+  }
+
+  /// Returns `true` if MWS rejected the credentials or the signature of the request.
+  pub fn is_auth_error(&self) -> bool {
+    self.error_codes().any(MwsErrorCode::is_auth)
+  }
+
+  /// Returns `true` if MWS rejected the request parameters or content.
+  pub fn is_client_input_error(&self) -> bool {
+    self.error_codes().any(MwsErrorCode::is_client_input)
+  }
 }
 
 macro_rules! impl_from {
@@ -65,6 +137,7 @@ impl_from!(XmlReader(::xml::reader::Error));
 impl_from!(XmlWriter(::xml::writer::Error));
 impl_from!(Csv(::csv::Error));
 impl_from!(Utf8(::std::str::Utf8Error));
+impl_from!(Json(::serde_json::Error));
 impl_from!(Msg(String));
 
 pub type MwsResult<T> = Result<T, MwsError>;
@@ -0,0 +1,142 @@
+//! Retry policy used by `client::Client` and `client::AsyncClient`
+//!
+//! Requests failing with an error for which `MwsError::should_try_again` returns `true`
+//! are signed and sent again after an exponentially growing delay.
+
+use crate::result::{MwsError, MwsResult};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Exponential backoff settings
+///
+/// The delay before retry `n` (starting from 1) is `base_delay * 2^(n-1)`, capped at `max_delay`.
+/// With `jitter` enabled a random delay between zero and that value is used instead.
+///
+/// Retries are disabled by default, set `max_attempts` above 1 to enable them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+  /// Total number of attempts, including the first one.
+  pub max_attempts: u32,
+  /// The delay before the first retry.
+  pub base_delay: Duration,
+  /// Upper bound of the delay between two attempts.
+  pub max_delay: Duration,
+  /// Randomize delays so that concurrent clients do not retry in lockstep.
+  pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    RetryPolicy {
+      max_attempts: 1,
+      base_delay: Duration::from_secs(1),
+      max_delay: Duration::from_secs(60),
+      jitter: true,
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// A policy with the default delays which makes up to `max_attempts` attempts.
+  pub fn with_max_attempts(max_attempts: u32) -> Self {
+    RetryPolicy {
+      max_attempts,
+      ..Default::default()
+    }
+  }
+
+  /// Returns `true` if a request failed with `err` on attempt `attempt` (starting from 1)
+  /// should be sent again.
+  pub fn should_retry(&self, attempt: u32, err: &MwsError) -> bool {
+    attempt < self.max_attempts && err.should_try_again()
+  }
+
+  /// Returns the delay before retrying a request which failed on attempt `attempt` (starting from 1).
+  pub fn delay(&self, attempt: u32) -> Duration {
+    let exp = attempt.saturating_sub(1).min(31);
+    let delay = self
+      .base_delay
+      .checked_mul(1 << exp)
+      .unwrap_or(self.max_delay)
+      .min(self.max_delay);
+    if self.jitter {
+      let millis = delay.as_millis() as u64;
+      Duration::from_millis(random_u64(attempt) % (millis + 1))
+    } else {
+      delay
+    }
+  }
+
+  /// Calls `f` until it succeeds, fails with an error which should not be retried,
+  /// or `max_attempts` is reached, waiting `delay` between attempts.
+  ///
+  /// Useful for operations which fail after the request, e.g. downloading a report again after
+  /// `MwsError::ContentMD5Mismatch`. `f` receives the attempt number, starting from 1.
+  pub fn run<T, F>(&self, mut f: F) -> MwsResult<T>
+  where
+    F: FnMut(u32) -> MwsResult<T>,
+  {
+    let mut attempt = 1;
+    loop {
+      match f(attempt) {
+        Err(ref err) if self.should_retry(attempt, err) => {
+          thread::sleep(self.delay(attempt));
+          attempt += 1;
+        }
+        res => return res,
+      }
+    }
+  }
+}
+
+// A pseudo-random number, good enough to spread out retries.
+fn random_u64(seed: u32) -> u64 {
+  let mut hasher = RandomState::new().build_hasher();
+  seed.hash(&mut hasher);
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.subsec_nanos())
+    .unwrap_or(0)
+    .hash(&mut hasher);
+  hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_delay() {
+    let policy = RetryPolicy {
+      max_attempts: 10,
+      base_delay: Duration::from_millis(100),
+      max_delay: Duration::from_millis(1000),
+      jitter: false,
+    };
+    let delays: Vec<_> = (1..7).map(|n| policy.delay(n).as_millis()).collect();
+    assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    assert_eq!(policy.delay(100), Duration::from_millis(1000));
+
+    let policy = RetryPolicy {
+      jitter: true,
+      ..policy
+    };
+    for n in 1..10 {
+      assert!(policy.delay(n) <= Duration::from_millis(1000));
+    }
+  }
+
+  #[test]
+  fn test_should_retry() {
+    use std::io;
+    let policy = RetryPolicy::with_max_attempts(3);
+    let err = MwsError::Io(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+    assert!(policy.should_retry(1, &err));
+    assert!(policy.should_retry(2, &err));
+    assert!(!policy.should_retry(3, &err));
+    assert!(!policy.should_retry(1, &MwsError::Msg("error".to_owned())));
+    assert!(!RetryPolicy::default().should_retry(1, &err));
+  }
+}
@@ -3,6 +3,9 @@
 //! http://docs.developer.amazonservices.com/en_CA/dev_guide/DG_SigningQueryRequest.html
 //! http://docs.aws.amazon.com/general/latest/gr/signature-version-2.html
 //!
+//! Signature Version 4 is implemented in `v4`.
+
+pub mod v4;
 
 pub use reqwest::Method;
 use crate::result::{MwsError, MwsResult};
@@ -55,6 +58,7 @@ impl Ord for Param {
 }
 
 pub struct SignedUrl<'a> {
+  pub scheme: &'a str,
   pub host: &'a str,
   pub method: Method,
   pub path: String,
@@ -71,7 +75,12 @@ impl<'a> Into<String> for SignedUrl<'a> {
 
 impl<'a> SignedUrl<'a> {
   pub fn get_url_without_query(&self) -> String {
-    format!("https://{host}{path}", host = &self.host, path = self.path,)
+    format!(
+      "{scheme}://{host}{path}",
+      scheme = self.scheme,
+      host = &self.host,
+      path = self.path,
+    )
   }
 
   pub fn to_string(self) -> String {
@@ -81,7 +90,8 @@ impl<'a> SignedUrl<'a> {
       signature_encoded.push_str(part);
     }
     format!(
-      "https://{host}{path}?{qs}&Signature={signature}",
+      "{scheme}://{host}{path}?{qs}&Signature={signature}",
+      scheme = self.scheme,
       host = &self.host,
       path = self.path,
       qs = self.query_string,
@@ -93,6 +103,7 @@ impl<'a> SignedUrl<'a> {
 /// Signature V2 generator
 #[derive(Debug, Clone)]
 pub struct SignatureV2 {
+  scheme: String,
   host: String,
   aws_access_key_id: String,
   secret_key: String,
@@ -102,14 +113,21 @@ pub struct SignatureV2 {
 
 impl SignatureV2 {
   /// Constructs a new, empty generator
+  ///
+  /// `host` may be prefixed with `http://` or `https://`, the default scheme is `https`.
   pub fn new(
     host: &str,
     aws_access_key_id: &str,
     secret_key: &str,
     auth_token: Option<&str>,
   ) -> SignatureV2 {
+    let (scheme, host) = match host.find("://") {
+      Some(pos) => (&host[..pos], &host[(pos + 3)..]),
+      None => ("https", host),
+    };
     SignatureV2 {
-      host: host.into(),
+      scheme: scheme.to_string(),
+      host: host.trim_end_matches('/').to_string(),
       aws_access_key_id: aws_access_key_id.to_string(),
       secret_key: secret_key.to_string(),
       auth_token: auth_token.map(ToString::to_string),
@@ -201,6 +219,7 @@ impl SignatureV2 {
     };
 
     Ok(SignedUrl {
+      scheme: &self.scheme,
       host: &self.host,
       method: method,
       path: path_str.to_string(),
@@ -238,4 +257,20 @@ mod tests {
       "e7NJFMRLOOpRUp0IP42irtpKzq404KDbjZpKZ/OWRLI="
     );
   }
+
+  #[test]
+  fn test_endpoint_scheme() {
+    let s = SignatureV2::new("http://127.0.0.1:8080", "3333", "0000", None);
+    let url = s
+      .generate_url(Method::POST, "/", "2009-01-01", "GetReport")
+      .expect("generate url");
+    assert_eq!(url.get_url_without_query(), "http://127.0.0.1:8080/");
+    assert!(url.to_string().starts_with("http://127.0.0.1:8080/?"));
+
+    let s = SignatureV2::new("mws.amazonservices.com", "3333", "0000", None);
+    let url = s
+      .generate_url(Method::POST, "/", "2009-01-01", "GetReport")
+      .expect("generate url");
+    assert_eq!(url.get_url_without_query(), "https://mws.amazonservices.com/");
+  }
 }
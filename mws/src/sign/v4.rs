@@ -0,0 +1,443 @@
+//! Implements AWS Signature Version 4 Signing
+//!
+//! https://docs.aws.amazon.com/general/latest/gr/signature-version-4.html
+//!
+//! Unlike Signature Version 2 the signature is sent in the `Authorization` header,
+//! the query string and the body of the request are left unchanged.
+
+use super::ParameterEncodeSet;
+use crate::result::{MwsError, MwsResult};
+use crate::transport::{Request, RequestBody};
+use chrono::{DateTime, Utc};
+use crypto::digest::Digest;
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha2::Sha256;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, HOST};
+pub use reqwest::Method;
+use url::percent_encoding::percent_encode;
+use url::Url;
+
+pub const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+const X_AMZ_DATE: &str = "x-amz-date";
+const X_AMZ_SECURITY_TOKEN: &str = "x-amz-security-token";
+
+/// Canonical request and the list of headers it includes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalRequest {
+  pub request: String,
+  /// Lowercase header names joined by `;`
+  pub signed_headers: String,
+}
+
+/// Signature V4 generator
+#[derive(Debug, Clone)]
+pub struct SignatureV4 {
+  access_key_id: String,
+  secret_key: String,
+  session_token: Option<String>,
+  region: String,
+  service: String,
+}
+
+impl SignatureV4 {
+  /// Constructs a generator for `service` in `region`, e.g. `execute-api` in `us-east-1`.
+  pub fn new(access_key_id: &str, secret_key: &str, region: &str, service: &str) -> SignatureV4 {
+    SignatureV4 {
+      access_key_id: access_key_id.to_string(),
+      secret_key: secret_key.to_string(),
+      session_token: None,
+      region: region.to_string(),
+      service: service.to_string(),
+    }
+  }
+
+  /// Sends the session token of temporary credentials in the `X-Amz-Security-Token` header.
+  pub fn session_token(mut self, session_token: &str) -> SignatureV4 {
+    self.session_token = Some(session_token.to_string());
+    self
+  }
+
+  /// Adds the `Host`, `X-Amz-Date`, `X-Amz-Security-Token` and `Authorization` headers.
+  ///
+  /// Every header present in `headers` is signed.
+  pub fn sign(
+    &self,
+    method: &Method,
+    url: &str,
+    headers: &mut HeaderMap,
+    payload: &[u8],
+    date: DateTime<Utc>,
+  ) -> MwsResult<()> {
+    let url =
+      Url::parse(url).map_err(|err| MwsError::Msg(format!("invalid url '{}': {}", url, err)))?;
+    let amz_date = date.format("%Y%m%dT%H%M%SZ").to_string();
+
+    if !headers.contains_key(HOST) {
+      let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(MwsError::Msg(format!("url without host: '{}'", url))),
+      };
+      headers.insert(HOST, header_value(&host)?);
+    }
+    headers.insert(X_AMZ_DATE, header_value(&amz_date)?);
+    if let Some(ref session_token) = self.session_token {
+      let mut session_token = header_value(session_token)?;
+      session_token.set_sensitive(true);
+      headers.insert(X_AMZ_SECURITY_TOKEN, session_token);
+    }
+    headers.remove(AUTHORIZATION);
+
+    let canonical = canonical_request(method, &url, headers, &hex_sha256(payload));
+    let scope = format!(
+      "{}/{}/{}/aws4_request",
+      date.format("%Y%m%d"),
+      self.region,
+      self.service
+    );
+    let key = signing_key(
+      &self.secret_key,
+      &date.format("%Y%m%d").to_string(),
+      &self.region,
+      &self.service,
+    );
+    let signature = to_hex(&hmac_sha256(
+      &key,
+      string_to_sign(&amz_date, &scope, &canonical.request).as_bytes(),
+    ));
+    let authorization = format!(
+      "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+      ALGORITHM, self.access_key_id, scope, canonical.signed_headers, signature
+    );
+    headers.insert(AUTHORIZATION, header_value(&authorization)?);
+    Ok(())
+  }
+
+  /// Signs a request built for a `Transport`.
+  ///
+  /// A `Form` body is encoded into bytes first, so the signed payload is exactly what is sent.
+  /// `Reader` bodies can not be signed.
+  pub fn sign_request(&self, request: &mut Request, date: DateTime<Utc>) -> MwsResult<()> {
+    if let RequestBody::Form(ref pairs) = request.body {
+      let encoded = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs.iter())
+        .finish();
+      request.headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-www-form-urlencoded"),
+      );
+      request.body = RequestBody::Bytes(encoded.into_bytes());
+    }
+    let payload: &[u8] = match request.body {
+      RequestBody::Empty => &[],
+      RequestBody::Bytes(ref bytes) => bytes,
+      RequestBody::Form(_) => unreachable!(),
+      RequestBody::Reader(_) => {
+        return Err(MwsError::Msg(
+          "a streaming request body can not be signed".to_owned(),
+        ))
+      }
+    };
+    self.sign(
+      &request.method,
+      &request.url,
+      &mut request.headers,
+      payload,
+      date,
+    )
+  }
+}
+
+/// Builds the canonical request of Task 1.
+///
+/// `payload_hash` is the lowercase hex SHA-256 digest of the body.
+pub fn canonical_request(
+  method: &Method,
+  url: &Url,
+  headers: &HeaderMap,
+  payload_hash: &str,
+) -> CanonicalRequest {
+  let mut query: Vec<(String, String)> = url
+    .query_pairs()
+    .map(|(k, v)| (encode(&k), encode(&v)))
+    .collect();
+  query.sort();
+  let query = query
+    .into_iter()
+    .map(|(k, v)| format!("{}={}", k, v))
+    .collect::<Vec<_>>()
+    .join("&");
+
+  let mut names: Vec<&str> = headers.keys().map(|name| name.as_str()).collect();
+  names.sort();
+  names.dedup();
+  let mut canonical_headers = String::new();
+  for name in &names {
+    let values: Vec<String> = headers
+      .get_all(*name)
+      .iter()
+      .map(|v| trim_value(&String::from_utf8_lossy(v.as_bytes())))
+      .collect();
+    canonical_headers.push_str(name);
+    canonical_headers.push(':');
+    canonical_headers.push_str(&values.join(","));
+    canonical_headers.push('\n');
+  }
+  let signed_headers = names.join(";");
+
+  CanonicalRequest {
+    request: format!(
+      "{}\n{}\n{}\n{}\n{}\n{}",
+      method,
+      canonical_path(url.path()),
+      query,
+      canonical_headers,
+      signed_headers,
+      payload_hash
+    ),
+    signed_headers,
+  }
+}
+
+/// Builds the string to sign of Task 2.
+///
+/// `amz_date` is formatted as `20150830T123600Z`, `scope` as `20150830/us-east-1/iam/aws4_request`.
+pub fn string_to_sign(amz_date: &str, scope: &str, canonical_request: &str) -> String {
+  format!(
+    "{}\n{}\n{}\n{}",
+    ALGORITHM,
+    amz_date,
+    scope,
+    hex_sha256(canonical_request.as_bytes())
+  )
+}
+
+/// Derives the signing key of Task 3, `date` is formatted as `20150830`.
+pub fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+  let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+  let key = hmac_sha256(&key, region.as_bytes());
+  let key = hmac_sha256(&key, service.as_bytes());
+  hmac_sha256(&key, b"aws4_request")
+}
+
+/// Lowercase hex SHA-256 digest
+pub fn hex_sha256(data: &[u8]) -> String {
+  let mut digest = Sha256::new();
+  digest.input(data);
+  digest.result_str()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut hmac = Hmac::new(Sha256::new(), key);
+  hmac.input(data);
+  hmac.result().code().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn encode(value: &str) -> String {
+  percent_encode(value.as_bytes(), ParameterEncodeSet).to_string()
+}
+
+fn header_value(value: &str) -> MwsResult<HeaderValue> {
+  HeaderValue::from_str(value)
+    .map_err(|_| MwsError::Msg(format!("invalid header value: '{}'", value)))
+}
+
+// Removes the leading and trailing spaces and collapses sequential spaces.
+fn trim_value(value: &str) -> String {
+  value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Normalizes the path and encodes every segment, the path of `Url` is already encoded once
+// and services other than S3 expect it to be encoded twice.
+fn canonical_path(path: &str) -> String {
+  let mut segments: Vec<&str> = vec![];
+  for segment in path.split('/') {
+    match segment {
+      "" | "." => {}
+      ".." => {
+        segments.pop();
+      }
+      segment => segments.push(segment),
+    }
+  }
+  let mut canonical = String::from("/");
+  canonical.push_str(
+    &segments
+      .iter()
+      .map(|segment| encode(segment))
+      .collect::<Vec<_>>()
+      .join("/"),
+  );
+  if !segments.is_empty() && path.ends_with('/') {
+    canonical.push('/');
+  }
+  canonical
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // https://docs.aws.amazon.com/general/latest/gr/signature-v4-test-suite.html
+  const ACCESS_KEY_ID: &str = "AKIDEXAMPLE";
+  const SECRET_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+  fn test_date() -> DateTime<Utc> {
+    "2015-08-30T12:36:00Z".parse().expect("parse date")
+  }
+
+  fn sign_test_request(method: Method, url: &str, body: &[u8]) -> HeaderMap {
+    let signer = SignatureV4::new(ACCESS_KEY_ID, SECRET_KEY, "us-east-1", "service");
+    let mut headers = HeaderMap::new();
+    signer
+      .sign(&method, url, &mut headers, body, test_date())
+      .expect("sign request");
+    headers
+  }
+
+  fn authorization(headers: &HeaderMap) -> &str {
+    headers.get(AUTHORIZATION).unwrap().to_str().unwrap()
+  }
+
+  #[test]
+  fn test_get_vanilla() {
+    let headers = sign_test_request(Method::GET, "https://example.amazonaws.com/", b"");
+    assert_eq!(headers.get(HOST).unwrap(), "example.amazonaws.com");
+    assert_eq!(headers.get(X_AMZ_DATE).unwrap(), "20150830T123600Z");
+    assert_eq!(
+      authorization(&headers),
+      "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+       SignedHeaders=host;x-amz-date, \
+       Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+    );
+  }
+
+  #[test]
+  fn test_post_vanilla() {
+    let headers = sign_test_request(Method::POST, "https://example.amazonaws.com/", b"");
+    assert_eq!(
+      authorization(&headers),
+      "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+       SignedHeaders=host;x-amz-date, \
+       Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+    );
+  }
+
+  #[test]
+  fn test_get_vanilla_query_order_key_case() {
+    let headers = sign_test_request(
+      Method::GET,
+      "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+      b"",
+    );
+    assert_eq!(
+      authorization(&headers),
+      "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+       SignedHeaders=host;x-amz-date, \
+       Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+    );
+  }
+
+  #[test]
+  fn test_post_x_www_form_urlencoded() {
+    let signer = SignatureV4::new(ACCESS_KEY_ID, SECRET_KEY, "us-east-1", "service");
+    let mut request = Request::new(Method::POST, "https://example.amazonaws.com/".to_owned());
+    request.body = RequestBody::Form(vec![("Param1".to_owned(), "value1".to_owned())]);
+    signer
+      .sign_request(&mut request, test_date())
+      .expect("sign request");
+    match request.body {
+      RequestBody::Bytes(ref bytes) => assert_eq!(bytes, b"Param1=value1"),
+      ref other => panic!("unexpected body: {:?}", other),
+    }
+    assert_eq!(
+      authorization(&request.headers),
+      "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+       SignedHeaders=content-type;host;x-amz-date, \
+       Signature=ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+    );
+  }
+
+  // https://docs.aws.amazon.com/general/latest/gr/sigv4-create-canonical-request.html
+  #[test]
+  fn test_iam_example() {
+    let url = Url::parse("https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      CONTENT_TYPE,
+      HeaderValue::from_static("application/x-www-form-urlencoded; charset=utf-8"),
+    );
+    headers.insert(HOST, HeaderValue::from_static("iam.amazonaws.com"));
+    headers.insert(X_AMZ_DATE, HeaderValue::from_static("20150830T123600Z"));
+
+    let canonical = canonical_request(&Method::GET, &url, &headers, &hex_sha256(b""));
+    assert_eq!(
+      canonical.request,
+      "GET\n\
+       /\n\
+       Action=ListUsers&Version=2010-05-08\n\
+       content-type:application/x-www-form-urlencoded; charset=utf-8\n\
+       host:iam.amazonaws.com\n\
+       x-amz-date:20150830T123600Z\n\
+       \n\
+       content-type;host;x-amz-date\n\
+       e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+      hex_sha256(canonical.request.as_bytes()),
+      "f536975d06c0309214f805bb90ccff089219ecd68b2577efef23edd43b7e1a59"
+    );
+
+    let key = signing_key(SECRET_KEY, "20150830", "us-east-1", "iam");
+    assert_eq!(
+      to_hex(&key),
+      "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+    );
+
+    let string_to_sign = string_to_sign(
+      "20150830T123600Z",
+      "20150830/us-east-1/iam/aws4_request",
+      &canonical.request,
+    );
+    assert_eq!(
+      to_hex(&hmac_sha256(&key, string_to_sign.as_bytes())),
+      "5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+    );
+  }
+
+  #[test]
+  fn test_session_token() {
+    let signer =
+      SignatureV4::new(ACCESS_KEY_ID, SECRET_KEY, "us-east-1", "service").session_token("TOKEN");
+    let mut headers = HeaderMap::new();
+    signer
+      .sign(
+        &Method::GET,
+        "https://example.amazonaws.com/",
+        &mut headers,
+        b"",
+        test_date(),
+      )
+      .expect("sign request");
+    assert_eq!(headers.get(X_AMZ_SECURITY_TOKEN).unwrap(), "TOKEN");
+    assert!(authorization(&headers).contains("SignedHeaders=host;x-amz-date;x-amz-security-token,"));
+    assert!(!format!("{:?}", headers).contains("TOKEN"));
+  }
+
+  #[test]
+  fn test_canonical_path() {
+    assert_eq!(canonical_path(""), "/");
+    assert_eq!(canonical_path("/"), "/");
+    assert_eq!(canonical_path("//example//"), "/example/");
+    assert_eq!(canonical_path("/example1/example2/../.."), "/");
+    assert_eq!(canonical_path("/./example/."), "/example");
+    assert_eq!(canonical_path("/orders/v0/orders"), "/orders/v0/orders");
+    assert_eq!(canonical_path("/ab%20c"), "/ab%2520c");
+  }
+}
@@ -0,0 +1,410 @@
+//! HTTP client of the Selling Partner API
+//!
+//! Every request carries an LWA access token in the `x-amz-access-token` header,
+//! and is signed with Signature Version 4 if AWS credentials are configured.
+//! Restricted operations use a Restricted Data Token instead, see `tokens`.
+
+use super::lwa::{Grant, LwaOptions, TokenProvider};
+use super::tokens::{createRestrictedDataToken, RdtCache, RestrictedResource};
+use crate::result::{MwsError, MwsResult};
+use crate::retry::RetryPolicy;
+use crate::sign::v4::SignatureV4;
+use crate::transport::{Request, RequestBody, ReqwestTransport, Response, Transport};
+use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+pub use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// [Reference](https://developer-docs.amazon.com/sp-api/docs/sp-api-endpoints)
+#[derive(Clone, Default)]
+pub struct SpClientOptions {
+  /// The regional endpoint, e.g. `https://sellingpartnerapi-na.amazon.com`.
+  pub endpoint: String,
+
+  /// The AWS region of the endpoint, e.g. `us-east-1`.
+  pub region: String,
+
+  /// The credentials of the IAM user or role registered with your application.
+  /// Requests are not signed if `aws_access_key_id` is empty.
+  pub aws_access_key_id: String,
+  pub secret_key: String,
+  /// The session token of temporary credentials, e.g. of an assumed role.
+  pub session_token: Option<String>,
+
+  /// Credentials used to obtain access tokens.
+  pub lwa: LwaOptions,
+
+  /// How `SpClient` retries failed requests. Retries are disabled by default.
+  pub retry_policy: RetryPolicy,
+
+  /// The maximum number of cached Restricted Data Tokens, `tokens::DEFAULT_RDT_CACHE_CAPACITY` by default.
+  pub rdt_cache_capacity: Option<usize>,
+}
+
+impl fmt::Debug for SpClientOptions {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("SpClientOptions")
+      .field("endpoint", &self.endpoint)
+      .field("region", &self.region)
+      .field("aws_access_key_id", &self.aws_access_key_id)
+      .field("secret_key", &"<redacted>")
+      .field(
+        "session_token",
+        &self.session_token.as_ref().map(|_| "<redacted>"),
+      )
+      .field("lwa", &self.lwa)
+      .field("retry_policy", &self.retry_policy)
+      .field("rdt_cache_capacity", &self.rdt_cache_capacity)
+      .finish()
+  }
+}
+
+/// Metadata returned in the headers of every SP-API response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpResponseMeta {
+  /// The `x-amzn-RequestId` header, Amazon support asks for it when investigating a problem.
+  pub request_id: String,
+  /// The `x-amzn-RateLimit-Limit` header, requests per second allowed for the operation.
+  pub rate_limit: Option<f64>,
+}
+
+impl SpResponseMeta {
+  pub fn from_headers(headers: &HeaderMap) -> SpResponseMeta {
+    fn get<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+      headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    SpResponseMeta {
+      request_id: get(headers, "x-amzn-requestid")
+        .unwrap_or_default()
+        .to_owned(),
+      rate_limit: get(headers, "x-amzn-ratelimit-limit").and_then(|v| v.parse().ok()),
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct SpErrorResponse {
+  pub status: StatusCode,
+  pub errors: Vec<SpError>,
+  pub raw: String,
+  pub meta: SpResponseMeta,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SpError {
+  pub code: String,
+  pub message: String,
+  pub details: String,
+}
+
+/// The `payload` wrapper of v0 operations
+#[derive(Debug, Deserialize)]
+pub struct Payload<T> {
+  pub payload: T,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ErrorList {
+  errors: Vec<SpError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpClient {
+  options: Arc<SpClientOptions>,
+  token_provider: TokenProvider,
+  transport: Arc<dyn Transport>,
+  rdt_cache: RdtCache,
+  /// Receives the metadata of every response, set by `with_meta`.
+  last_meta: Option<Arc<Mutex<Option<SpResponseMeta>>>>,
+}
+
+impl SpClient {
+  pub fn new(options: SpClientOptions) -> SpClient {
+    SpClient::with_transport(options, ReqwestTransport::default())
+  }
+
+  /// Constructs a client which sends requests, including token requests, through `transport`
+  pub fn with_transport<T>(options: SpClientOptions, transport: T) -> SpClient
+  where
+    T: Transport + Clone + 'static,
+  {
+    let token_provider = TokenProvider::with_transport(options.lwa.clone(), transport.clone());
+    let rdt_cache = options
+      .rdt_cache_capacity
+      .map(RdtCache::new)
+      .unwrap_or_default();
+    SpClient {
+      options: Arc::new(options),
+      token_provider,
+      transport: Arc::new(transport),
+      rdt_cache,
+      last_meta: None,
+    }
+  }
+
+  pub fn token_provider(&self) -> &TokenProvider {
+    &self.token_provider
+  }
+
+  pub fn rdt_cache(&self) -> &RdtCache {
+    &self.rdt_cache
+  }
+
+  /// Returns the cached Restricted Data Token of `resource`, or requests a new one.
+  pub fn restricted_data_token(&self, resource: &RestrictedResource) -> MwsResult<String> {
+    if let Some(token) = self.rdt_cache.get(resource) {
+      return Ok(token);
+    }
+    let res = createRestrictedDataToken(self, ::std::slice::from_ref(resource))?;
+    self.rdt_cache.insert(
+      resource.clone(),
+      res.restricted_data_token.clone(),
+      Duration::from_secs(res.expires_in),
+    );
+    Ok(res.restricted_data_token)
+  }
+
+  /// Calls `f` with a client which records the metadata of the responses it receives,
+  /// and returns the value returned by `f` along with the metadata of the last response.
+  ///
+  /// Failed requests carry their metadata in `MwsError::SpErrorResponse`.
+  pub fn with_meta<F, T>(&self, f: F) -> MwsResult<(T, SpResponseMeta)>
+  where
+    F: FnOnce(&SpClient) -> MwsResult<T>,
+  {
+    let last_meta = Arc::new(Mutex::new(None));
+    let client = SpClient {
+      last_meta: Some(last_meta.clone()),
+      ..self.clone()
+    };
+    let value = f(&client)?;
+    let meta = last_meta
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .take()
+      .unwrap_or_default();
+    Ok((value, meta))
+  }
+
+  /// Sends a request with an access token, retried according to `SpClientOptions::retry_policy`.
+  ///
+  /// `query` pairs are appended to `path`, `body` is sent as `application/json`.
+  pub fn request(
+    &self,
+    method: Method,
+    path: &str,
+    query: &[(&str, String)],
+    body: Option<Vec<u8>>,
+  ) -> MwsResult<Response> {
+    self.send_with_retry(method, path, query, body, None)
+  }
+
+  /// Sends a request to a restricted operation with a Restricted Data Token for the PII
+  /// in `data_elements`, see `tokens::RestrictedResource`.
+  pub fn request_restricted(
+    &self,
+    method: Method,
+    path: &str,
+    query: &[(&str, String)],
+    data_elements: &[&str],
+  ) -> MwsResult<Response> {
+    let resource = RestrictedResource::new(method.clone(), path, data_elements);
+    self.send_with_retry(method, path, query, None, Some(&resource))
+  }
+
+  /// Sends a request to a restricted operation and decodes the JSON response body.
+  pub fn request_json_restricted<T>(
+    &self,
+    method: Method,
+    path: &str,
+    query: &[(&str, String)],
+    data_elements: &[&str],
+  ) -> MwsResult<T>
+  where
+    T: DeserializeOwned,
+  {
+    let resp = self.request_restricted(method, path, query, data_elements)?;
+    Ok(serde_json::from_reader(resp)?)
+  }
+
+  /// Downloads a pre-signed URL, e.g. of a report document.
+  ///
+  /// The URL carries its own credentials, so the request is neither signed nor sent with a token.
+  pub fn download(&self, url: &str) -> MwsResult<Response> {
+    self.send_presigned(Method::GET, url, None)
+  }
+
+  /// Uploads `body` to a pre-signed URL, e.g. of a feed document.
+  ///
+  /// `content_type` must match the content type the URL was created for.
+  pub fn upload(&self, url: &str, content_type: &str, body: Vec<u8>) -> MwsResult<()> {
+    self
+      .send_presigned(Method::PUT, url, Some((content_type, body)))
+      .map(|_| ())
+  }
+
+  fn send_presigned(
+    &self,
+    method: Method,
+    url: &str,
+    body: Option<(&str, Vec<u8>)>,
+  ) -> MwsResult<Response> {
+    let policy = &self.options.retry_policy;
+    let mut attempt = 1;
+    loop {
+      let mut request = Request::new(method.clone(), url.to_owned());
+      if let Some((content_type, ref body)) = body {
+        request.headers.insert(
+          CONTENT_TYPE,
+          HeaderValue::from_str(content_type)
+            .map_err(|_| MwsError::Msg(format!("invalid content type: '{}'", content_type)))?,
+        );
+        request.body = RequestBody::Bytes(body.clone());
+      }
+      let res = self.transport.send(request).and_then(|resp| {
+        if resp.status().is_success() {
+          return Ok(resp);
+        }
+        let status = resp.status();
+        let meta = SpResponseMeta::from_headers(resp.headers());
+        let mut resp = resp;
+        let mut raw = String::new();
+        resp.read_to_string(&mut raw)?;
+        Err(MwsError::SpErrorResponse(SpErrorResponse {
+          status,
+          errors: vec![],
+          raw,
+          meta,
+        }))
+      });
+      match res {
+        Err(ref err) if policy.should_retry(attempt, err) => {
+          thread::sleep(policy.delay(attempt));
+          attempt += 1;
+        }
+        res => return res,
+      }
+    }
+  }
+
+  fn send_with_retry(
+    &self,
+    method: Method,
+    path: &str,
+    query: &[(&str, String)],
+    body: Option<Vec<u8>>,
+    restricted: Option<&RestrictedResource>,
+  ) -> MwsResult<Response> {
+    let mut url = format!("{}{}", self.options.endpoint.trim_end_matches('/'), path);
+    if !query.is_empty() {
+      let qs = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(query.iter().map(|&(k, ref v)| (k, v)))
+        .finish();
+      url.push('?');
+      url.push_str(&qs);
+    }
+
+    let policy = &self.options.retry_policy;
+    let mut attempt = 1;
+    loop {
+      let mut request = Request::new(method.clone(), url.clone());
+      if let Some(ref body) = body {
+        request
+          .headers
+          .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        request.body = RequestBody::Bytes(body.clone());
+      }
+      match self.send(request, restricted) {
+        Err(ref err) if policy.should_retry(attempt, err) => {
+          thread::sleep(policy.delay(attempt));
+          attempt += 1;
+        }
+        res => return res,
+      }
+    }
+  }
+
+  /// Sends a request and decodes the JSON response body.
+  pub fn request_json<T>(
+    &self,
+    method: Method,
+    path: &str,
+    query: &[(&str, String)],
+    body: Option<Vec<u8>>,
+  ) -> MwsResult<T>
+  where
+    T: DeserializeOwned,
+  {
+    let resp = self.request(method, path, query, body)?;
+    Ok(serde_json::from_reader(resp)?)
+  }
+
+  fn send(
+    &self,
+    mut request: Request,
+    restricted: Option<&RestrictedResource>,
+  ) -> MwsResult<Response> {
+    let access_token = match restricted {
+      Some(resource) => self.restricted_data_token(resource)?,
+      None => self.token_provider.access_token()?,
+    };
+    let mut access_token = HeaderValue::from_str(&access_token)
+      .map_err(|_| MwsError::Msg("invalid access token".to_owned()))?;
+    // keeps the token out of the `Debug` output of the request
+    access_token.set_sensitive(true);
+    request.headers.insert("x-amz-access-token", access_token);
+    let options = &self.options;
+    if !options.aws_access_key_id.is_empty() {
+      let mut signer = SignatureV4::new(
+        &options.aws_access_key_id,
+        &options.secret_key,
+        &options.region,
+        "execute-api",
+      );
+      if let Some(ref session_token) = options.session_token {
+        signer = signer.session_token(session_token);
+      }
+      signer.sign_request(&mut request, Utc::now())?;
+    }
+
+    let resp = self.transport.send(request)?;
+    let meta = SpResponseMeta::from_headers(resp.headers());
+    if let Some(ref last_meta) = self.last_meta {
+      *last_meta
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(meta.clone());
+    }
+    if resp.status().is_success() {
+      return Ok(resp);
+    }
+
+    let status = resp.status();
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+      // the token may have been revoked, request a new one next time
+      match restricted {
+        Some(resource) => self.rdt_cache.invalidate(resource),
+        None => self.token_provider.invalidate(&Grant::RefreshToken),
+      }
+    }
+    let mut resp = resp;
+    let mut raw = String::new();
+    resp.read_to_string(&mut raw)?;
+    let errors = serde_json::from_str::<ErrorList>(&raw)
+      .map(|list| list.errors)
+      .unwrap_or_default();
+    Err(MwsError::SpErrorResponse(SpErrorResponse {
+      status,
+      errors,
+      raw,
+      meta,
+    }))
+  }
+}
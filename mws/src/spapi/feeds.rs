@@ -0,0 +1,308 @@
+//! Selling Partner API Feeds 2021-06-30
+//!
+//! [Documentation](https://developer-docs.amazon.com/sp-api/docs/feeds-api-v2021-06-30-reference)
+//!
+//! Feed contents are uploaded to a feed document before the feed is created, see `submit_feed`
+//! for the whole flow. Feed types are accepted in either form, e.g. `POST_INVENTORY_AVAILABILITY_DATA`
+//! or the `_POST_INVENTORY_AVAILABILITY_DATA_` of `feeds::FeedType`.
+
+use super::client::{Method, SpClient};
+use super::reports::decompress;
+use crate::feeds::{Envelope, Message};
+use crate::result::{MwsError, MwsResult};
+use crate::xmlhelper::encode;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static PATH: &str = "/feeds/2021-06-30";
+
+/// Returns the SP-API name of a feed type, e.g. `POST_PRODUCT_DATA` for `_POST_PRODUCT_DATA_`.
+pub fn feed_type_name(feed_type: &str) -> &str {
+  feed_type.trim_matches('_')
+}
+
+str_enum! {
+  pub enum ProcessingStatus {
+    IN_QUEUE,
+    IN_PROGRESS,
+    CANCELLED,
+    DONE,
+    FATAL,
+  }
+}
+
+impl ProcessingStatus {
+  /// Returns `true` if the feed will not be processed any further.
+  pub fn is_final(&self) -> bool {
+    matches!(
+      *self,
+      ProcessingStatus::CANCELLED | ProcessingStatus::DONE | ProcessingStatus::FATAL
+    )
+  }
+}
+
+/// The contents of a feed document and their content type
+#[derive(Debug, Clone)]
+pub struct FeedContent {
+  pub content_type: String,
+  pub data: Vec<u8>,
+}
+
+impl FeedContent {
+  pub fn new<T: Into<String>>(content_type: T, data: Vec<u8>) -> FeedContent {
+    FeedContent {
+      content_type: content_type.into(),
+      data,
+    }
+  }
+
+  /// An XML feed built from a `feeds::Envelope`.
+  pub fn xml<M: Message>(envelope: &Envelope<M>) -> MwsResult<FeedContent>
+  where
+    Envelope<M>: encode::XmlWrite<encode::EventWriter<Vec<u8>>>,
+  {
+    let xml = envelope.to_xml_string()?;
+    Ok(FeedContent::new(
+      "text/xml; charset=UTF-8",
+      xml.into_bytes(),
+    ))
+  }
+
+  /// A tab-delimited flat file feed, `data` must be encoded in UTF-8.
+  pub fn flat_file(data: Vec<u8>) -> FeedContent {
+    FeedContent::new("text/tab-separated-values; charset=UTF-8", data)
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateFeedDocumentSpecification<'a> {
+  content_type: &'a str,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CreateFeedDocumentResponse {
+  pub feed_document_id: String,
+  /// The pre-signed URL the feed contents are uploaded to, valid for five minutes.
+  pub url: String,
+}
+
+/// Parameters for `createFeed`
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFeedSpecification {
+  /// The feed type, either the SP-API name or the MWS identifier.
+  pub feed_type: String,
+  pub marketplace_ids: Vec<String>,
+  /// The `feed_document_id` returned by `createFeedDocument`.
+  pub input_feed_document_id: String,
+  /// Additional options of the feed type, e.g. `UnitOfMeasurement` of `POST_FLAT_FILE_LISTINGS_DATA`.
+  #[serde(skip_serializing_if = "HashMap::is_empty")]
+  pub feed_options: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CreateFeedResponse {
+  pub feed_id: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Feed {
+  pub feed_id: String,
+  pub feed_type: String,
+  pub marketplace_ids: Vec<String>,
+  pub created_time: Option<DateTime<Utc>>,
+  pub processing_status: ProcessingStatus,
+  pub processing_start_time: Option<DateTime<Utc>>,
+  pub processing_end_time: Option<DateTime<Utc>>,
+  /// The identifier of the processing report, available once the feed is `DONE`.
+  pub result_feed_document_id: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FeedDocument {
+  pub feed_document_id: String,
+  /// A pre-signed URL, valid for five minutes.
+  pub url: String,
+  /// `GZIP` if the document is compressed.
+  pub compression_algorithm: Option<String>,
+}
+
+/// Creates a feed document and returns the URL its contents are uploaded to.
+#[allow(non_snake_case)]
+pub fn createFeedDocument(
+  client: &SpClient,
+  content_type: &str,
+) -> MwsResult<CreateFeedDocumentResponse> {
+  let body = serde_json::to_vec(&CreateFeedDocumentSpecification { content_type })?;
+  client.request_json(
+    Method::POST,
+    &format!("{}/documents", PATH),
+    &[],
+    Some(body),
+  )
+}
+
+/// Creates a feed from an uploaded feed document.
+#[allow(non_snake_case)]
+pub fn createFeed(
+  client: &SpClient,
+  mut spec: CreateFeedSpecification,
+) -> MwsResult<CreateFeedResponse> {
+  spec.feed_type = feed_type_name(&spec.feed_type).to_owned();
+  let body = serde_json::to_vec(&spec)?;
+  client.request_json(Method::POST, &format!("{}/feeds", PATH), &[], Some(body))
+}
+
+/// Returns feed details, including the `result_feed_document_id` once the feed is done.
+#[allow(non_snake_case)]
+pub fn getFeed(client: &SpClient, feed_id: &str) -> MwsResult<Feed> {
+  client.request_json(
+    Method::GET,
+    &format!("{}/feeds/{}", PATH, feed_id),
+    &[],
+    None,
+  )
+}
+
+/// Cancels the feed that you specify. Only feeds in `IN_QUEUE` can be cancelled.
+#[allow(non_snake_case)]
+pub fn cancelFeed(client: &SpClient, feed_id: &str) -> MwsResult<()> {
+  client
+    .request(
+      Method::DELETE,
+      &format!("{}/feeds/{}", PATH, feed_id),
+      &[],
+      None,
+    )
+    .map(|_| ())
+}
+
+/// Returns the information required for retrieving a feed document's contents.
+#[allow(non_snake_case)]
+pub fn getFeedDocument(client: &SpClient, feed_document_id: &str) -> MwsResult<FeedDocument> {
+  client.request_json(
+    Method::GET,
+    &format!("{}/documents/{}", PATH, feed_document_id),
+    &[],
+    None,
+  )
+}
+
+/// Downloads the contents of a feed document to `out`, and returns the number of bytes written.
+pub fn download_feed_document<W: Write>(
+  client: &SpClient,
+  document: &FeedDocument,
+  out: &mut W,
+) -> MwsResult<u64> {
+  let resp = client.download(&document.url)?;
+  decompress(
+    document.compression_algorithm.as_ref().map(AsRef::as_ref),
+    resp,
+    out,
+  )
+}
+
+/// Parameters for `submit_feed`
+#[derive(Debug, Default, Clone)]
+pub struct SubmitFeedParameters {
+  /// The feed type, either the SP-API name or the MWS identifier.
+  pub feed_type: String,
+  pub marketplace_ids: Vec<String>,
+  pub feed_options: HashMap<String, String>,
+  /// How long to wait between `getFeed` requests, 30 seconds by default.
+  pub poll_interval: Option<Duration>,
+  /// How long to wait for the feed to be processed, unlimited by default.
+  pub timeout: Option<Duration>,
+}
+
+/// Uploads `content`, creates a feed from it and waits until the feed is processed.
+///
+/// The processing report is written to `out` if Amazon produced one, including for `FATAL` feeds.
+/// Returns the last state of the feed.
+pub fn submit_feed<W: Write>(
+  client: &SpClient,
+  params: SubmitFeedParameters,
+  content: FeedContent,
+  out: &mut W,
+) -> MwsResult<Feed> {
+  let document = createFeedDocument(client, &content.content_type)?;
+  client.upload(&document.url, &content.content_type, content.data)?;
+  let feed_id = createFeed(
+    client,
+    CreateFeedSpecification {
+      feed_type: params.feed_type,
+      marketplace_ids: params.marketplace_ids,
+      input_feed_document_id: document.feed_document_id,
+      feed_options: params.feed_options,
+    },
+  )?
+  .feed_id;
+
+  let poll_interval = params
+    .poll_interval
+    .unwrap_or_else(|| Duration::from_secs(30));
+  let started_at = Instant::now();
+  let feed = loop {
+    let feed = getFeed(client, &feed_id)?;
+    if feed.processing_status.is_final() {
+      break feed;
+    }
+    if let Some(timeout) = params.timeout {
+      if started_at.elapsed() + poll_interval > timeout {
+        return Err(MwsError::Timeout(format!(
+          "feed '{}', still {} after {:?}",
+          feed_id,
+          feed.processing_status.as_ref(),
+          timeout
+        )));
+      }
+    }
+    thread::sleep(poll_interval);
+  };
+
+  if let Some(ref result_feed_document_id) = feed.result_feed_document_id {
+    let document = getFeedDocument(client, result_feed_document_id)?;
+    download_feed_document(client, &document, out)?;
+  }
+  Ok(feed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_create_feed_body() {
+    let spec = CreateFeedSpecification {
+      feed_type: "_POST_FLAT_FILE_INVLOADER_DATA_".to_owned(),
+      marketplace_ids: vec!["A1VC38T7YXB528".to_owned()],
+      input_feed_document_id: "3d4e42b5-1d6e-44e8-a89c-2abfca0625bb".to_owned(),
+      feed_options: HashMap::new(),
+    };
+    assert_eq!(
+      feed_type_name(&spec.feed_type),
+      "POST_FLAT_FILE_INVLOADER_DATA"
+    );
+    assert_eq!(
+      serde_json::to_string(&spec).unwrap(),
+      r#"{"feedType":"_POST_FLAT_FILE_INVLOADER_DATA_","marketplaceIds":["A1VC38T7YXB528"],"inputFeedDocumentId":"3d4e42b5-1d6e-44e8-a89c-2abfca0625bb"}"#
+    );
+  }
+
+  #[test]
+  fn test_processing_status_is_final() {
+    assert!(!ProcessingStatus::IN_QUEUE.is_final());
+    assert!(!ProcessingStatus::IN_PROGRESS.is_final());
+    assert!(ProcessingStatus::DONE.is_final());
+    assert!(ProcessingStatus::FATAL.is_final());
+    assert!(ProcessingStatus::CANCELLED.is_final());
+  }
+}
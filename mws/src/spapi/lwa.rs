@@ -0,0 +1,226 @@
+//! Login with Amazon (LWA) access tokens
+//!
+//! [Documentation](https://developer-docs.amazon.com/sp-api/docs/connecting-to-the-selling-partner-api)
+
+use crate::result::{MwsError, MwsResult};
+use crate::transport::{Request, RequestBody, ReqwestTransport, Transport};
+use reqwest::Method;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The default LWA token endpoint
+pub const TOKEN_ENDPOINT: &str = "https://api.amazon.com/auth/o2/token";
+
+/// Grantless scope of the Notifications API
+pub const SCOPE_NOTIFICATIONS: &str = "sellingpartnerapi::notifications";
+
+/// Grantless scope of the Application Management API
+pub const SCOPE_CLIENT_CREDENTIAL_ROTATION: &str = "sellingpartnerapi::client_credential:rotation";
+
+#[derive(Clone, Default)]
+pub struct LwaOptions {
+  /// The token endpoint, `TOKEN_ENDPOINT` is used if empty.
+  pub token_endpoint: String,
+
+  /// The LWA client identifier of your application.
+  pub client_id: String,
+  pub client_secret: String,
+
+  /// The refresh token issued when a selling partner authorized your application.
+  /// Only grantless operations are available without it.
+  pub refresh_token: Option<String>,
+
+  /// How long before its expiration a cached access token is refreshed, 60 seconds by default.
+  pub refresh_margin: Option<Duration>,
+}
+
+impl fmt::Debug for LwaOptions {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("LwaOptions")
+      .field("token_endpoint", &self.token_endpoint)
+      .field("client_id", &self.client_id)
+      .field("client_secret", &"<redacted>")
+      .field(
+        "refresh_token",
+        &self.refresh_token.as_ref().map(|_| "<redacted>"),
+      )
+      .field("refresh_margin", &self.refresh_margin)
+      .finish()
+  }
+}
+
+/// How an access token is obtained
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Grant {
+  /// Exchanges `LwaOptions::refresh_token`, for operations on behalf of a selling partner.
+  RefreshToken,
+  /// `client_credentials` grant for a grantless scope, e.g. `SCOPE_NOTIFICATIONS`.
+  ClientCredentials(String),
+}
+
+/// A cached access token
+#[derive(Clone)]
+pub struct AccessToken {
+  pub access_token: String,
+  pub token_type: String,
+  pub expires_at: Instant,
+}
+
+impl fmt::Debug for AccessToken {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("AccessToken")
+      .field("access_token", &"<redacted>")
+      .field("token_type", &self.token_type)
+      .field("expires_at", &self.expires_at)
+      .finish()
+  }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+  access_token: String,
+  #[serde(default)]
+  token_type: String,
+  expires_in: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+  error: String,
+  #[serde(default)]
+  error_description: String,
+}
+
+/// Exchanges LWA credentials for access tokens and caches them until they expire
+///
+/// Clones share the same cache. Concurrent callers of a grant wait for a refresh of that grant
+/// in progress instead of requesting another token, the other grants are not blocked.
+#[derive(Debug, Clone)]
+pub struct TokenProvider {
+  options: Arc<LwaOptions>,
+  transport: Arc<dyn Transport>,
+  /// One slot per grant, locked while the token of the grant is refreshed.
+  cache: Arc<Mutex<HashMap<Grant, TokenSlot>>>,
+}
+
+type TokenSlot = Arc<Mutex<Option<AccessToken>>>;
+
+impl TokenProvider {
+  pub fn new(options: LwaOptions) -> TokenProvider {
+    TokenProvider::with_transport(options, ReqwestTransport::default())
+  }
+
+  /// Constructs a provider which sends token requests through `transport`
+  pub fn with_transport<T>(options: LwaOptions, transport: T) -> TokenProvider
+  where
+    T: Transport + 'static,
+  {
+    TokenProvider {
+      options: Arc::new(options),
+      transport: Arc::new(transport),
+      cache: Arc::new(Mutex::new(HashMap::new())),
+    }
+  }
+
+  /// Returns an access token for operations on behalf of the selling partner.
+  pub fn access_token(&self) -> MwsResult<String> {
+    self.token(&Grant::RefreshToken)
+  }
+
+  /// Returns an access token for grantless operations in `scope`.
+  pub fn grantless_access_token(&self, scope: &str) -> MwsResult<String> {
+    self.token(&Grant::ClientCredentials(scope.to_owned()))
+  }
+
+  /// Returns the cached access token of `grant`, or requests a new one if it expires
+  /// within `LwaOptions::refresh_margin`.
+  pub fn token(&self, grant: &Grant) -> MwsResult<String> {
+    // the cache is only locked to find the slot, not while a token is requested
+    let slot = self
+      .cache
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .entry(grant.clone())
+      .or_default()
+      .clone();
+    let mut cached = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let margin = self
+      .options
+      .refresh_margin
+      .unwrap_or_else(|| Duration::from_secs(60));
+    if let Some(ref token) = *cached {
+      if Instant::now() + margin < token.expires_at {
+        return Ok(token.access_token.clone());
+      }
+    }
+    let token = self.request_token(grant)?;
+    let access_token = token.access_token.clone();
+    *cached = Some(token);
+    Ok(access_token)
+  }
+
+  /// Drops the cached access token of `grant`, e.g. after it has been rejected.
+  pub fn invalidate(&self, grant: &Grant) {
+    self
+      .cache
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .remove(grant);
+  }
+
+  fn request_token(&self, grant: &Grant) -> MwsResult<AccessToken> {
+    let options = &self.options;
+    let mut form = match *grant {
+      Grant::RefreshToken => {
+        let refresh_token = options.refresh_token.clone().ok_or_else(|| {
+          MwsError::Msg("LwaOptions::refresh_token is required for this operation".to_owned())
+        })?;
+        vec![
+          ("grant_type".to_owned(), "refresh_token".to_owned()),
+          ("refresh_token".to_owned(), refresh_token),
+        ]
+      }
+      Grant::ClientCredentials(ref scope) => vec![
+        ("grant_type".to_owned(), "client_credentials".to_owned()),
+        ("scope".to_owned(), scope.clone()),
+      ],
+    };
+    form.push(("client_id".to_owned(), options.client_id.clone()));
+    form.push(("client_secret".to_owned(), options.client_secret.clone()));
+
+    let endpoint = if options.token_endpoint.is_empty() {
+      TOKEN_ENDPOINT
+    } else {
+      &options.token_endpoint
+    };
+    let mut request = Request::new(Method::POST, endpoint.to_owned());
+    request.body = RequestBody::Form(form);
+
+    let requested_at = Instant::now();
+    let mut resp = self.transport.send(request)?;
+    let status = resp.status();
+    let mut body = vec![];
+    resp.read_to_end(&mut body)?;
+    if !status.is_success() {
+      let (error, description) = match serde_json::from_slice::<TokenErrorResponse>(&body) {
+        Ok(err) => (err.error, err.error_description),
+        Err(_) => (String::new(), String::from_utf8_lossy(&body).into_owned()),
+      };
+      return Err(MwsError::LwaError {
+        status,
+        error,
+        description,
+      });
+    }
+
+    let token: TokenResponse = serde_json::from_slice(&body)?;
+    Ok(AccessToken {
+      access_token: token.access_token,
+      token_type: token.token_type,
+      expires_at: requested_at + Duration::from_secs(token.expires_in),
+    })
+  }
+}
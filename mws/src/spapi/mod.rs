@@ -0,0 +1,14 @@
+//! Selling Partner API, the successor of MWS
+//!
+//! [Documentation](https://developer-docs.amazon.com/sp-api/docs)
+//!
+//! Requests are authorized with Login with Amazon (LWA) access tokens issued by `lwa::TokenProvider`.
+
+pub mod client;
+pub mod feeds;
+pub mod lwa;
+pub mod orders;
+pub mod reports;
+pub mod tokens;
+
+pub use self::client::{SpClient, SpClientOptions};
@@ -0,0 +1,273 @@
+//! Selling Partner API Orders v0
+//!
+//! [Documentation](https://developer-docs.amazon.com/sp-api/docs/orders-api-v0-reference)
+//!
+//! Orders are decoded into the types of `orders`, so code written for MWS Orders 2013-09-01
+//! keeps working with the results of these operations.
+
+use super::client::{Method, Payload, SpClient};
+use crate::orders::{
+  FulfillmentChannel, Order, OrderItem, OrderStatus, PaymentMethod, ShippingAddress,
+};
+use crate::paginator::{Page, Paginator};
+use crate::result::MwsResult;
+use crate::types::ToIso8601;
+use chrono::{DateTime, Utc};
+
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Clone)]
+pub struct GetOrdersParameters {
+  /// A list of MarketplaceId values. Used to select orders that were placed in the specified marketplaces.
+  pub MarketplaceIds: Vec<String>,
+  pub CreatedAfter: Option<DateTime<Utc>>,
+  pub CreatedBefore: Option<DateTime<Utc>>,
+  pub LastUpdatedAfter: Option<DateTime<Utc>>,
+  pub LastUpdatedBefore: Option<DateTime<Utc>>,
+  pub OrderStatuses: Vec<OrderStatus>,
+  pub FulfillmentChannels: Vec<FulfillmentChannel>,
+  pub PaymentMethods: Vec<PaymentMethod>,
+  pub BuyerEmail: Option<String>,
+  pub SellerOrderId: Option<String>,
+  /// A number that indicates the maximum number of orders that can be returned per page. Value must be 1 - 100.
+  pub MaxResultsPerPage: Option<i32>,
+  /// A list of AmazonOrderId values, up to 50.
+  pub AmazonOrderIds: Vec<String>,
+  /// A string token returned in the response of your previous request.
+  pub NextToken: Option<String>,
+}
+
+impl GetOrdersParameters {
+  fn query(&self) -> Vec<(&'static str, String)> {
+    fn join<T: AsRef<str>>(values: &[T]) -> String {
+      values
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+
+    let mut query = vec![];
+    let lists = [
+      ("MarketplaceIds", join(&self.MarketplaceIds)),
+      ("OrderStatuses", join(&self.OrderStatuses)),
+      ("FulfillmentChannels", join(&self.FulfillmentChannels)),
+      ("PaymentMethods", join(&self.PaymentMethods)),
+      ("AmazonOrderIds", join(&self.AmazonOrderIds)),
+    ];
+    for (key, value) in lists.iter() {
+      if !value.is_empty() {
+        query.push((*key, value.clone()));
+      }
+    }
+    let dates = [
+      ("CreatedAfter", self.CreatedAfter),
+      ("CreatedBefore", self.CreatedBefore),
+      ("LastUpdatedAfter", self.LastUpdatedAfter),
+      ("LastUpdatedBefore", self.LastUpdatedBefore),
+    ];
+    for (key, value) in dates.iter() {
+      if let Some(ref value) = *value {
+        query.push((*key, value.to_iso8601()));
+      }
+    }
+    let strings = [
+      ("BuyerEmail", &self.BuyerEmail),
+      ("SellerOrderId", &self.SellerOrderId),
+      ("NextToken", &self.NextToken),
+    ];
+    for (key, value) in strings.iter() {
+      if let Some(ref value) = **value {
+        query.push((*key, value.clone()));
+      }
+    }
+    if let Some(max) = self.MaxResultsPerPage {
+      query.push(("MaxResultsPerPage", max.to_string()));
+    }
+    query
+  }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GetOrdersResponse {
+  pub Orders: Vec<Order>,
+  pub NextToken: Option<String>,
+  pub LastUpdatedBefore: Option<DateTime<Utc>>,
+  pub CreatedBefore: Option<DateTime<Utc>>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GetOrderItemsResponse {
+  pub AmazonOrderId: String,
+  pub OrderItems: Vec<OrderItem>,
+  pub NextToken: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct OrderAddress {
+  pub AmazonOrderId: String,
+  pub ShippingAddress: Option<ShippingAddress>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct OrderBuyerInfo {
+  pub AmazonOrderId: String,
+  pub BuyerEmail: String,
+  pub BuyerName: String,
+  pub BuyerCounty: String,
+  pub PurchaseOrderNumber: String,
+}
+
+/// Returns orders created or updated during the time frame indicated by the specified parameters.
+#[allow(non_snake_case)]
+pub fn getOrders(client: &SpClient, params: GetOrdersParameters) -> MwsResult<GetOrdersResponse> {
+  client
+    .request_json::<Payload<_>>(Method::GET, "/orders/v0/orders", &params.query(), None)
+    .map(|p| p.payload)
+}
+
+/// Returns the order indicated by the specified order ID.
+#[allow(non_snake_case)]
+pub fn getOrder(client: &SpClient, order_id: &str) -> MwsResult<Order> {
+  client
+    .request_json::<Payload<_>>(
+      Method::GET,
+      &format!("/orders/v0/orders/{}", order_id),
+      &[],
+      None,
+    )
+    .map(|p| p.payload)
+}
+
+/// Returns detailed order item information for the order indicated by the specified order ID.
+#[allow(non_snake_case)]
+pub fn getOrderItems(
+  client: &SpClient,
+  order_id: &str,
+  next_token: Option<String>,
+) -> MwsResult<GetOrderItemsResponse> {
+  let query: Vec<_> = next_token.map(|t| ("NextToken", t)).into_iter().collect();
+  client
+    .request_json::<Payload<_>>(
+      Method::GET,
+      &format!("/orders/v0/orders/{}/orderItems", order_id),
+      &query,
+      None,
+    )
+    .map(|p| p.payload)
+}
+
+/// Returns the shipping address for the order indicated by the specified order ID.
+///
+/// The request uses a Restricted Data Token, so `Name`, `AddressLine1` and `Phone` are included.
+#[allow(non_snake_case)]
+pub fn getOrderAddress(client: &SpClient, order_id: &str) -> MwsResult<OrderAddress> {
+  client
+    .request_json_restricted::<Payload<_>>(
+      Method::GET,
+      &format!("/orders/v0/orders/{}/address", order_id),
+      &[],
+      &[],
+    )
+    .map(|p| p.payload)
+}
+
+/// Returns buyer information for the order indicated by the specified order ID.
+///
+/// The request uses a Restricted Data Token, so `BuyerEmail` and `BuyerName` are included.
+#[allow(non_snake_case)]
+pub fn getOrderBuyerInfo(client: &SpClient, order_id: &str) -> MwsResult<OrderBuyerInfo> {
+  client
+    .request_json_restricted::<Payload<_>>(
+      Method::GET,
+      &format!("/orders/v0/orders/{}/buyerInfo", order_id),
+      &[],
+      &[],
+    )
+    .map(|p| p.payload)
+}
+
+impl From<GetOrdersResponse> for Page<Order> {
+  fn from(res: GetOrdersResponse) -> Self {
+    Page {
+      items: res.Orders,
+      next_token: res.NextToken,
+    }
+  }
+}
+
+impl From<GetOrderItemsResponse> for Page<OrderItem> {
+  fn from(res: GetOrderItemsResponse) -> Self {
+    Page {
+      items: res.OrderItems,
+      next_token: res.NextToken,
+    }
+  }
+}
+
+/// Iterates over all orders matching `params`, following `NextToken`.
+pub fn iter_orders(
+  client: &SpClient,
+  params: GetOrdersParameters,
+) -> Paginator<'_, Order, SpClient> {
+  let marketplace_ids = params.MarketplaceIds.clone();
+  Paginator::new(
+    client,
+    move |client| getOrders(client, params).map(Into::into),
+    move |client, next_token| {
+      getOrders(
+        client,
+        GetOrdersParameters {
+          MarketplaceIds: marketplace_ids.clone(),
+          NextToken: Some(next_token),
+          ..Default::default()
+        },
+      )
+      .map(Into::into)
+    },
+  )
+}
+
+/// Iterates over all items of an order, following `NextToken`.
+pub fn iter_order_items(client: &SpClient, order_id: String) -> Paginator<'_, OrderItem, SpClient> {
+  let next_order_id = order_id.clone();
+  Paginator::new(
+    client,
+    move |client| getOrderItems(client, &order_id, None).map(Into::into),
+    move |client, next_token| {
+      getOrderItems(client, &next_order_id, Some(next_token)).map(Into::into)
+    },
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_get_orders_query() {
+    let params = GetOrdersParameters {
+      MarketplaceIds: vec!["ATVPDKIKX0DER".to_owned(), "A2EUQ1WTGCTBG2".to_owned()],
+      CreatedAfter: Some("2020-10-01T00:00:00Z".parse().unwrap()),
+      OrderStatuses: vec![OrderStatus::Unshipped, OrderStatus::PartiallyShipped],
+      MaxResultsPerPage: Some(50),
+      ..Default::default()
+    };
+    assert_eq!(
+      params.query(),
+      vec![
+        ("MarketplaceIds", "ATVPDKIKX0DER,A2EUQ1WTGCTBG2".to_owned()),
+        ("OrderStatuses", "Unshipped,PartiallyShipped".to_owned()),
+        ("CreatedAfter", "2020-10-01T00:00:00Z".to_owned()),
+        ("MaxResultsPerPage", "50".to_owned()),
+      ]
+    );
+  }
+}
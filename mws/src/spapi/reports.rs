@@ -0,0 +1,469 @@
+//! Selling Partner API Reports 2021-06-30
+//!
+//! [Documentation](https://developer-docs.amazon.com/sp-api/docs/reports-api-v2021-06-30-reference)
+//!
+//! The SP-API names report types without the surrounding underscores of MWS, e.g.
+//! `GET_FLAT_FILE_OPEN_LISTINGS_DATA` for `_GET_FLAT_FILE_OPEN_LISTINGS_DATA_`.
+//! The operations of this module accept either form, see `report_type_name` and `mws_report_type`.
+
+use super::client::{Method, SpClient};
+use crate::paginator::{Page, Paginator};
+use crate::result::{MwsError, MwsResult};
+use crate::types::ToIso8601;
+use chrono::{DateTime, Utc};
+use crypto::aes::{cbc_decryptor, KeySize};
+use crypto::blockmodes::PkcsPadding;
+use crypto::buffer::{BufferResult, ReadBuffer, RefReadBuffer, RefWriteBuffer, WriteBuffer};
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+static PATH: &str = "/reports/2021-06-30";
+
+/// Returns the SP-API name of a report type, e.g. `GET_MERCHANT_LISTINGS_ALL_DATA`
+/// for `_GET_MERCHANT_LISTINGS_ALL_DATA_`.
+pub fn report_type_name(report_type: &str) -> &str {
+  report_type.trim_matches('_')
+}
+
+/// Returns the MWS identifier of a report type, e.g. `_GET_MERCHANT_LISTINGS_ALL_DATA_`
+/// for `GET_MERCHANT_LISTINGS_ALL_DATA`.
+pub fn mws_report_type(report_type: &str) -> String {
+  format!("_{}_", report_type_name(report_type))
+}
+
+str_enum! {
+  pub enum ProcessingStatus {
+    IN_QUEUE,
+    IN_PROGRESS,
+    CANCELLED,
+    DONE,
+    FATAL,
+  }
+}
+
+/// Parameters for `createReport`
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReportSpecification {
+  /// The report type, either the SP-API name or the MWS identifier.
+  pub report_type: String,
+  /// A list of marketplace identifiers. The report document includes data for all of them.
+  pub marketplace_ids: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub data_start_time: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub data_end_time: Option<DateTime<Utc>>,
+  /// Additional information passed to reports, e.g. `custom` of `GET_MERCHANT_LISTINGS_ALL_DATA`.
+  #[serde(skip_serializing_if = "HashMap::is_empty")]
+  pub report_options: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CreateReportResponse {
+  pub report_id: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Report {
+  pub marketplace_ids: Vec<String>,
+  pub report_id: String,
+  pub report_type: String,
+  pub data_start_time: Option<DateTime<Utc>>,
+  pub data_end_time: Option<DateTime<Utc>>,
+  pub report_schedule_id: Option<String>,
+  pub created_time: Option<DateTime<Utc>>,
+  pub processing_status: ProcessingStatus,
+  pub processing_start_time: Option<DateTime<Utc>>,
+  pub processing_end_time: Option<DateTime<Utc>>,
+  /// The identifier of the report document, available once `processing_status` is `DONE`.
+  pub report_document_id: Option<String>,
+}
+
+/// Parameters for `getReports`
+#[derive(Debug, Default, Clone)]
+pub struct GetReportsParameters {
+  /// Report types, either SP-API names or MWS identifiers. Required unless `next_token` is set.
+  pub report_types: Vec<String>,
+  pub processing_statuses: Vec<ProcessingStatus>,
+  pub marketplace_ids: Vec<String>,
+  /// The maximum number of reports to return per page, 1 - 100.
+  pub page_size: Option<i32>,
+  pub created_since: Option<DateTime<Utc>>,
+  pub created_until: Option<DateTime<Utc>>,
+  /// A token returned by a previous request, the other parameters are ignored if it is set.
+  pub next_token: Option<String>,
+}
+
+impl GetReportsParameters {
+  fn query(&self) -> Vec<(&'static str, String)> {
+    if let Some(ref next_token) = self.next_token {
+      return vec![("nextToken", next_token.clone())];
+    }
+
+    let mut query = vec![];
+    let report_types: Vec<&str> = self
+      .report_types
+      .iter()
+      .map(|t| report_type_name(t))
+      .collect();
+    let processing_statuses: Vec<&str> =
+      self.processing_statuses.iter().map(AsRef::as_ref).collect();
+    let lists = [
+      ("reportTypes", report_types.join(",")),
+      ("processingStatuses", processing_statuses.join(",")),
+      ("marketplaceIds", self.marketplace_ids.join(",")),
+    ];
+    for (key, value) in lists.iter() {
+      if !value.is_empty() {
+        query.push((*key, value.clone()));
+      }
+    }
+    if let Some(page_size) = self.page_size {
+      query.push(("pageSize", page_size.to_string()));
+    }
+    let dates = [
+      ("createdSince", self.created_since),
+      ("createdUntil", self.created_until),
+    ];
+    for (key, value) in dates.iter() {
+      if let Some(ref value) = *value {
+        query.push((*key, value.to_iso8601()));
+      }
+    }
+    query
+  }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GetReportsResponse {
+  pub reports: Vec<Report>,
+  pub next_token: Option<String>,
+}
+
+/// How a report document is encrypted, only returned for documents of the 2020-09-04 API
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EncryptionDetails {
+  /// The encryption standard, `AES`.
+  pub standard: String,
+  /// The base64 encoded initialization vector.
+  pub initialization_vector: String,
+  /// The base64 encoded encryption key.
+  pub key: String,
+}
+
+impl fmt::Debug for EncryptionDetails {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("EncryptionDetails")
+      .field("standard", &self.standard)
+      .field("initialization_vector", &self.initialization_vector)
+      .field("key", &"<redacted>")
+      .finish()
+  }
+}
+
+impl EncryptionDetails {
+  /// Decrypts `data` with AES-CBC and PKCS#7 padding.
+  pub fn decrypt(&self, data: &[u8]) -> MwsResult<Vec<u8>> {
+    if self.standard != "AES" {
+      return Err(MwsError::Msg(format!(
+        "unsupported report document encryption: '{}'",
+        self.standard
+      )));
+    }
+    let key = decode_base64("key", &self.key)?;
+    let iv = decode_base64("initializationVector", &self.initialization_vector)?;
+    let key_size = match key.len() {
+      16 => KeySize::KeySize128,
+      24 => KeySize::KeySize192,
+      32 => KeySize::KeySize256,
+      len => {
+        return Err(MwsError::Msg(format!(
+          "invalid report document key length: {}",
+          len
+        )))
+      }
+    };
+
+    let mut decryptor = cbc_decryptor(key_size, &key, &iv, PkcsPadding);
+    let mut decrypted = Vec::with_capacity(data.len());
+    let mut input = RefReadBuffer::new(data);
+    let mut buf = [0; 4096];
+    loop {
+      let mut output = RefWriteBuffer::new(&mut buf);
+      let res = decryptor
+        .decrypt(&mut input, &mut output, true)
+        .map_err(|err| MwsError::Msg(format!("report document decryption error: {:?}", err)))?;
+      decrypted.extend_from_slice(output.take_read_buffer().take_remaining());
+      if let BufferResult::BufferUnderflow = res {
+        return Ok(decrypted);
+      }
+    }
+  }
+}
+
+fn decode_base64(what: &str, value: &str) -> MwsResult<Vec<u8>> {
+  base64::decode(value).map_err(|err| MwsError::ParseString {
+    what: what.to_owned(),
+    message: format!("{:?}", err),
+  })
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReportDocument {
+  pub report_document_id: String,
+  /// A pre-signed URL, valid for five minutes.
+  pub url: String,
+  /// `GZIP` if the document is compressed.
+  pub compression_algorithm: Option<String>,
+  pub encryption_details: Option<EncryptionDetails>,
+}
+
+/// Creates a report.
+#[allow(non_snake_case)]
+pub fn createReport(
+  client: &SpClient,
+  mut spec: CreateReportSpecification,
+) -> MwsResult<CreateReportResponse> {
+  spec.report_type = report_type_name(&spec.report_type).to_owned();
+  let body = serde_json::to_vec(&spec)?;
+  client.request_json(Method::POST, &format!("{}/reports", PATH), &[], Some(body))
+}
+
+/// Returns report details, including the `report_document_id` once the report is done.
+#[allow(non_snake_case)]
+pub fn getReport(client: &SpClient, report_id: &str) -> MwsResult<Report> {
+  client.request_json(
+    Method::GET,
+    &format!("{}/reports/{}", PATH, report_id),
+    &[],
+    None,
+  )
+}
+
+/// Returns report details for the reports that match the filters that you specify.
+#[allow(non_snake_case)]
+pub fn getReports(
+  client: &SpClient,
+  params: GetReportsParameters,
+) -> MwsResult<GetReportsResponse> {
+  client.request_json(
+    Method::GET,
+    &format!("{}/reports", PATH),
+    &params.query(),
+    None,
+  )
+}
+
+/// Cancels the report that you specify. Only reports in `IN_QUEUE` can be cancelled.
+#[allow(non_snake_case)]
+pub fn cancelReport(client: &SpClient, report_id: &str) -> MwsResult<()> {
+  client
+    .request(
+      Method::DELETE,
+      &format!("{}/reports/{}", PATH, report_id),
+      &[],
+      None,
+    )
+    .map(|_| ())
+}
+
+/// Returns the information required for retrieving a report document's contents.
+#[allow(non_snake_case)]
+pub fn getReportDocument(client: &SpClient, report_document_id: &str) -> MwsResult<ReportDocument> {
+  client.request_json(
+    Method::GET,
+    &format!("{}/documents/{}", PATH, report_document_id),
+    &[],
+    None,
+  )
+}
+
+/// Downloads the contents of a report document to `out`, and returns the number of bytes written.
+pub fn download_report_document<W: Write>(
+  client: &SpClient,
+  document: &ReportDocument,
+  out: &mut W,
+) -> MwsResult<u64> {
+  let resp = client.download(&document.url)?;
+  decode_report_document(document, resp, out)
+}
+
+/// Writes the contents of a downloaded report document to `out`, decrypting and
+/// decompressing them as described by `document`.
+pub fn decode_report_document<R: Read, W: Write>(
+  document: &ReportDocument,
+  mut body: R,
+  out: &mut W,
+) -> MwsResult<u64> {
+  let compression = document.compression_algorithm.as_ref().map(AsRef::as_ref);
+  match document.encryption_details {
+    Some(ref details) => {
+      let mut encrypted = vec![];
+      body.read_to_end(&mut encrypted)?;
+      let decrypted = details.decrypt(&encrypted)?;
+      decompress(compression, &decrypted[..], out)
+    }
+    None => decompress(compression, body, out),
+  }
+}
+
+/// Copies `body` to `out`, decompressing it according to the `compressionAlgorithm` of a document.
+pub(crate) fn decompress<R: Read, W: Write>(
+  compression_algorithm: Option<&str>,
+  body: R,
+  out: &mut W,
+) -> MwsResult<u64> {
+  let size = match compression_algorithm {
+    None => io::copy(&mut { body }, out)?,
+    Some("GZIP") => io::copy(&mut GzDecoder::new(body), out)?,
+    Some(other) => {
+      return Err(MwsError::Msg(format!(
+        "unsupported document compression: '{}'",
+        other
+      )))
+    }
+  };
+  Ok(size)
+}
+
+impl From<GetReportsResponse> for Page<Report> {
+  fn from(res: GetReportsResponse) -> Self {
+    Page {
+      items: res.reports,
+      next_token: res.next_token,
+    }
+  }
+}
+
+/// Iterates over the reports returned by `getReports`, following `nextToken`.
+pub fn iter_reports(
+  client: &SpClient,
+  params: GetReportsParameters,
+) -> Paginator<'_, Report, SpClient> {
+  Paginator::new(
+    client,
+    move |client| getReports(client, params).map(Into::into),
+    |client, next_token| {
+      getReports(
+        client,
+        GetReportsParameters {
+          next_token: Some(next_token),
+          ..Default::default()
+        },
+      )
+      .map(Into::into)
+    },
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crypto::aes::cbc_encryptor;
+  use flate2::write::GzEncoder;
+  use flate2::Compression;
+
+  #[test]
+  fn test_report_type_name() {
+    assert_eq!(
+      report_type_name("_GET_FLAT_FILE_OPEN_LISTINGS_DATA_"),
+      "GET_FLAT_FILE_OPEN_LISTINGS_DATA"
+    );
+    assert_eq!(
+      report_type_name("GET_FLAT_FILE_OPEN_LISTINGS_DATA"),
+      "GET_FLAT_FILE_OPEN_LISTINGS_DATA"
+    );
+    assert_eq!(
+      mws_report_type("GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE"),
+      "_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_"
+    );
+    assert_eq!(
+      mws_report_type("_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_"),
+      "_GET_V2_SETTLEMENT_REPORT_DATA_FLAT_FILE_"
+    );
+  }
+
+  #[test]
+  fn test_get_reports_query() {
+    let params = GetReportsParameters {
+      report_types: vec![
+        "_GET_AFN_INVENTORY_DATA_".to_owned(),
+        "GET_MERCHANT_LISTINGS_ALL_DATA".to_owned(),
+      ],
+      processing_statuses: vec![ProcessingStatus::IN_QUEUE, ProcessingStatus::DONE],
+      page_size: Some(10),
+      created_since: Some("2021-06-01T00:00:00Z".parse().unwrap()),
+      ..Default::default()
+    };
+    assert_eq!(
+      params.query(),
+      vec![
+        (
+          "reportTypes",
+          "GET_AFN_INVENTORY_DATA,GET_MERCHANT_LISTINGS_ALL_DATA".to_owned()
+        ),
+        ("processingStatuses", "IN_QUEUE,DONE".to_owned()),
+        ("pageSize", "10".to_owned()),
+        ("createdSince", "2021-06-01T00:00:00Z".to_owned()),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_decode_report_document() {
+    let content = "sku\tprice\tquantity\nCBA_OTF_1\t25.99\t3\n".repeat(500);
+    let key = [7u8; 32];
+    let iv = [3u8; 16];
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(content.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut encryptor = cbc_encryptor(KeySize::KeySize256, &key, &iv, PkcsPadding);
+    let mut encrypted = vec![];
+    let mut input = RefReadBuffer::new(&compressed);
+    let mut buf = [0; 4096];
+    loop {
+      let mut output = RefWriteBuffer::new(&mut buf);
+      let res = encryptor.encrypt(&mut input, &mut output, true).unwrap();
+      encrypted.extend_from_slice(output.take_read_buffer().take_remaining());
+      if let BufferResult::BufferUnderflow = res {
+        break;
+      }
+    }
+
+    let document = ReportDocument {
+      report_document_id: "amzn1.tortuga.3.ed4cd0d8-447b-4c1d-87bb-c9e8e0e2b2e9.T3YUVYPGKE9BMY"
+        .to_owned(),
+      url: String::new(),
+      compression_algorithm: Some("GZIP".to_owned()),
+      encryption_details: Some(EncryptionDetails {
+        standard: "AES".to_owned(),
+        initialization_vector: base64::encode(&iv),
+        key: base64::encode(&key),
+      }),
+    };
+    let mut out = vec![];
+    let size = decode_report_document(&document, &encrypted[..], &mut out).unwrap();
+    assert_eq!(size, content.len() as u64);
+    assert_eq!(String::from_utf8(out).unwrap(), content);
+    assert!(!format!("{:?}", document).contains(&base64::encode(&key)));
+
+    // a wrong key fails with a padding error or garbage that is not gzip
+    let document = ReportDocument {
+      encryption_details: Some(EncryptionDetails {
+        key: base64::encode(&[8u8; 32]),
+        ..document.encryption_details.clone().unwrap()
+      }),
+      ..document
+    };
+    assert!(decode_report_document(&document, &encrypted[..], &mut vec![]).is_err());
+  }
+}
@@ -0,0 +1,269 @@
+//! Selling Partner API Tokens 2021-03-01
+//!
+//! [Documentation](https://developer-docs.amazon.com/sp-api/docs/tokens-api-v2021-03-01-reference)
+//!
+//! Operations returning Personally Identifiable Information (PII), e.g. buyer names and
+//! shipping addresses, require a Restricted Data Token (RDT) instead of the LWA access token.
+//! `SpClient::request_restricted` requests and caches them transparently.
+//!
+//! Tokens are credentials: the `Debug` output of the types in this module never contains them.
+
+use super::client::{Method, SpClient};
+use crate::result::MwsResult;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The default number of tokens kept by `RdtCache`
+pub const DEFAULT_RDT_CACHE_CAPACITY: usize = 256;
+
+/// A restricted operation and the PII it returns
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestrictedResource {
+  /// The HTTP method of the restricted operation.
+  pub method: String,
+  /// The path of the restricted operation, e.g. `/orders/v0/orders/902-3159896-1390916/address`.
+  pub path: String,
+  /// The types of PII requested, e.g. `buyerInfo` or `shippingAddress`.
+  /// Only used by `getOrder`, `getOrders` and `getOrderItems`.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub data_elements: Vec<String>,
+}
+
+impl RestrictedResource {
+  pub fn new(method: Method, path: &str, data_elements: &[&str]) -> RestrictedResource {
+    RestrictedResource {
+      method: method.as_str().to_owned(),
+      path: path.to_owned(),
+      data_elements: data_elements.iter().map(|&v| v.to_owned()).collect(),
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateRestrictedDataTokenRequest<'a> {
+  restricted_resources: &'a [RestrictedResource],
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRestrictedDataTokenResponse {
+  pub restricted_data_token: String,
+  /// The lifetime of the token, in seconds.
+  pub expires_in: u64,
+}
+
+impl fmt::Debug for CreateRestrictedDataTokenResponse {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("CreateRestrictedDataTokenResponse")
+      .field("restricted_data_token", &"<redacted>")
+      .field("expires_in", &self.expires_in)
+      .finish()
+  }
+}
+
+/// Returns a Restricted Data Token (RDT) for one or more restricted resources.
+#[allow(non_snake_case)]
+pub fn createRestrictedDataToken(
+  client: &SpClient,
+  restricted_resources: &[RestrictedResource],
+) -> MwsResult<CreateRestrictedDataTokenResponse> {
+  let body = serde_json::to_vec(&CreateRestrictedDataTokenRequest {
+    restricted_resources,
+  })?;
+  client.request_json(
+    Method::POST,
+    "/tokens/2021-03-01/restrictedDataToken",
+    &[],
+    Some(body),
+  )
+}
+
+struct CachedRdt {
+  token: String,
+  expires_at: Instant,
+}
+
+#[derive(Default)]
+struct RdtCacheState {
+  tokens: HashMap<RestrictedResource, CachedRdt>,
+  /// Resources in insertion order, the oldest is evicted first.
+  order: VecDeque<RestrictedResource>,
+}
+
+/// Bounded cache of Restricted Data Tokens, keyed by resource
+///
+/// Clones share the same tokens.
+#[derive(Clone)]
+pub struct RdtCache {
+  capacity: usize,
+  state: Arc<Mutex<RdtCacheState>>,
+}
+
+impl Default for RdtCache {
+  fn default() -> Self {
+    RdtCache::new(DEFAULT_RDT_CACHE_CAPACITY)
+  }
+}
+
+impl fmt::Debug for RdtCache {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("RdtCache")
+      .field("capacity", &self.capacity)
+      .field("len", &self.len())
+      .finish()
+  }
+}
+
+impl RdtCache {
+  pub fn new(capacity: usize) -> RdtCache {
+    RdtCache {
+      capacity,
+      state: Arc::new(Mutex::new(RdtCacheState::default())),
+    }
+  }
+
+  /// Returns the token of `resource` if it is valid for at least one more minute.
+  pub fn get(&self, resource: &RestrictedResource) -> Option<String> {
+    let state = self.lock();
+    state
+      .tokens
+      .get(resource)
+      .filter(|cached| Instant::now() + Duration::from_secs(60) < cached.expires_at)
+      .map(|cached| cached.token.clone())
+  }
+
+  /// Stores the token of `resource`, evicting expired tokens and then the oldest ones
+  /// if the cache is full.
+  pub fn insert(&self, resource: RestrictedResource, token: String, expires_in: Duration) {
+    if self.capacity == 0 {
+      return;
+    }
+    let mut state = self.lock();
+    if state.tokens.remove(&resource).is_some() {
+      state.order.retain(|r| r != &resource);
+    }
+    if state.tokens.len() >= self.capacity {
+      let now = Instant::now();
+      let RdtCacheState {
+        ref mut tokens,
+        ref mut order,
+      } = *state;
+      tokens.retain(|_, cached| cached.expires_at > now);
+      order.retain(|r| tokens.contains_key(r));
+      while tokens.len() >= self.capacity {
+        match order.pop_front() {
+          Some(oldest) => {
+            tokens.remove(&oldest);
+          }
+          None => break,
+        }
+      }
+    }
+    state.order.push_back(resource.clone());
+    state.tokens.insert(
+      resource,
+      CachedRdt {
+        token,
+        expires_at: Instant::now() + expires_in,
+      },
+    );
+  }
+
+  /// Drops the token of `resource`, e.g. after it has been rejected.
+  pub fn invalidate(&self, resource: &RestrictedResource) {
+    let mut state = self.lock();
+    if state.tokens.remove(resource).is_some() {
+      state.order.retain(|r| r != resource);
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.lock().tokens.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn lock(&self) -> ::std::sync::MutexGuard<'_, RdtCacheState> {
+    self
+      .state
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn resource(order_id: &str) -> RestrictedResource {
+    RestrictedResource::new(
+      Method::GET,
+      &format!("/orders/v0/orders/{}/address", order_id),
+      &[],
+    )
+  }
+
+  #[test]
+  fn test_rdt_cache_bounded() {
+    let cache = RdtCache::new(2);
+    let hour = Duration::from_secs(3600);
+    cache.insert(resource("1"), "Atz.sprdt|1".to_owned(), hour);
+    cache.insert(resource("2"), "Atz.sprdt|2".to_owned(), hour);
+    cache.insert(resource("3"), "Atz.sprdt|3".to_owned(), hour);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&resource("1")), None);
+    assert_eq!(cache.get(&resource("3")), Some("Atz.sprdt|3".to_owned()));
+
+    // expired tokens are evicted before valid ones
+    cache.insert(
+      resource("2"),
+      "Atz.sprdt|2".to_owned(),
+      Duration::from_secs(0),
+    );
+    cache.insert(resource("4"), "Atz.sprdt|4".to_owned(), hour);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&resource("3")), Some("Atz.sprdt|3".to_owned()));
+
+    // tokens expiring within a minute are not returned
+    cache.insert(
+      resource("5"),
+      "Atz.sprdt|5".to_owned(),
+      Duration::from_secs(30),
+    );
+    assert_eq!(cache.get(&resource("5")), None);
+
+    cache.invalidate(&resource("5"));
+    assert_eq!(cache.len(), 1);
+    assert!(!format!("{:?}", cache).contains("Atz.sprdt"));
+  }
+
+  #[test]
+  fn test_request_body() {
+    let resources = vec![
+      RestrictedResource::new(
+        Method::GET,
+        "/orders/v0/orders/902-3159896-1390916/address",
+        &[],
+      ),
+      RestrictedResource::new(
+        Method::GET,
+        "/orders/v0/orders",
+        &["buyerInfo", "shippingAddress"],
+      ),
+    ];
+    let body = serde_json::to_string(&CreateRestrictedDataTokenRequest {
+      restricted_resources: &resources,
+    })
+    .unwrap();
+    assert_eq!(
+      body,
+      r#"{"restrictedResources":[{"method":"GET","path":"/orders/v0/orders/902-3159896-1390916/address"},{"method":"GET","path":"/orders/v0/orders","dataElements":["buyerInfo","shippingAddress"]}]}"#
+    );
+  }
+}
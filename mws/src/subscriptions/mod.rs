@@ -7,6 +7,8 @@ use crate::result::MwsResult;
 
 pub mod types;
 pub use self::types::*;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub mod notification;
 
 static PATH: &'static str = "/Subscriptions/2013-07-01";
@@ -0,0 +1,113 @@
+//! Async variants of the Subscriptions API operations
+
+use super::*;
+use crate::client::AsyncClient;
+
+/// Async version of `subscriptions::RegisterDestination`
+#[allow(non_snake_case)]
+pub async fn RegisterDestination(
+  client: &AsyncClient,
+  params: RegisterDestinationParameters,
+) -> MwsResult<()> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "RegisterDestination", params)
+    .await
+}
+
+/// Async version of `subscriptions::DeregisterDestination`
+#[allow(non_snake_case)]
+pub async fn DeregisterDestination(
+  client: &AsyncClient,
+  params: RegisterDestinationParameters,
+) -> MwsResult<()> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "DeregisterDestination", params)
+    .await
+}
+
+/// Async version of `subscriptions::ListRegisteredDestinations`
+#[allow(non_snake_case)]
+pub async fn ListRegisteredDestinations(
+  client: &AsyncClient,
+  marketplace_id: String,
+) -> MwsResult<ListRegisteredDestinationsResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListRegisteredDestinations",
+      vec![("MarketplaceId".to_string(), marketplace_id)],
+    )
+    .await
+    .map(|e: ListRegisteredDestinationsResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `subscriptions::SendTestNotificationToDestination`
+#[allow(non_snake_case)]
+pub async fn SendTestNotificationToDestination(
+  client: &AsyncClient,
+  params: RegisterDestinationParameters,
+) -> MwsResult<()> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "SendTestNotificationToDestination",
+      params,
+    )
+    .await
+}
+
+/// Async version of `subscriptions::CreateSubscription`
+#[allow(non_snake_case)]
+pub async fn CreateSubscription(
+  client: &AsyncClient,
+  params: CreateSubscriptionParameters,
+) -> MwsResult<()> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "CreateSubscription", params)
+    .await
+}
+
+/// Async version of `subscriptions::GetSubscription`
+#[allow(non_snake_case)]
+pub async fn GetSubscription(
+  client: &AsyncClient,
+  params: GetSubscriptionParameters,
+) -> MwsResult<GetSubscriptionResponse> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "GetSubscription", params)
+    .await
+    .map(|e: GetSubscriptionResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `subscriptions::ListSubscriptions`
+#[allow(non_snake_case)]
+pub async fn ListSubscriptions(
+  client: &AsyncClient,
+  marketplace_id: String,
+) -> MwsResult<ListSubscriptionsResponse> {
+  client
+    .request_xml(
+      Method::POST,
+      PATH,
+      VERSION,
+      "ListSubscriptions",
+      vec![("MarketplaceId".to_string(), marketplace_id)],
+    )
+    .await
+    .map(|e: ListSubscriptionsResponseEnvelope| e.into_inner())
+}
+
+/// Async version of `subscriptions::UpdateSubscription`
+#[allow(non_snake_case)]
+pub async fn UpdateSubscription(
+  client: &AsyncClient,
+  params: CreateSubscriptionParameters,
+) -> MwsResult<()> {
+  client
+    .request_xml(Method::POST, PATH, VERSION, "UpdateSubscription", params)
+    .await
+}
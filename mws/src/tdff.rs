@@ -1,8 +1,14 @@
 //! Tab-delimited flat file helpers
 
-use csv::{Reader, ReaderBuilder};
+use crate::constants::get_flat_file_encoding;
 use crate::result::{MwsError, MwsResult};
+use csv::{Position, Reader, ReaderBuilder, StringRecord};
+pub use encoding_rs::Encoding;
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+use std::any::type_name;
+use std::fmt;
 use std::io::Read;
+use std::marker::PhantomData;
 
 pub struct TdffParser<R: Read> {
   headers: Vec<String>,
@@ -10,6 +16,7 @@ pub struct TdffParser<R: Read> {
 }
 
 impl<R: Read> TdffParser<R> {
+  /// Creates a parser for a UTF-8 report, see `with_encoding` for other encodings.
   pub fn new(source: R) -> MwsResult<TdffParser<R>> {
     let mut reader = ReaderBuilder::new().delimiter(b'\t').from_reader(source);
     Ok(TdffParser {
@@ -23,6 +30,42 @@ impl<R: Read> TdffParser<R> {
   }
 }
 
+/// Reader which transcodes its source to UTF-8 while it is read
+pub type DecodingReader<R> = DecodeReaderBytes<R, Vec<u8>>;
+
+/// Wraps `source` in a reader which transcodes it from `encoding` to UTF-8, without buffering
+/// the whole source.
+///
+/// A UTF-8 or UTF-16 byte order mark takes precedence over `encoding` and is removed. Without
+/// `encoding` and byte order mark, the source is passed through as UTF-8.
+pub fn decoding_reader<R: Read>(
+  source: R,
+  encoding: Option<&'static Encoding>,
+) -> DecodingReader<R> {
+  DecodeReaderBytesBuilder::new()
+    .encoding(encoding)
+    .bom_override(true)
+    .build(source)
+}
+
+impl<R: Read> TdffParser<DecodingReader<R>> {
+  /// Creates a parser for a report encoded in `encoding`, e.g. `encoding_rs::SHIFT_JIS`.
+  pub fn with_encoding(
+    source: R,
+    encoding: &'static Encoding,
+  ) -> MwsResult<TdffParser<DecodingReader<R>>> {
+    TdffParser::new(decoding_reader(source, Some(encoding)))
+  }
+
+  /// Creates a parser for a flat file report of a marketplace, in the encoding of the marketplace.
+  pub fn for_marketplace(
+    source: R,
+    marketplace_id: &str,
+  ) -> MwsResult<TdffParser<DecodingReader<R>>> {
+    TdffParser::with_encoding(source, get_flat_file_encoding(marketplace_id))
+  }
+}
+
 pub type TdffRow<'a> = Vec<(&'a str, String)>;
 
 pub trait FromTdffRow: Default + Sized {
@@ -30,26 +73,150 @@ pub trait FromTdffRow: Default + Sized {
 }
 
 impl<R: Read> TdffParser<R> {
+  /// Parses all the rows of the report.
   pub fn parse_all<T>(mut self) -> MwsResult<Vec<T>>
   where
     T: FromTdffRow,
   {
-    let size = self.headers.len();
-    let mut row_container = Vec::with_capacity(size);
-    let mut items = vec![];
-    for row in self.reader.records() {
-      row_container.clear();
-      for (i, value) in row?.into_iter().enumerate() {
-        match self.headers.get(i) {
-          Some(key) => {
-            row_container.push((key.as_ref() as &str, value.to_string()));
-          }
-          None => {}
+    self.rows().collect()
+  }
+
+  /// Returns an iterator over the rows of the report, which reads one row at a time.
+  pub fn rows<T>(&mut self) -> TdffRows<'_, R, T>
+  where
+    T: FromTdffRow,
+  {
+    TdffRows {
+      row: self
+        .headers
+        .iter()
+        .map(|key| (key.as_ref(), String::new()))
+        .collect(),
+      reader: &mut self.reader,
+      record: StringRecord::new(),
+      skip_malformed: false,
+      errors: vec![],
+      done: false,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+/// Iterator over the rows of a report, see `TdffParser::rows`
+///
+/// The record and row buffers are reused from one row to the next. Fields which cannot be parsed
+/// are `MwsError::Tdff` errors with the position of the row, malformed rows are `MwsError::Csv`
+/// errors.
+pub struct TdffRows<'a, R: Read, T> {
+  reader: &'a mut Reader<R>,
+  record: StringRecord,
+  row: TdffRow<'a>,
+  skip_malformed: bool,
+  errors: Vec<MwsError>,
+  done: bool,
+  _phantom: PhantomData<T>,
+}
+
+impl<'a, R: Read, T: FromTdffRow> TdffRows<'a, R, T> {
+  /// Skips the rows which cannot be parsed instead of returning an error, the errors are
+  /// collected in `errors`. I/O errors still end the iteration.
+  pub fn skip_malformed(mut self) -> Self {
+    self.skip_malformed = true;
+    self
+  }
+
+  /// The errors of the rows skipped so far.
+  pub fn errors(&self) -> &[MwsError] {
+    &self.errors
+  }
+
+  /// Takes the errors of the rows skipped so far.
+  pub fn take_errors(&mut self) -> Vec<MwsError> {
+    ::std::mem::take(&mut self.errors)
+  }
+
+  fn read_row(&mut self) -> MwsResult<Option<T>> {
+    if !self.reader.read_record(&mut self.record)? {
+      return Ok(None);
+    }
+    for (i, (_, value)) in self.row.iter_mut().enumerate() {
+      value.clear();
+      if let Some(field) = self.record.get(i) {
+        value.push_str(field);
+      }
+    }
+    T::from_tdff_row(&self.row).map(Some).map_err(|err| match err {
+      MwsError::Tdff(mut err) => {
+        err.position = self.record.position().cloned();
+        MwsError::Tdff(err)
+      }
+      err => err,
+    })
+  }
+}
+
+impl<'a, R: Read, T: FromTdffRow> Iterator for TdffRows<'a, R, T> {
+  type Item = MwsResult<T>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while !self.done {
+      let err = match self.read_row() {
+        Ok(Some(row)) => return Some(Ok(row)),
+        Ok(None) => {
+          self.done = true;
+          return None;
         }
+        Err(err) => err,
+      };
+      let fatal = match err {
+        MwsError::Csv(ref err) => err.is_io_error(),
+        _ => false,
+      };
+      if fatal {
+        self.done = true;
+        return Some(Err(err));
+      }
+      if !self.skip_malformed {
+        return Some(Err(err));
       }
-      items.push(T::from_tdff_row(&row_container)?);
+      self.errors.push(err);
     }
-    Ok(items)
+    None
+  }
+}
+
+/// A field of a report which cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TdffError {
+  /// The position of the row, the header being record 0. Set when the row is read by a
+  /// `TdffParser`.
+  pub position: Option<Position>,
+  /// The header of the column.
+  pub header: String,
+  /// The value of the field, as found in the report.
+  pub value: String,
+  /// The type the field is parsed to.
+  pub target_type: &'static str,
+  pub message: String,
+}
+
+impl fmt::Display for TdffError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "field '{}'", self.header)?;
+    if let Some(ref position) = self.position {
+      write!(
+        f,
+        " of record {} (line {}, byte {})",
+        position.record(),
+        position.line(),
+        position.byte()
+      )?;
+    }
+    write!(
+      f,
+      ": cannot parse '{}' as {}: {}",
+      self.value, self.target_type, self.message
+    )
   }
 }
 
@@ -57,6 +224,22 @@ pub trait FromTdffField: Sized {
   fn parse_tdff_field(key: &str, v: &str) -> MwsResult<Self>;
 }
 
+/// Parses the field `key` of a row, as `#[derive(FromTdffRow)]` does.
+///
+/// Errors are `MwsError::Tdff`.
+pub fn parse_field<T: FromTdffField>(key: &str, v: &str) -> MwsResult<T> {
+  T::parse_tdff_field(key, v).map_err(|err| match err {
+    MwsError::Tdff(err) => MwsError::Tdff(err),
+    err => MwsError::Tdff(TdffError {
+      position: None,
+      header: key.to_string(),
+      value: v.to_string(),
+      target_type: type_name::<T>(),
+      message: err.to_string(),
+    }),
+  })
+}
+
 impl<T, Err> FromTdffField for T
 where
   T: ::std::str::FromStr<Err = Err> + Default,
@@ -65,9 +248,14 @@ where
   fn parse_tdff_field(key: &str, v: &str) -> MwsResult<Self> {
     let trimmed = v.trim();
     if !trimmed.is_empty() {
-      trimmed.parse().map_err(|err| MwsError::ParseString {
-        what: key.to_string(),
-        message: format!("{}: '{}'", err, v),
+      trimmed.parse().map_err(|err: Err| {
+        MwsError::Tdff(TdffError {
+          position: None,
+          header: key.to_string(),
+          value: v.to_string(),
+          target_type: type_name::<T>(),
+          message: err.to_string(),
+        })
       })
     } else {
       Ok(T::default())
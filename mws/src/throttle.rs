@@ -0,0 +1,424 @@
+//! Client side throttling based on the MWS request quotas
+//!
+//! [Documentation](http://docs.developer.amazonservices.com/en_US/dev_guide/DG_Throttling.html)
+//!
+//! Every operation has a maximum request quota and a restore rate. `Throttler` keeps a token bucket
+//! per (section path, quota group, seller) and makes callers wait until a request is available.
+//! Clones of a `Throttler` share their buckets, so threads working for the same seller cooperate.
+
+use crate::client::ResponseMeta;
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Maximum request quota and restore rate of an operation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+  /// The number of requests that can be submitted at once.
+  pub max_quota: u32,
+  /// The time it takes to restore one request.
+  pub restore_interval: Duration,
+}
+
+impl Quota {
+  /// A quota restoring `requests` requests every `interval`.
+  pub fn new(max_quota: u32, requests: u32, interval: Duration) -> Self {
+    Quota {
+      max_quota,
+      restore_interval: interval / requests.max(1),
+    }
+  }
+
+  pub fn per_second(max_quota: u32, requests: u32) -> Self {
+    Quota::new(max_quota, requests, Duration::from_secs(1))
+  }
+}
+
+/// Documented quota of an operation
+#[derive(Debug)]
+pub struct OperationQuota {
+  pub path: &'static str,
+  pub action: &'static str,
+  /// Operations with the same group share a quota, e.g. `ListOrders` and `ListOrdersByNextToken`.
+  pub group: &'static str,
+  pub quota: Quota,
+}
+
+lazy_static! {
+  /// Documented quotas of the operations implemented by this crate.
+  pub static ref QUOTAS: Vec<OperationQuota> = {
+    macro_rules! item {
+      ($path:expr, $action:expr, $group:expr, $max:expr, $requests:expr, $secs:expr) => {
+        OperationQuota {
+          path: $path,
+          action: $action,
+          group: $group,
+          quota: Quota::new($max, $requests, Duration::from_secs($secs)),
+        }
+      };
+      ($path:expr, $action:expr, $max:expr, $requests:expr, $secs:expr) => {
+        item!($path, $action, $action, $max, $requests, $secs)
+      };
+    }
+
+    const ORDERS: &str = "/Orders/2013-09-01";
+    const REPORTS: &str = "/";
+    const FEEDS: &str = "/";
+    const INVENTORY: &str = "/FulfillmentInventory/2010-10-01";
+    const INBOUND: &str = "/FulfillmentInboundShipment/2010-10-01";
+    const OUTBOUND: &str = "/FulfillmentOutboundShipment/2010-10-01";
+    const PRODUCTS: &str = "/Products/2011-10-01";
+    const MERCHANT_FULFILLMENT: &str = "/MerchantFulfillment/2015-06-01";
+    const SUBSCRIPTIONS: &str = "/Subscriptions/2013-07-01";
+
+    vec![
+      item!(ORDERS, "ListOrders", 6, 1, 60),
+      item!(ORDERS, "ListOrdersByNextToken", "ListOrders", 6, 1, 60),
+      item!(ORDERS, "ListOrderItems", 30, 1, 2),
+      item!(ORDERS, "ListOrderItemsByNextToken", "ListOrderItems", 30, 1, 2),
+      item!(ORDERS, "GetServiceStatus", 2, 1, 300),
+
+      item!(REPORTS, "RequestReport", 15, 1, 60),
+      item!(REPORTS, "GetReportRequestList", 10, 1, 45),
+      item!(REPORTS, "GetReportRequestListByNextToken", 30, 1, 2),
+      item!(REPORTS, "GetReportList", 10, 1, 60),
+      item!(REPORTS, "GetReportListByNextToken", 30, 1, 2),
+      item!(REPORTS, "GetReportCount", 10, 1, 45),
+      item!(REPORTS, "GetReportRequestCount", 10, 1, 45),
+      item!(REPORTS, "CancelReportRequests", 10, 1, 45),
+      item!(REPORTS, "GetReport", 15, 1, 60),
+      item!(REPORTS, "ManageReportSchedule", 10, 1, 45),
+      item!(REPORTS, "GetReportScheduleList", 10, 1, 45),
+      item!(REPORTS, "GetReportScheduleCount", 10, 1, 45),
+      item!(REPORTS, "UpdateReportAcknowledgements", 10, 1, 45),
+
+      item!(FEEDS, "SubmitFeed", 15, 1, 120),
+      item!(FEEDS, "GetFeedSubmissionList", 10, 1, 45),
+      item!(FEEDS, "GetFeedSubmissionListByNextToken", 30, 1, 2),
+      item!(FEEDS, "GetFeedSubmissionResult", 15, 1, 60),
+
+      item!(INVENTORY, "ListInventorySupply", 30, 2, 1),
+      item!(INVENTORY, "ListInventorySupplyByNextToken", "ListInventorySupply", 30, 2, 1),
+
+      item!(INBOUND, "ListInboundShipments", 30, 2, 1),
+      item!(INBOUND, "ListInboundShipmentsByNextToken", "ListInboundShipments", 30, 2, 1),
+      item!(INBOUND, "ListInboundShipmentItems", 30, 2, 1),
+      item!(INBOUND, "ListInboundShipmentItemsByNextToken", "ListInboundShipmentItems", 30, 2, 1),
+
+      item!(OUTBOUND, "ListAllFulfillmentOrders", 30, 2, 1),
+      item!(OUTBOUND, "ListAllFulfillmentOrdersByNextToken", "ListAllFulfillmentOrders", 30, 2, 1),
+      item!(OUTBOUND, "GetFulfillmentOrder", 30, 2, 1),
+      item!(OUTBOUND, "GetPackageTrackingDetails", 30, 2, 1),
+      item!(OUTBOUND, "GetFulfillmentPreview", 30, 2, 1),
+      item!(OUTBOUND, "CreateFulfillmentOrder", 30, 2, 1),
+      item!(OUTBOUND, "CancelFulfillmentOrder", 30, 2, 1),
+
+      item!(PRODUCTS, "GetLowestPricedOffersForSKU", 10, 5, 1),
+      item!(PRODUCTS, "GetMyPriceForASIN", 10, 5, 1),
+      item!(PRODUCTS, "GetMyPriceForSKU", 10, 5, 1),
+      item!(PRODUCTS, "GetMatchingProductForId", 20, 5, 1),
+
+      item!(MERCHANT_FULFILLMENT, "GetEligibleShippingServices", 10, 5, 1),
+      item!(MERCHANT_FULFILLMENT, "CreateShipment", 10, 5, 1),
+      item!(MERCHANT_FULFILLMENT, "GetShipment", 10, 5, 1),
+      item!(MERCHANT_FULFILLMENT, "CancelShipment", 10, 5, 1),
+
+      item!(SUBSCRIPTIONS, "RegisterDestination", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "DeregisterDestination", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "ListRegisteredDestinations", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "SendTestNotificationToDestination", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "CreateSubscription", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "GetSubscription", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "ListSubscriptions", 25, 5, 1),
+      item!(SUBSCRIPTIONS, "UpdateSubscription", 25, 5, 1),
+    ]
+  };
+}
+
+/// Returns the documented quota of an operation.
+pub fn get_quota(path: &str, action: &str) -> Option<&'static OperationQuota> {
+  QUOTAS.iter().find(|q| q.path == path && q.action == action)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+  path: String,
+  group: String,
+  seller_id: String,
+}
+
+#[derive(Debug)]
+struct Bucket {
+  quota: Quota,
+  tokens: f64,
+  updated_at: Instant,
+  /// Set when MWS reports that the hourly quota is used up.
+  blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+  fn new(quota: Quota) -> Self {
+    Bucket {
+      quota,
+      tokens: quota.max_quota as f64,
+      updated_at: Instant::now(),
+      blocked_until: None,
+    }
+  }
+
+  fn refill(&mut self, now: Instant) {
+    let elapsed = now.duration_since(self.updated_at);
+    let interval = duration_secs(self.quota.restore_interval);
+    if interval > 0.0 {
+      self.tokens += duration_secs(elapsed) / interval;
+    } else {
+      self.tokens = self.quota.max_quota as f64;
+    }
+    self.tokens = self.tokens.min(self.quota.max_quota as f64);
+    self.updated_at = now;
+  }
+
+  // Takes a request if one is available, otherwise returns how long to wait.
+  fn reserve(&mut self, now: Instant) -> Option<Duration> {
+    if let Some(until) = self.blocked_until {
+      if until > now {
+        return Some(until - now);
+      }
+      self.blocked_until = None;
+    }
+    self.refill(now);
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      None
+    } else {
+      let secs = (1.0 - self.tokens) * duration_secs(self.quota.restore_interval);
+      Some(Duration::from_millis((secs * 1000.0).ceil() as u64))
+    }
+  }
+}
+
+fn duration_secs(d: Duration) -> f64 {
+  d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1_000_000_000.0
+}
+
+#[derive(Debug, Default)]
+struct ThrottlerState {
+  buckets: HashMap<BucketKey, Bucket>,
+  quotas: HashMap<(String, String), Quota>,
+}
+
+/// Token bucket throttler shared by all clones
+///
+/// Operations without a known quota are not throttled.
+#[derive(Debug, Clone, Default)]
+pub struct Throttler {
+  state: Arc<Mutex<ThrottlerState>>,
+}
+
+impl Throttler {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Overrides the quota of an operation. The operation no longer shares its quota group.
+  pub fn set_quota(&self, path: &str, action: &str, quota: Quota) -> &Self {
+    let mut state = self.lock();
+    state
+      .quotas
+      .insert((path.to_owned(), action.to_owned()), quota);
+    state
+      .buckets
+      .retain(|key, _| !(key.path == path && key.group == action));
+    self
+  }
+
+  /// Blocks the current thread until a request to `action` is available.
+  pub fn acquire(&self, path: &str, action: &str, seller_id: &str) {
+    while let Some(wait) = self.reserve(path, action, seller_id) {
+      thread::sleep(wait);
+    }
+  }
+
+  /// Takes a request to `action` if one is available,
+  /// otherwise returns how long to wait before trying again.
+  pub fn reserve(&self, path: &str, action: &str, seller_id: &str) -> Option<Duration> {
+    let mut state = self.lock();
+    let (key, quota) = state.bucket_key(path, action, seller_id)?;
+    state
+      .buckets
+      .entry(key)
+      .or_insert_with(|| Bucket::new(quota))
+      .reserve(Instant::now())
+  }
+
+  /// Resynchronizes with the `x-mws-quota-*` headers of a response.
+  ///
+  /// The remaining request count is capped to `x-mws-quota-remaining`, and once it reaches zero
+  /// requests wait until `x-mws-quota-resetsOn`.
+  pub fn update_from_headers(
+    &self,
+    path: &str,
+    action: &str,
+    seller_id: &str,
+    headers: &HeaderMap,
+  ) {
+    self.update_from_meta(path, action, seller_id, &ResponseMeta::from_headers(headers))
+  }
+
+  /// Same as `update_from_headers`, with the headers already parsed.
+  pub fn update_from_meta(&self, path: &str, action: &str, seller_id: &str, meta: &ResponseMeta) {
+    let remaining = match meta.quota_remaining {
+      Some(v) => v,
+      None => return,
+    };
+
+    let mut state = self.lock();
+    let (key, quota) = match state.bucket_key(path, action, seller_id) {
+      Some(v) => v,
+      None => return,
+    };
+    let bucket = state
+      .buckets
+      .entry(key)
+      .or_insert_with(|| Bucket::new(quota));
+    let now = Instant::now();
+    bucket.refill(now);
+    bucket.tokens = bucket.tokens.min(remaining);
+    if let Some(max) = meta.quota_max {
+      bucket.tokens = bucket.tokens.min(max);
+    }
+    if remaining < 1.0 {
+      if let Some(wait) = meta
+        .quota_resets_on
+        .and_then(|t| t.signed_duration_since(Utc::now()).to_std().ok())
+      {
+        bucket.blocked_until = Some(now + wait);
+      }
+    }
+  }
+
+  /// Empties the bucket of `action` after MWS rejected a request with `RequestThrottled`.
+  pub fn throttled(&self, path: &str, action: &str, seller_id: &str) {
+    let mut state = self.lock();
+    if let Some((key, quota)) = state.bucket_key(path, action, seller_id) {
+      let bucket = state
+        .buckets
+        .entry(key)
+        .or_insert_with(|| Bucket::new(quota));
+      bucket.refill(Instant::now());
+      bucket.tokens = 0.0;
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<ThrottlerState> {
+    self
+      .state
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+impl ThrottlerState {
+  fn bucket_key(&self, path: &str, action: &str, seller_id: &str) -> Option<(BucketKey, Quota)> {
+    let (group, quota) = match self.quotas.get(&(path.to_owned(), action.to_owned())) {
+      Some(quota) => (action, *quota),
+      None => get_quota(path, action).map(|q| (q.group, q.quota))?,
+    };
+    Some((
+      BucketKey {
+        path: path.to_owned(),
+        group: group.to_owned(),
+        seller_id: seller_id.to_owned(),
+      },
+      quota,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use reqwest::header::HeaderValue;
+
+  #[test]
+  fn test_quotas() {
+    let q = get_quota("/Orders/2013-09-01", "ListOrdersByNextToken").unwrap();
+    assert_eq!(q.group, "ListOrders");
+    assert_eq!(q.quota.max_quota, 6);
+    assert_eq!(q.quota.restore_interval, Duration::from_secs(60));
+    let q = get_quota("/Products/2011-10-01", "GetMyPriceForASIN").unwrap();
+    assert_eq!(q.quota.restore_interval, Duration::from_millis(200));
+    assert!(get_quota("/Orders/2013-09-01", "Fake").is_none());
+    for action in &["GetReportCount", "GetReportRequestCount", "CancelReportRequests"] {
+      let q = get_quota("/", action).unwrap();
+      assert_eq!(q.group, *action);
+      assert_eq!(q.quota.max_quota, 10);
+      assert_eq!(q.quota.restore_interval, Duration::from_secs(45));
+    }
+  }
+
+  #[test]
+  fn test_reserve() {
+    let t = Throttler::new();
+    for _ in 0..6 {
+      assert_eq!(t.reserve("/Orders/2013-09-01", "ListOrders", "A"), None);
+    }
+    // quota is shared with ListOrdersByNextToken
+    let wait = t
+      .reserve("/Orders/2013-09-01", "ListOrdersByNextToken", "A")
+      .unwrap();
+    assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+    // but not with other sellers
+    assert_eq!(t.reserve("/Orders/2013-09-01", "ListOrders", "B"), None);
+    // unknown operations are not throttled
+    for _ in 0..100 {
+      assert_eq!(t.reserve("/Orders/2013-09-01", "Fake", "A"), None);
+    }
+
+    let shared = t.clone();
+    shared.set_quota(
+      "/Orders/2013-09-01",
+      "ListOrders",
+      Quota::new(1, 1, Duration::from_millis(10)),
+    );
+    assert_eq!(t.reserve("/Orders/2013-09-01", "ListOrders", "A"), None);
+    assert!(t.reserve("/Orders/2013-09-01", "ListOrders", "A").is_some());
+    t.acquire("/Orders/2013-09-01", "ListOrders", "A");
+  }
+
+  #[test]
+  fn test_update_from_headers() {
+    let t = Throttler::new();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-mws-quota-max", HeaderValue::from_static("200.0"));
+    headers.insert("x-mws-quota-remaining", HeaderValue::from_static("1.0"));
+    t.update_from_headers("/Orders/2013-09-01", "ListOrderItems", "A", &headers);
+    assert_eq!(t.reserve("/Orders/2013-09-01", "ListOrderItems", "A"), None);
+    assert!(t
+      .reserve("/Orders/2013-09-01", "ListOrderItems", "A")
+      .is_some());
+
+    let resets_on = (Utc::now() + ::chrono::Duration::minutes(10)).to_rfc3339();
+    headers.insert("x-mws-quota-remaining", HeaderValue::from_static("0.0"));
+    headers.insert(
+      "x-mws-quota-resetsOn",
+      HeaderValue::from_str(&resets_on).unwrap(),
+    );
+    t.update_from_headers("/Orders/2013-09-01", "ListOrders", "A", &headers);
+    let wait = t.reserve("/Orders/2013-09-01", "ListOrders", "A").unwrap();
+    assert!(wait > Duration::from_secs(9 * 60));
+  }
+
+  #[test]
+  fn test_throttled() {
+    let t = Throttler::new();
+    t.throttled("/Products/2011-10-01", "GetMyPriceForSKU", "A");
+    let wait = t
+      .reserve("/Products/2011-10-01", "GetMyPriceForSKU", "A")
+      .unwrap();
+    assert!(wait <= Duration::from_millis(200));
+  }
+}
@@ -0,0 +1,283 @@
+//! HTTP transports used by `client::Client`
+//!
+//! `ReqwestTransport` is the default transport. `MockTransport` serves canned responses from
+//! memory, so code built on top of `Client` can be tested without MWS credentials.
+
+use crate::result::{MwsError, MwsResult};
+pub use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+pub use reqwest::{Method, StatusCode};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, Mutex};
+
+/// Body of a signed request
+pub enum RequestBody {
+  Empty,
+  Bytes(Vec<u8>),
+  /// `application/x-www-form-urlencoded` pairs
+  Form(Vec<(String, String)>),
+  Reader(Box<dyn Read + Send>),
+}
+
+impl fmt::Debug for RequestBody {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      RequestBody::Empty => write!(f, "Empty"),
+      RequestBody::Bytes(ref bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+      RequestBody::Form(ref pairs) => f.debug_tuple("Form").field(pairs).finish(),
+      RequestBody::Reader(_) => write!(f, "Reader"),
+    }
+  }
+}
+
+/// A signed request ready to be sent
+#[derive(Debug)]
+pub struct Request {
+  pub method: Method,
+  pub url: String,
+  pub headers: HeaderMap,
+  pub body: RequestBody,
+}
+
+impl Request {
+  pub fn new(method: Method, url: String) -> Self {
+    Request {
+      method,
+      url,
+      headers: HeaderMap::new(),
+      body: RequestBody::Empty,
+    }
+  }
+}
+
+/// Status, headers and a body stream returned by a transport
+pub struct Response {
+  status: StatusCode,
+  headers: HeaderMap,
+  body: Box<dyn Read + Send>,
+}
+
+impl Response {
+  pub fn new<R>(status: StatusCode, headers: HeaderMap, body: R) -> Self
+  where
+    R: Read + Send + 'static,
+  {
+    Response {
+      status,
+      headers,
+      body: Box::new(body),
+    }
+  }
+
+  pub fn status(&self) -> StatusCode {
+    self.status
+  }
+
+  pub fn headers(&self) -> &HeaderMap {
+    &self.headers
+  }
+}
+
+impl Read for Response {
+  fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {
+    self.body.read(buf)
+  }
+}
+
+impl fmt::Debug for Response {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Response")
+      .field("status", &self.status)
+      .field("headers", &self.headers)
+      .finish()
+  }
+}
+
+/// Sends signed requests and returns the raw responses
+///
+/// Error statuses must be returned as `Ok`, `Client` turns them into `MwsError::ErrorResponse`.
+pub trait Transport: fmt::Debug + Send + Sync {
+  fn send(&self, request: Request) -> MwsResult<Response>;
+}
+
+/// Transport backed by `reqwest::blocking::Client`
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+  http_client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+  pub fn new(http_client: reqwest::blocking::Client) -> Self {
+    ReqwestTransport { http_client }
+  }
+}
+
+impl Transport for ReqwestTransport {
+  fn send(&self, request: Request) -> MwsResult<Response> {
+    let builder = self
+      .http_client
+      .request(request.method, &request.url)
+      .headers(request.headers);
+    let builder = match request.body {
+      RequestBody::Empty => builder,
+      RequestBody::Bytes(bytes) => builder.body(bytes),
+      RequestBody::Form(pairs) => builder.form(&pairs),
+      RequestBody::Reader(reader) => builder.body(reqwest::blocking::Body::new(reader)),
+    };
+    let resp = builder.send()?;
+    Ok(Response {
+      status: resp.status(),
+      headers: resp.headers().clone(),
+      body: Box::new(resp),
+    })
+  }
+}
+
+/// A canned response served by `MockTransport`
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+  pub status: StatusCode,
+  pub headers: HeaderMap,
+  pub body: Vec<u8>,
+}
+
+impl MockResponse {
+  pub fn new<B: Into<Vec<u8>>>(status: StatusCode, body: B) -> Self {
+    MockResponse {
+      status,
+      headers: HeaderMap::new(),
+      body: body.into(),
+    }
+  }
+
+  /// A `200 OK` response with a `text/xml` body
+  pub fn xml<B: Into<Vec<u8>>>(body: B) -> Self {
+    MockResponse::new(StatusCode::OK, body).header("Content-Type", "text/xml")
+  }
+
+  pub fn header(mut self, name: &'static str, value: &str) -> Self {
+    if let Ok(value) = HeaderValue::from_str(value) {
+      self.headers.insert(name, value);
+    }
+    self
+  }
+}
+
+/// A request received by `MockTransport`
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+  pub method: Method,
+  pub url: String,
+  /// Query string and form parameters
+  pub params: Vec<(String, String)>,
+  pub headers: HeaderMap,
+  pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+  pub fn param(&self, key: &str) -> Option<&str> {
+    self
+      .params
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, v)| v.as_ref())
+  }
+
+  pub fn action(&self) -> Option<&str> {
+    self.param("Action")
+  }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+  responses: HashMap<String, VecDeque<MockResponse>>,
+  requests: Vec<RecordedRequest>,
+}
+
+/// In-memory transport which serves canned responses keyed by the `Action` parameter
+///
+/// Responses queued for an action are served in order. Clones share the same state,
+/// so a clone can be kept to inspect the recorded requests.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+  state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Queues a response for `action`
+  pub fn add_response(&self, action: &str, response: MockResponse) -> &Self {
+    self
+      .lock()
+      .responses
+      .entry(action.to_owned())
+      .or_insert_with(VecDeque::new)
+      .push_back(response);
+    self
+  }
+
+  /// Queues a `200 OK` XML response for `action`
+  pub fn add_xml(&self, action: &str, body: &str) -> &Self {
+    self.add_response(action, MockResponse::xml(body))
+  }
+
+  /// Returns all requests received so far
+  pub fn requests(&self) -> Vec<RecordedRequest> {
+    self.lock().requests.clone()
+  }
+
+  fn lock(&self) -> ::std::sync::MutexGuard<MockState> {
+    self
+      .state
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+impl Transport for MockTransport {
+  fn send(&self, request: Request) -> MwsResult<Response> {
+    let mut params: Vec<(String, String)> = url::Url::parse(&request.url)
+      .map_err(|err| MwsError::Msg(format!("invalid url '{}': {}", request.url, err)))?
+      .query_pairs()
+      .into_owned()
+      .collect();
+    let body = match request.body {
+      RequestBody::Empty => vec![],
+      RequestBody::Bytes(bytes) => bytes,
+      RequestBody::Form(pairs) => {
+        params.extend(pairs);
+        vec![]
+      }
+      RequestBody::Reader(mut reader) => {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        bytes
+      }
+    };
+    let recorded = RecordedRequest {
+      method: request.method,
+      url: request.url,
+      params,
+      headers: request.headers,
+      body,
+    };
+
+    let mut state = self.lock();
+    let action = recorded.action().unwrap_or("").to_owned();
+    state.requests.push(recorded);
+    let response = state
+      .responses
+      .get_mut(&action)
+      .and_then(VecDeque::pop_front)
+      .ok_or_else(|| MwsError::Msg(format!("no mock response for action '{}'", action)))?;
+    Ok(Response {
+      status: response.status,
+      headers: response.headers,
+      body: Box::new(Cursor::new(response.body)),
+    })
+  }
+}